@@ -4,6 +4,7 @@
 #include <cstring>
 #include <functional>
 #include <memory>
+#include <stdexcept>
 #include <string>
 #include <vector>
 #include <utility>
@@ -17,9 +18,12 @@ extern "C" {
   void parattice_lattice_free(void* parattice);
   std::size_t parattice_lattice_get_size(void const* lattice);
   std::size_t parattice_lattice_get_required_capacity(void const* lattice);
+  // returns null if the data is broken; see parattice_lattice_try_new_from_bytes for the reason
   void* parattice_lattice_new_from_bytes(std::uint8_t const* data, const std::size_t length);
+  void* parattice_lattice_try_new_from_bytes(std::uint8_t const* data, const std::size_t length, int* error_code);
   std::uint8_t* parattice_lattice_to_bytes(void const* lattice, std::size_t* length);
   char* parattice_lattice_dump_dot(void const* lattice, bool is_numbered);
+  char* parattice_lattice_dump_graphml(void const* lattice);
   std::size_t parattice_lattice_get_trunk_span(void const* lattice, const char* const* edge_labels, std::size_t const* node_ids, std::size_t length, const char** new_edge_labels, std::size_t* new_edge_label_length, std::size_t* new_node_ids);
   void parattice_lattice_get_trunk_spans(void const* lattice, std::size_t* trunk_lefts, std::size_t* trunk_rights);
   std::size_t parattice_lattice_dump_for_search_index(void const* lattice, const char** texts, std::size_t* text_lengths, std::size_t* offset_starts, std::size_t* offset_ends, std::size_t* increments, std::size_t* lengths);
@@ -35,6 +39,42 @@ extern "C" {
 
 namespace parattice {
 
+  class decode_error : public std::runtime_error {
+
+    public:
+
+    explicit decode_error(int code)
+      : std::runtime_error(message(code)), code_(code) {}
+
+    int code() const {
+      return code_;
+    }
+
+    private:
+
+    static const char* message(int code) {
+      switch (code) {
+        case 1: return "the data is truncated";
+        case 2: return "a label is not valid UTF-8";
+        case 3: return "an edge refers to a node out of range";
+        case 4: return "the number of nodes differs from the header";
+        case 5: return "the trunk doesn't reach the last node";
+        case 6: return "unsupported format version";
+        case 7: return "unsupported optional sections";
+        case 8: return "checksum mismatch";
+        case 9: return "broken optional section";
+        case 10: return "too long integer";
+        case 11: return "label out of range";
+        case 12: return "main edge out of range";
+        case 13: return "trunk mismatch";
+        default: return "unknown error";
+      }
+    }
+
+    int code_;
+
+  };
+
   struct search_index_node {
     std::string text;
     std::size_t offset_start;
@@ -80,6 +120,13 @@ namespace parattice {
       return cpp_str;
     }
 
+    std::string dump_graphml() const {
+      char* s = parattice_lattice_dump_graphml(ptr_.get());
+      std::string cpp_str(s);
+      parattice_free_string(s);
+      return cpp_str;
+    }
+
     std::vector<std::pair<std::string, std::size_t>> get_trunk_span(const std::vector<std::pair<std::string, std::size_t>>& path) const {
       std::vector<const char*> edge_labels;
       std::vector<std::size_t> node_ids;
@@ -149,22 +196,33 @@ namespace parattice {
 
     static Lattice from_bytes(const std::vector<std::uint8_t>& data) {
       Lattice lattice(data);
-      lattice.ptr_ = std::unique_ptr<void, std::function<void(void*)>>(parattice_lattice_new_from_bytes(lattice.data_.data(), lattice.data_.size()), parattice_lattice_free);
+      lattice.ptr_ = std::unique_ptr<void, std::function<void(void*)>>(new_from_bytes(lattice.data_.data(), lattice.data_.size()), parattice_lattice_free);
       return lattice;
     }
 
     static Lattice from_bytes(std::vector<std::uint8_t>&& data) {
       Lattice lattice(std::forward<std::vector<std::uint8_t>>(data));
-      lattice.ptr_ = std::unique_ptr<void, std::function<void(void*)>>(parattice_lattice_new_from_bytes(lattice.data_.data(), lattice.data_.size()), parattice_lattice_free);
+      lattice.ptr_ = std::unique_ptr<void, std::function<void(void*)>>(new_from_bytes(lattice.data_.data(), lattice.data_.size()), parattice_lattice_free);
       return lattice;
     }
 
     static Lattice from_bytes(std::uint8_t const* data, std::size_t size) {
       Lattice lattice;
-      lattice.ptr_ = std::unique_ptr<void, std::function<void(void*)>>(parattice_lattice_new_from_bytes(data, size), parattice_lattice_free);
+      lattice.ptr_ = std::unique_ptr<void, std::function<void(void*)>>(new_from_bytes(data, size), parattice_lattice_free);
       return lattice;
     }
 
+    private:
+
+    static void* new_from_bytes(std::uint8_t const* data, std::size_t size) {
+      int error_code;
+      void* ptr = parattice_lattice_try_new_from_bytes(data, size, &error_code);
+      if (ptr == nullptr) {
+        throw decode_error(error_code);
+      }
+      return ptr;
+    }
+
     public:
 
     std::unique_ptr<void, std::function<void(void*)>> ptr_;
@@ -62,6 +62,14 @@ namespace parattice {
     const std::vector<std::uint8_t> bytes = lattice.to_bytes();
     const Lattice deserialized_lattice = Lattice::from_bytes(bytes);
     EXPECT_EQ(lattice.dump_dot(true), deserialized_lattice.dump_dot(true));
+    EXPECT_EQ(lattice.dump_graphml(), deserialized_lattice.dump_graphml());
+    const std::vector<std::uint8_t> truncated_bytes(bytes.begin(), bytes.end() - 1);
+    try {
+      Lattice::from_bytes(truncated_bytes);
+      FAIL();
+    } catch (const decode_error& e) {
+      EXPECT_EQ(1, e.code());
+    }
   }
 
   TEST_F(PaRatticeTest, GetTrunkSpanTest) {
@@ -24,7 +24,13 @@ namespace parattice {
 
       JNIEXPORT jlong JNICALL Java_parattice_Lattice_jniNewFromBytes(JNIEnv* env, jclass, jbyteArray bytes) {
         JNIByteArrayAccess bytes_access(env, bytes);
-        return to_jlong(new JNILatticeWrapper(Lattice::from_bytes(reinterpret_cast<std::uint8_t*>(bytes_access.data()), bytes_access.size()), std::move(bytes_access)));
+        try {
+          Lattice lattice = Lattice::from_bytes(reinterpret_cast<std::uint8_t*>(bytes_access.data()), bytes_access.size());
+          return to_jlong(new JNILatticeWrapper(std::move(lattice), std::move(bytes_access)));
+        } catch (const decode_error& e) {
+          env->ThrowNew(env->FindClass("parattice/LatticeDecodeException"), e.what());
+          return 0;
+        }
       }
 
       JNIEXPORT jbyteArray JNICALL Java_parattice_Lattice_jniToBytes(JNIEnv* env, jobject, jlong handle) {
@@ -39,6 +45,10 @@ namespace parattice {
         return env->NewStringUTF(to_object<JNILatticeWrapper>(handle).lattice_.dump_dot(is_numbered).c_str());
       }
 
+      JNIEXPORT jstring JNICALL Java_parattice_Lattice_jniDumpGraphml(JNIEnv* env, jobject, jlong handle) {
+        return env->NewStringUTF(to_object<JNILatticeWrapper>(handle).lattice_.dump_graphml().c_str());
+      }
+
       JNIEXPORT jint JNICALL Java_parattice_Lattice_jniGetTrunkSpan(JNIEnv* env, jobject, jlong handle, jobjectArray path_string, jintArray path_node_id, jobjectArray result_string, jintArray result_node_id) {
         const JNIObjectArrayAccess path_string_access(env, path_string);
         const JNIIntArrayAccess path_node_id_access(env, path_node_id);
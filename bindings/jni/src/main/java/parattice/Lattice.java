@@ -52,7 +52,7 @@ public class Lattice implements Externalizable, AutoCloseable {
     return jniToBytes(this.handle);
   }
 
-  public static Lattice fromBytes(byte[] data) {
+  public static Lattice fromBytes(byte[] data) throws LatticeDecodeException {
     return new Lattice(jniNewFromBytes(data));
   }
 
@@ -80,7 +80,11 @@ public class Lattice implements Externalizable, AutoCloseable {
       if (sizeSum != cap) {
         throw new IOException("size mismatch: read " + sizeSum + " bytes, expected " + cap + " bytes");
       }
-      this.handle = jniNewFromBytes(data);
+      try {
+        this.handle = jniNewFromBytes(data);
+      } catch (LatticeDecodeException e) {
+        throw new IOException(e);
+      }
     } else {
       throw new IllegalStateException();
     }
@@ -93,6 +97,13 @@ public class Lattice implements Externalizable, AutoCloseable {
     return jniDumpDot(this.handle, isNumbered);
   }
 
+  public String dumpGraphml() {
+    if (this.handle == 0) {
+      throw new IllegalStateException();
+    }
+    return jniDumpGraphml(this.handle);
+  }
+
   public List<Pair<String, Integer>> getTrunkSpan(List<Pair<String, Integer>> path) {
     if (this.handle == 0) {
       throw new IllegalStateException();
@@ -153,6 +164,7 @@ public class Lattice implements Externalizable, AutoCloseable {
   private static native long jniNewFromBytes(byte[] data);
   private native byte[] jniToBytes(long handle);
   private native String jniDumpDot(long handle, boolean isNumbered);
+  private native String jniDumpGraphml(long handle);
   private native int jniGetTrunkSpan(long handle, String[] pathString, int[] pathNodeId, String[] resultString, int[] resultNodeId);
   private native void jniGetTrunkSpans(long handle, int[] leftTrunks, int[] rightTrunks);
   private native int jniDumpForSearchIndex(long handle, String[] texts, int[] offsetStarts, int[] offsetEnds, int[] increments, int[] lengths);
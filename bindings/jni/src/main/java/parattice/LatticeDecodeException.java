@@ -0,0 +1,10 @@
+package parattice;
+
+public class LatticeDecodeException extends IllegalArgumentException {
+
+  private static final long serialVersionUID = 1L;
+
+  public LatticeDecodeException(String message) {
+    super(message);
+  }
+}
@@ -1,6 +1,7 @@
 package parattice;
 
 import static org.junit.jupiter.api.Assertions.assertEquals;
+import static org.junit.jupiter.api.Assertions.assertThrows;
 
 import java.io.ByteArrayInputStream;
 import java.io.ByteArrayOutputStream;
@@ -117,11 +118,23 @@ public class PaRatticeTest {
         ObjectInputStream ois = new ObjectInputStream(ins);
         try (Lattice latticeDeserialized = (Lattice) ois.readObject()) {
           assertEquals(lattice.dumpDot(true), latticeDeserialized.dumpDot(true));
+          assertEquals(lattice.dumpGraphml(), latticeDeserialized.dumpGraphml());
         }
       }
     }
   }
 
+  @Test
+  public void fromBrokenBytes() {
+    try (PaRattice parattice = new PaRattice(paradict)) {
+      String[] words = {"造血", "幹", "細胞", "移植"};
+      try (Lattice lattice = parattice.getLattice(words, true, 10)) {
+        byte[] bytes = lattice.toBytes();
+        assertThrows(LatticeDecodeException.class, () -> Lattice.fromBytes(Arrays.copyOf(bytes, bytes.length - 1)));
+      }
+    }
+  }
+
   @Test
   public void getTrunkSpan() {
     try (PaRattice parattice = new PaRattice(paradict)) {
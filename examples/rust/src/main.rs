@@ -1,4 +1,5 @@
 use parattice::Lattice;
+use parattice::svg::SvgOptions;
 use parattice::LatticeKMP;
 use parattice::PaRattice;
 
@@ -39,6 +40,11 @@ fn main() -> Result<(), io::Error> {
     let mut file = File::create(&path)?;
     file.write_all(lattice.dump_dot(true).as_bytes())?;
 
+    // render a generated lattice without Graphviz
+    let path = Path::new("paraphrase-lattice.svg");
+    let mut file = File::create(&path)?;
+    file.write_all(lattice.dump_svg(&SvgOptions::default()).as_bytes())?;
+
     // serialization & deserialization
     let bytes = lattice.to_bytes();
     let new_lattice = Lattice::new_from_bytes(&bytes);
@@ -0,0 +1,220 @@
+use crate::lattice::Lattice;
+use crate::lattice::SearchIndexNode;
+
+use arrow_array::builder::ArrayBuilder;
+use arrow_array::builder::StringBuilder;
+use arrow_array::builder::StringDictionaryBuilder;
+use arrow_array::builder::UInt64Builder;
+use arrow_array::types::UInt32Type;
+use arrow_array::ArrayRef;
+use arrow_array::RecordBatch;
+use arrow_schema::DataType;
+use arrow_schema::Field;
+use arrow_schema::Schema;
+use arrow_schema::SchemaRef;
+
+#[cfg(feature = "parquet")]
+use parquet::arrow::ArrowWriter;
+#[cfg(feature = "parquet")]
+use parquet::errors::ParquetError;
+
+#[cfg(feature = "parquet")]
+use std::io::Write;
+use std::sync::Arc;
+
+/// The default number of rows of a record batch written by `SearchIndexWriter`.
+pub const DEFAULT_BATCH_SIZE: usize = 8192;
+
+/// Returns the Arrow schema of search index tokens.
+///
+/// Columns are as follows:
+///
+/// * `document` - The document ID, dictionary-encoded so that each ID is stored once
+/// * `text` - The label of the token
+/// * `offset_start`, `offset_end` - The span of the token in the trunk
+/// * `increment` - The position increment of the token
+/// * `length` - The position length of the token
+///
+/// Lattices don't record the provenance of edges yet, so there is no column for it.
+pub fn search_index_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new(
+            "document",
+            DataType::Dictionary(Box::new(DataType::UInt32), Box::new(DataType::Utf8)),
+            false,
+        ),
+        Field::new("text", DataType::Utf8, false),
+        Field::new("offset_start", DataType::UInt64, false),
+        Field::new("offset_end", DataType::UInt64, false),
+        Field::new("increment", DataType::UInt64, false),
+        Field::new("length", DataType::UInt64, false),
+    ]))
+}
+
+/// A builder of Arrow record batches of search index tokens of many lattices.
+///
+/// Labels are copied once from lattices into the buffers of the record batch.
+#[derive(Default)]
+pub struct SearchIndexBatchBuilder {
+    document: StringDictionaryBuilder<UInt32Type>,
+    text: StringBuilder,
+    offset_start: UInt64Builder,
+    offset_end: UInt64Builder,
+    increment: UInt64Builder,
+    length: UInt64Builder,
+}
+
+impl SearchIndexBatchBuilder {
+    /// Returns an empty builder.
+    pub fn new() -> SearchIndexBatchBuilder {
+        SearchIndexBatchBuilder::default()
+    }
+
+    /// Appends tokens of the given lattice.
+    ///
+    /// # Arguments
+    ///
+    /// * `document` - A document ID
+    /// * `lattice` - A lattice
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use parattice::PaRattice;
+    /// use parattice::arrow::SearchIndexBatchBuilder;
+    ///
+    /// let paradict = vec![
+    ///     vec![
+    ///         vec!["blood", "stem", "cell"],
+    ///         vec!["造血", "幹", "細胞"],
+    ///         vec!["hematopoietic", "stem", "cell"],
+    ///     ],
+    ///     vec![
+    ///         vec!["造血", "幹", "細胞", "移植"],
+    ///         vec!["hematopoietic", "stem", "cell", "transplantation"],
+    ///     ],
+    ///     vec![vec!["stem", "cell"], vec!["幹", "細胞"]],
+    ///     vec![
+    ///         vec!["幹", "細胞", "移植"],
+    ///         vec!["rescue", "transplant"],
+    ///         vec!["stem", "cell", "rescue"],
+    ///     ],
+    ///     vec![vec!["rescue"], vec!["救命"]],
+    ///     vec![vec!["blood"], vec!["血液"]],
+    /// ];
+    /// let parattice = PaRattice::new(paradict);
+    /// let words = vec!["造血", "幹", "細胞", "移植"];
+    /// let lattice = parattice.get_lattice(&words, true, 2);
+    ///
+    /// let mut builder = SearchIndexBatchBuilder::new();
+    /// builder.push("doc1", &lattice);
+    /// let batch = builder.finish();
+    /// assert_eq!(lattice.dump_for_search_index().len(), batch.num_rows());
+    /// ```
+    pub fn push(&mut self, document: &str, lattice: &Lattice) {
+        self.push_nodes(document, &lattice.dump_for_search_index());
+    }
+
+    /// Appends the given tokens.
+    ///
+    /// # Arguments
+    ///
+    /// * `document` - A document ID
+    /// * `nodes` - Tokens returned by `Lattice::dump_for_search_index()`
+    pub fn push_nodes(&mut self, document: &str, nodes: &[SearchIndexNode]) {
+        for node in nodes {
+            self.document.append_value(document);
+            self.text.append_value(node.text);
+            self.offset_start.append_value(node.offset.0 as u64);
+            self.offset_end.append_value(node.offset.1 as u64);
+            self.increment.append_value(node.increment as u64);
+            self.length.append_value(node.length as u64);
+        }
+    }
+
+    /// Returns the number of appended tokens.
+    pub fn len(&self) -> usize {
+        self.text.len()
+    }
+
+    /// Returns true if no tokens are appended.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns a record batch of appended tokens and resets the builder.
+    pub fn finish(&mut self) -> RecordBatch {
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(self.document.finish()),
+            Arc::new(self.text.finish()),
+            Arc::new(self.offset_start.finish()),
+            Arc::new(self.offset_end.finish()),
+            Arc::new(self.increment.finish()),
+            Arc::new(self.length.finish()),
+        ];
+        // columns always match the schema
+        RecordBatch::try_new(search_index_schema(), columns).unwrap()
+    }
+}
+
+/// A writer of a Parquet file of search index tokens of many lattices.
+///
+/// Tokens are buffered and written as a record batch every `batch_size` rows. The schema is the
+/// one returned by `search_index_schema()`.
+#[cfg(feature = "parquet")]
+pub struct SearchIndexWriter<W: Write + Send> {
+    writer: ArrowWriter<W>,
+    builder: SearchIndexBatchBuilder,
+    batch_size: usize,
+}
+
+#[cfg(feature = "parquet")]
+impl<W: Write + Send> SearchIndexWriter<W> {
+    /// Returns a writer that writes a record batch every `DEFAULT_BATCH_SIZE` rows.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - A destination such as `File`
+    pub fn new(writer: W) -> Result<SearchIndexWriter<W>, ParquetError> {
+        SearchIndexWriter::with_batch_size(writer, DEFAULT_BATCH_SIZE)
+    }
+
+    /// Returns a writer that writes a record batch every `batch_size` rows.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - A destination such as `File`
+    /// * `batch_size` - The number of rows of a record batch
+    pub fn with_batch_size(
+        writer: W,
+        batch_size: usize,
+    ) -> Result<SearchIndexWriter<W>, ParquetError> {
+        Ok(SearchIndexWriter {
+            writer: ArrowWriter::try_new(writer, search_index_schema(), None)?,
+            builder: SearchIndexBatchBuilder::new(),
+            batch_size: batch_size.max(1),
+        })
+    }
+
+    /// Writes tokens of the given lattice.
+    ///
+    /// # Arguments
+    ///
+    /// * `document` - A document ID
+    /// * `lattice` - A lattice
+    pub fn write(&mut self, document: &str, lattice: &Lattice) -> Result<(), ParquetError> {
+        self.builder.push(document, lattice);
+        if self.builder.len() >= self.batch_size {
+            self.writer.write(&self.builder.finish())?;
+        }
+        Ok(())
+    }
+
+    /// Returns the destination after writing buffered tokens and the footer.
+    pub fn finish(mut self) -> Result<W, ParquetError> {
+        if !self.builder.is_empty() {
+            self.writer.write(&self.builder.finish())?;
+        }
+        self.writer.into_inner()
+    }
+}
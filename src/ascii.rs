@@ -0,0 +1,181 @@
+use crate::lattice::Label;
+use crate::lattice::Lattice;
+
+use std::cmp;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Returns the number of terminal columns of a text, treating CJK characters as full-width.
+fn display_width(s: &str) -> usize {
+    s.chars().map(|c| if c >= '\u{1100}' { 2 } else { 1 }).sum()
+}
+
+impl<'a, T: Label + fmt::Display> Lattice<'a, T> {
+    /// Returns a plain text rendering of the lattice for terminals.
+    ///
+    /// The first line is the trunk, and the second line underlines it. Each following line
+    /// contains alternative phrases placed beneath the trunk spans they replace. Each
+    /// alternative phrase contains an edge that is not a forward main edge, and main edges that
+    /// connect it to the trunk on both sides.
+    ///
+    /// If the lattice is not valid (see `validate()`), each line instead contains a node ID, its
+    /// trunk position if any, and its forward edges, where the forward main edge is marked by
+    /// `*`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use parattice::PaRattice;
+    ///
+    /// let paradict = vec![
+    ///     vec![
+    ///         vec!["blood", "stem", "cell"],
+    ///         vec!["造血", "幹", "細胞"],
+    ///         vec!["hematopoietic", "stem", "cell"],
+    ///     ],
+    ///     vec![
+    ///         vec!["造血", "幹", "細胞", "移植"],
+    ///         vec!["hematopoietic", "stem", "cell", "transplantation"],
+    ///     ],
+    ///     vec![vec!["stem", "cell"], vec!["幹", "細胞"]],
+    ///     vec![
+    ///         vec!["幹", "細胞", "移植"],
+    ///         vec!["rescue", "transplant"],
+    ///         vec!["stem", "cell", "rescue"],
+    ///     ],
+    ///     vec![vec!["rescue"], vec!["救命"]],
+    ///     vec![vec!["blood"], vec!["血液"]],
+    /// ];
+    /// let parattice = PaRattice::new(paradict);
+    /// let words = vec!["造血", "幹", "細胞", "移植"];
+    /// let lattice = parattice.get_lattice(&words, true, 2);
+    ///
+    /// println!("{}", lattice.dump_ascii());
+    /// ```
+    pub fn dump_ascii(&self) -> String {
+        self.render_ascii(|label| label.to_string())
+    }
+}
+
+impl<'a, T: Label> Lattice<'a, T> {
+    /// Returns the text rendering of `dump_ascii` with labels formatted by `label_text`.
+    fn render_ascii<F: Fn(T) -> String>(&self, label_text: F) -> String {
+        if self.validate().is_err() {
+            return self.render_nodes(label_text);
+        }
+        let trunk_node_ids = self.trunk_node_ids();
+        // columns of trunk nodes
+        let mut columns = vec![0; trunk_node_ids.len()];
+        let mut words = vec![];
+        for k in 1..trunk_node_ids.len() {
+            let (s, _) = self.lattice[trunk_node_ids[k - 1]].forward_main.unwrap();
+            let word = label_text(s);
+            columns[k] = columns[k - 1] + display_width(&word) + 1;
+            words.push(word);
+        }
+        let mut result = words.join(" ") + "\n";
+        result += &words
+            .iter()
+            .map(|s| "=".repeat(display_width(s)))
+            .collect::<Vec<String>>()
+            .join(" ");
+        result += "\n";
+
+        // spans are the same as the search index
+        let trunk_spans = self.get_trunk_spans();
+        let mut phrases = vec![];
+        for (i, node) in self.lattice.iter().enumerate() {
+            for &edge in &node.forwards {
+                if node.forward_main == Some(edge) {
+                    continue;
+                }
+                let path = self.get_trunk_span(vec![(T::default(), i), edge]);
+                let phrase_words: Vec<String> =
+                    path[1..].iter().map(|&(s, _)| label_text(s)).collect();
+                let left = columns[trunk_spans[i].0];
+                let right = columns[trunk_spans[edge.1].1];
+                phrases.push((left, right, phrase_words.join(" ")));
+            }
+        }
+        phrases.sort();
+        phrases.dedup();
+        // each row is a pair of a text and its width
+        let mut rows: Vec<(String, usize)> = vec![];
+        for (left, right, text) in phrases {
+            let text_width = display_width(&text);
+            // the span excludes the space after the last word
+            let span_width = cmp::max(right - left, 1) - 1;
+            let row_id = match rows.iter().position(|row| row.1 < left || row.1 == 0) {
+                Some(row_id) => row_id,
+                None => {
+                    rows.push((String::new(), 0));
+                    rows.len() - 1
+                }
+            };
+            let row = &mut rows[row_id];
+            row.0 += &" ".repeat(left - row.1);
+            row.0 += &text;
+            row.0 += &"-".repeat(span_width.saturating_sub(text_width));
+            row.1 = left + cmp::max(text_width, span_width);
+        }
+        for (row, _) in rows {
+            result += &row;
+            result += "\n";
+        }
+        result
+    }
+
+    /// Returns a line of each node with its trunk position and forward edges, which doesn't
+    /// need a valid lattice.
+    fn render_nodes<F: Fn(T) -> String>(&self, label_text: F) -> String {
+        let mut result = String::new();
+        for (i, node) in self.lattice.iter().enumerate() {
+            result += &i.to_string();
+            if let Some(position) = self.trunk.get(&i) {
+                result += &format!(" [trunk {}]", position);
+            }
+            result += ":";
+            for &edge in &node.forwards {
+                let mark = if node.forward_main == Some(edge) { "*" } else { "" };
+                result += &format!(" {}{}->{}", mark, label_text(edge.0), edge.1);
+            }
+            result += "\n";
+        }
+        result
+    }
+}
+
+impl<'a, T: Label + fmt::Debug> fmt::Debug for Lattice<'a, T> {
+    /// Formats the size and the trunk of the lattice, or the text rendering of `dump_ascii` with
+    /// `{:#?}`. Labels are formatted with `{:?}`. Lattices that are not valid are formatted with
+    /// their nodes, the trunk and the capacity as they are.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if f.alternate() {
+            write!(
+                f,
+                "Lattice ({} nodes, {} edges)\n{}",
+                self.lattice.len(),
+                self.capacity,
+                self.render_ascii(|label| format!("{:?}", label))
+            )
+        } else if self.validate().is_err() {
+            let trunk: BTreeMap<_, _> = self.trunk.iter().collect();
+            f.debug_struct("Lattice")
+                .field("nodes", &self.lattice)
+                .field("trunk", &trunk)
+                .field("capacity", &self.capacity)
+                .finish()
+        } else {
+            let trunk_node_ids = self.trunk_node_ids();
+            let trunk: Vec<T> = trunk_node_ids[..trunk_node_ids.len() - 1]
+                .iter()
+                .map(|&node_id| self.lattice[node_id].forward_main.unwrap().0)
+                .collect();
+            f.debug_struct("Lattice")
+                .field("nodes", &self.lattice.len())
+                .field("edges", &self.capacity)
+                .field("trunk", &trunk)
+                .finish()
+        }
+    }
+}
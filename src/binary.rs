@@ -0,0 +1,494 @@
+use crate::error::DecodeError;
+use crate::lattice::usize_to_vec;
+use crate::lattice::ByteReader;
+use crate::lattice::EdgeWeights;
+use crate::lattice::Lattice;
+use crate::lattice::LatticeNode;
+use crate::lattice_view::encode_indexed;
+use crate::lattice_view::LatticeView;
+use crate::utils::crc32;
+
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::str;
+
+/// The magic number at the beginning of binary data.
+pub const MAGIC: [u8; 8] = *b"\x89PRT\r\n\x1a\n";
+
+/// The current format version.
+pub const FORMAT_VERSION: u16 = 1;
+
+/// A flag of the section of edge weights.
+pub const FLAG_WEIGHTS: u16 = 0x1;
+
+/// A flag of the section of node depths. The compact and the indexed layouts store depths in the
+/// lattice section, so the section is needed only for the legacy layout.
+pub const FLAG_DEPTH: u16 = 0x2;
+
+/// A flag reserved for the section of edge provenance. It is not supported yet.
+pub const FLAG_PROVENANCE: u16 = 0x4;
+
+/// A flag of the section of node offsets in the lattice section.
+pub const FLAG_OFFSETS: u16 = 0x8;
+
+/// A flag of the compact layout of the lattice section.
+pub const FLAG_COMPACT: u16 = 0x10;
+
+/// A flag of the indexed layout of the lattice section.
+pub const FLAG_INDEXED: u16 = 0x20;
+
+const SUPPORTED_FLAGS: u16 = FLAG_WEIGHTS | FLAG_DEPTH | FLAG_OFFSETS | FLAG_COMPACT | FLAG_INDEXED;
+
+// magic, version, flags, reserved and payload length
+const HEADER_LENGTH: usize = 24;
+
+/// A layout of the lattice section.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Layout {
+    /// The layout of `to_legacy_bytes`
+    #[default]
+    Legacy,
+    /// Labels in a string table and forward edges in variable-length integers
+    Compact,
+    /// Fixed-width arrays that `LatticeView` reads without decoding
+    Indexed,
+}
+
+/// Options of optional sections of binary data.
+#[derive(Default)]
+pub struct BinaryOptions<'b> {
+    /// Weights of edges. Edges without weights are stored as NaN.
+    pub weights: Option<&'b EdgeWeights<'b>>,
+    /// If true, depths of nodes are stored in a section. The legacy layout doesn't store them
+    /// otherwise.
+    pub depth: bool,
+    /// If true, byte offsets of nodes in the lattice section are stored.
+    pub offsets: bool,
+    /// The layout of the lattice section.
+    pub layout: Layout,
+}
+
+fn push_section(payload: &mut Vec<u8>, section: Vec<u8>) {
+    payload.extend(usize_to_vec(section.len()));
+    payload.extend(section);
+}
+
+/// Checks the header and the checksum, and returns the flags and a reader of the payload.
+pub(crate) fn read_header(data: &[u8]) -> Result<(u16, ByteReader<'_>), DecodeError> {
+    let mut reader = ByteReader { data, offset: 0 };
+    reader.read_bytes(MAGIC.len())?;
+    let version = reader.read_u16()?;
+    let flags = reader.read_u16()?;
+    reader.read_u32()?;
+    let payload = reader
+        .read_usize()
+        .and_then(|length| reader.read_bytes(length))?;
+    let checksum_offset = reader.offset;
+    let checksum = reader.read_u32()?;
+    let actual_checksum = crc32(&data[..checksum_offset]);
+    if checksum != actual_checksum {
+        return Err(DecodeError::ChecksumMismatch {
+            expected: checksum,
+            actual: actual_checksum,
+        });
+    }
+    if version != FORMAT_VERSION {
+        return Err(DecodeError::UnsupportedVersion(version));
+    }
+    if flags & !SUPPORTED_FLAGS != 0
+        || flags & (FLAG_COMPACT | FLAG_INDEXED) == FLAG_COMPACT | FLAG_INDEXED
+    {
+        return Err(DecodeError::UnsupportedFlags(flags));
+    }
+    Ok((
+        flags,
+        ByteReader {
+            data: payload,
+            offset: 0,
+        },
+    ))
+}
+
+/// Returns the next section of the payload.
+pub(crate) fn read_section<'a>(reader: &mut ByteReader<'a>) -> Result<&'a [u8], DecodeError> {
+    let length = reader.read_usize()?;
+    reader.read_bytes(length)
+}
+
+/// Appends an unsigned LEB128 integer.
+pub(crate) fn push_varint(result: &mut Vec<u8>, mut x: u64) {
+    while x >= 0x80 {
+        result.push(x as u8 | 0x80);
+        x >>= 7;
+    }
+    result.push(x as u8);
+}
+
+/// Returns the lattice section in the compact layout and byte offsets of nodes in it.
+///
+/// Integers are unsigned LEB128. The section consists of the number of labels, the labels in
+/// ascending order as pairs of the length and UTF-8 bytes, and the number of nodes followed by
+/// nodes. Each node has the number of forward edges, pairs of the label ID and the zigzag-encoded
+/// difference of the target node ID from the node ID, 1-based indices of the forward and the
+/// backward main edges in ascending order of edges (0 if none), and the depth. Backward edges are
+/// rebuilt from forward edges.
+fn encode_compact(lattice: &Lattice) -> (Vec<u8>, Vec<usize>) {
+    let mut labels: Vec<&str> = lattice
+        .lattice
+        .iter()
+        .flat_map(|node| node.forwards.iter().map(|&(s, _)| s))
+        .collect();
+    labels.sort();
+    labels.dedup();
+    let label_ids: HashMap<&str, usize> = labels.iter().enumerate().map(|(i, &s)| (s, i)).collect();
+    let mut result = vec![];
+    push_varint(&mut result, labels.len() as u64);
+    for s in &labels {
+        push_varint(&mut result, s.len() as u64);
+        result.extend(s.as_bytes());
+    }
+    let offsets = encode_compact_nodes(lattice, &mut result, |s| label_ids[s] as u64);
+    (result, offsets)
+}
+
+/// Appends nodes in the compact layout with the given label IDs, and returns byte offsets of
+/// nodes in `result`.
+pub(crate) fn encode_compact_nodes<'a, F>(
+    lattice: &Lattice<'a>,
+    result: &mut Vec<u8>,
+    mut label_id: F,
+) -> Vec<usize>
+where
+    F: FnMut(&'a str) -> u64,
+{
+    push_varint(result, lattice.lattice.len() as u64);
+    let mut offsets = Vec::with_capacity(lattice.lattice.len());
+    for (i, node) in lattice.lattice.iter().enumerate() {
+        offsets.push(result.len());
+        push_varint(result, node.forwards.len() as u64);
+        for &(s, j) in &node.forwards {
+            let delta = j.wrapping_sub(i) as i64;
+            push_varint(result, label_id(s));
+            push_varint(result, ((delta << 1) ^ (delta >> 63)) as u64);
+        }
+        push_varint(result, main_index(&node.forwards, node.forward_main));
+        push_varint(result, main_index(&node.backwards, node.backward_main));
+        push_varint(result, node.depth as u64);
+    }
+    offsets
+}
+
+fn main_index<'a>(edges: &BTreeSet<(&'a str, usize)>, main: Option<(&'a str, usize)>) -> u64 {
+    main.map_or(0, |main| edges.range(..main).count() as u64 + 1)
+}
+
+pub(crate) fn read_index(reader: &mut ByteReader) -> Result<usize, DecodeError> {
+    let offset = reader.offset;
+    let x = reader.read_varint()?;
+    if x > usize::MAX as u64 {
+        return Err(DecodeError::InvalidVarint { offset });
+    }
+    Ok(x as usize)
+}
+
+fn nth_edge<'a>(
+    edges: &BTreeSet<(&'a str, usize)>,
+    index: usize,
+    node: usize,
+) -> Result<Option<(&'a str, usize)>, DecodeError> {
+    if index == 0 {
+        return Ok(None);
+    }
+    edges
+        .iter()
+        .nth(index - 1)
+        .cloned()
+        .map(Some)
+        .ok_or(DecodeError::InvalidMainEdge { node })
+}
+
+/// Returns a lattice and byte offsets of nodes from the lattice section in the compact layout.
+fn decode_compact<'a>(data: &'a [u8]) -> Result<(Lattice<'a>, Vec<usize>), DecodeError> {
+    let mut reader = ByteReader { data, offset: 0 };
+    let num_labels = read_index(&mut reader)?;
+    // each label takes at least 1 byte
+    let mut labels = Vec::with_capacity(num_labels.min(data.len()));
+    for _ in 0..num_labels {
+        let length = read_index(&mut reader)?;
+        let offset = reader.offset;
+        let label = str::from_utf8(reader.read_bytes(length)?)
+            .map_err(|_| DecodeError::InvalidUtf8 { offset })?;
+        labels.push(label);
+    }
+    let result = decode_compact_nodes(&mut reader, |label_id| labels.get(label_id).cloned())?;
+    if reader.offset != data.len() {
+        return Err(DecodeError::InvalidSection(FLAG_COMPACT));
+    }
+    Ok(result)
+}
+
+/// Returns a lattice and byte offsets of nodes from nodes in the compact layout with the given
+/// label table.
+pub(crate) fn decode_compact_nodes<'a, F>(
+    reader: &mut ByteReader,
+    label: F,
+) -> Result<(Lattice<'a>, Vec<usize>), DecodeError>
+where
+    F: Fn(usize) -> Option<&'a str>,
+{
+    let num_nodes = read_index(reader)?;
+    // each node takes at least 4 bytes
+    let capacity = num_nodes.min((reader.data.len() - reader.offset) / 4);
+    let mut offsets = Vec::with_capacity(capacity);
+    let mut main_indices = Vec::with_capacity(capacity);
+    let mut lattice: Vec<LatticeNode> = Vec::with_capacity(capacity);
+    for i in 0..num_nodes {
+        offsets.push(reader.offset);
+        let mut node = LatticeNode::new(None, None, 0);
+        for _ in 0..read_index(reader)? {
+            let label_id = read_index(reader)?;
+            let delta = reader.read_varint()?;
+            let delta = ((delta >> 1) as i64 ^ -((delta & 1) as i64)) as usize;
+            let target = i.wrapping_add(delta);
+            let label = label(label_id).ok_or(DecodeError::LabelOutOfRange {
+                node: i,
+                label: label_id,
+            })?;
+            if target >= num_nodes {
+                return Err(DecodeError::NodeOutOfRange { node: i, target });
+            }
+            node.forwards.insert((label, target));
+        }
+        main_indices.push((read_index(reader)?, read_index(reader)?));
+        node.depth = read_index(reader)?;
+        lattice.push(node);
+    }
+    for i in 0..num_nodes {
+        let forwards: Vec<(&str, usize)> = lattice[i].forwards.iter().cloned().collect();
+        for (s, j) in forwards {
+            lattice[j].backwards.insert((s, i));
+        }
+    }
+    for (i, (node, (forward_index, backward_index))) in
+        lattice.iter_mut().zip(main_indices).enumerate()
+    {
+        node.forward_main = nth_edge(&node.forwards, forward_index, i)?;
+        node.backward_main = nth_edge(&node.backwards, backward_index, i)?;
+    }
+    Ok((Lattice::from_decoded_nodes(lattice)?, offsets))
+}
+
+impl<'a> Lattice<'a> {
+    /// Returns binary data of the lattice with the given optional sections.
+    ///
+    /// Integers are little-endian. The data consists of the following items:
+    ///
+    /// * Magic number `\x89PRT\r\n\x1a\n` (8 bytes)
+    /// * Format version (2 bytes)
+    /// * Flags of optional sections (2 bytes)
+    /// * Reserved (4 bytes, zero)
+    /// * Payload length (8 bytes)
+    /// * Payload: sections, each of which is its length (8 bytes) followed by its contents. The
+    ///   lattice section in the legacy layout, the compact layout with `FLAG_COMPACT` or the
+    ///   indexed layout with `FLAG_INDEXED` comes first, and the optional sections follow in
+    ///   ascending order of their flags.
+    /// * CRC-32 of all the above (4 bytes)
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - Optional sections
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use parattice::PaRattice;
+    /// use parattice::Lattice;
+    /// use parattice::binary::BinaryOptions;
+    /// use parattice::lattice::EdgeWeights;
+    ///
+    /// let paradict = vec![
+    ///     vec![
+    ///         vec!["blood", "stem", "cell"],
+    ///         vec!["造血", "幹", "細胞"],
+    ///         vec!["hematopoietic", "stem", "cell"],
+    ///     ],
+    ///     vec![
+    ///         vec!["造血", "幹", "細胞", "移植"],
+    ///         vec!["hematopoietic", "stem", "cell", "transplantation"],
+    ///     ],
+    ///     vec![vec!["stem", "cell"], vec!["幹", "細胞"]],
+    ///     vec![
+    ///         vec!["幹", "細胞", "移植"],
+    ///         vec!["rescue", "transplant"],
+    ///         vec!["stem", "cell", "rescue"],
+    ///     ],
+    ///     vec![vec!["rescue"], vec!["救命"]],
+    ///     vec![vec!["blood"], vec!["血液"]],
+    /// ];
+    /// let parattice = PaRattice::new(paradict);
+    /// let words = vec!["造血", "幹", "細胞", "移植"];
+    /// let lattice = parattice.get_lattice(&words, true, 2);
+    ///
+    /// let mut weights = EdgeWeights::new();
+    /// for (i, node) in lattice.lattice.iter().enumerate() {
+    ///     for &(s, j) in &node.forwards {
+    ///         weights.insert((i, s, j), 1.0);
+    ///     }
+    /// }
+    /// let bytes = lattice.to_bytes_with_options(&BinaryOptions {
+    ///     weights: Some(&weights),
+    ///     depth: true,
+    ///     ..BinaryOptions::default()
+    /// });
+    /// let (new_lattice, new_weights) = Lattice::try_from_bytes_with_weights(&bytes).unwrap();
+    /// assert!(lattice == new_lattice);
+    /// assert_eq!(Some(weights), new_weights);
+    /// ```
+    pub fn to_bytes_with_options(&self, options: &BinaryOptions) -> Vec<u8> {
+        let mut flags = 0;
+        let mut payload = vec![];
+        let (body, offsets) = match options.layout {
+            Layout::Legacy => (self.to_legacy_bytes(), None),
+            Layout::Compact => {
+                flags |= FLAG_COMPACT;
+                let (body, offsets) = encode_compact(self);
+                (body, Some(offsets))
+            }
+            Layout::Indexed => {
+                flags |= FLAG_INDEXED;
+                let (body, offsets) = encode_indexed(&self.freeze());
+                (body, Some(offsets))
+            }
+        };
+        push_section(&mut payload, body);
+        if let Some(weights) = options.weights {
+            flags |= FLAG_WEIGHTS;
+            let mut section = Vec::with_capacity(self.capacity * 8);
+            for (i, node) in self.lattice.iter().enumerate() {
+                for &(s, j) in &node.forwards {
+                    let weight = weights.get(&(i, s, j)).cloned().unwrap_or(f64::NAN);
+                    section.extend(&weight.to_bits().to_le_bytes());
+                }
+            }
+            push_section(&mut payload, section);
+        }
+        if options.depth {
+            flags |= FLAG_DEPTH;
+            let mut section = Vec::with_capacity(self.lattice.len() * 8);
+            for node in &self.lattice {
+                section.extend(usize_to_vec(node.depth));
+            }
+            push_section(&mut payload, section);
+        }
+        if options.offsets {
+            flags |= FLAG_OFFSETS;
+            let mut section = Vec::with_capacity(self.lattice.len() * 8);
+            for offset in offsets.unwrap_or_else(|| self.node_offsets()) {
+                section.extend(usize_to_vec(offset));
+            }
+            push_section(&mut payload, section);
+        }
+        let mut result = Vec::with_capacity(HEADER_LENGTH + payload.len() + 4);
+        result.extend(&MAGIC);
+        result.extend(&FORMAT_VERSION.to_le_bytes());
+        result.extend(&flags.to_le_bytes());
+        result.extend(&[0; 4]);
+        result.extend(usize_to_vec(payload.len()));
+        result.extend(payload);
+        let checksum = crc32(&result);
+        result.extend(&checksum.to_le_bytes());
+        result
+    }
+
+    /// Returns a lattice and edge weights if any from the given binary data.
+    ///
+    /// Binary data of the legacy layout is also supported, and it has no weights.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - A byte array
+    pub fn try_from_bytes_with_weights(
+        data: &'a [u8],
+    ) -> Result<(Lattice<'a>, Option<EdgeWeights<'a>>), DecodeError> {
+        if !data.starts_with(&MAGIC) {
+            return Lattice::try_from_legacy_bytes(data).map(|lattice| (lattice, None));
+        }
+        let (flags, mut reader) = read_header(data)?;
+        let mut read_section = || read_section(&mut reader);
+        let body = read_section()?;
+        let (mut lattice, expected_offsets) = if flags & FLAG_COMPACT != 0 {
+            let (lattice, offsets) = decode_compact(body)?;
+            (lattice, Some(offsets))
+        } else if flags & FLAG_INDEXED != 0 {
+            let view = LatticeView::from_section(body)?;
+            (view.to_lattice(), Some(view.node_offsets().collect()))
+        } else {
+            (Lattice::try_from_legacy_bytes(body)?, None)
+        };
+        let weights = if flags & FLAG_WEIGHTS != 0 {
+            let section = read_section()?;
+            if section.len() != lattice.capacity * 8 {
+                return Err(DecodeError::InvalidSection(FLAG_WEIGHTS));
+            }
+            let mut weights = EdgeWeights::new();
+            let mut values = section.chunks(8).map(|x| {
+                f64::from_bits(u64::from_le_bytes([
+                    x[0], x[1], x[2], x[3], x[4], x[5], x[6], x[7],
+                ]))
+            });
+            for (i, node) in lattice.lattice.iter().enumerate() {
+                for &(s, j) in &node.forwards {
+                    let weight = values.next().unwrap();
+                    if !weight.is_nan() {
+                        weights.insert((i, s, j), weight);
+                    }
+                }
+            }
+            Some(weights)
+        } else {
+            None
+        };
+        if flags & FLAG_DEPTH != 0 {
+            let section = read_section()?;
+            if section.len() != lattice.lattice.len() * 8 {
+                return Err(DecodeError::InvalidSection(FLAG_DEPTH));
+            }
+            let mut section_reader = ByteReader {
+                data: section,
+                offset: 0,
+            };
+            for node in &mut lattice.lattice {
+                node.depth = section_reader.read_usize()?;
+            }
+        }
+        if flags & FLAG_OFFSETS != 0 {
+            let section = read_section()?;
+            let mut section_reader = ByteReader {
+                data: section,
+                offset: 0,
+            };
+            let mut offsets = Vec::with_capacity(lattice.lattice.len());
+            while section_reader.offset < section.len() {
+                offsets.push(section_reader.read_usize()?);
+            }
+            if offsets != expected_offsets.unwrap_or_else(|| lattice.node_offsets()) {
+                return Err(DecodeError::InvalidSection(FLAG_OFFSETS));
+            }
+        }
+        Ok((lattice, weights))
+    }
+
+    /// Returns byte offsets of nodes in the legacy layout.
+    pub(crate) fn node_offsets(&self) -> Vec<usize> {
+        let mut offsets = Vec::with_capacity(self.lattice.len());
+        let mut offset = 8;
+        for node in &self.lattice {
+            offsets.push(offset);
+            offset += 16;
+            for &(s, _) in node.forwards.iter().chain(node.backwards.iter()) {
+                offset += 16 + s.len();
+            }
+        }
+        offsets
+    }
+}
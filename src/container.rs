@@ -0,0 +1,423 @@
+use crate::binary::decode_compact_nodes;
+use crate::binary::encode_compact_nodes;
+use crate::binary::push_varint;
+use crate::binary::read_index;
+use crate::binary::FLAG_COMPACT;
+use crate::error::ContainerError;
+use crate::error::DecodeError;
+use crate::lattice::ByteReader;
+use crate::lattice::Lattice;
+use crate::utils::crc32;
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::io::Write;
+use std::str;
+
+/// The magic number at the beginning of containers.
+pub const CONTAINER_MAGIC: [u8; 8] = *b"\x89PRC\r\n\x1a\n";
+
+/// The current container format version.
+pub const CONTAINER_VERSION: u16 = 1;
+
+// magic, version and reserved
+const HEADER_LENGTH: u64 = 16;
+
+// offsets of the string table and the index, checksum and reserved
+const FOOTER_LENGTH: u64 = 24;
+
+struct Entry {
+    document: String,
+    sentence: u64,
+    offset: u64,
+    length: u64,
+    checksum: u32,
+}
+
+/// A writer of a container that packs many lattices with document and sentence keys.
+///
+/// Lattices are written one by one, and labels of all lattices are stored once in a shared
+/// string table. Integers are little-endian, and the container consists of the following items:
+///
+/// * Magic number `\x89PRC\r\n\x1a\n` (8 bytes)
+/// * Format version (2 bytes)
+/// * Reserved (6 bytes, zero)
+/// * Lattices as nodes in the compact layout with IDs of the shared string table
+/// * String table: the number of labels and pairs of the length and UTF-8 bytes in variable-length
+///   integers
+/// * Index: the number of lattices, and the document, the sentence, the offset, the length
+///   in variable-length integers and CRC-32 (4 bytes) of each lattice
+/// * Offsets of the string table and the index (8 bytes each)
+/// * CRC-32 of the string table, the index and their offsets (4 bytes)
+/// * Reserved (4 bytes, zero)
+pub struct LatticeWriter<W: Write> {
+    writer: W,
+    offset: u64,
+    label_ids: HashMap<String, u64>,
+    keys: HashMap<String, HashSet<u64>>,
+    entries: Vec<Entry>,
+}
+
+impl<W: Write> LatticeWriter<W> {
+    /// Returns a writer after writing the header.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - A destination such as `BufWriter<File>`
+    pub fn new(mut writer: W) -> Result<LatticeWriter<W>, ContainerError> {
+        writer.write_all(&CONTAINER_MAGIC)?;
+        writer.write_all(&CONTAINER_VERSION.to_le_bytes())?;
+        writer.write_all(&[0; 6])?;
+        Ok(LatticeWriter {
+            writer,
+            offset: HEADER_LENGTH,
+            label_ids: HashMap::new(),
+            keys: HashMap::new(),
+            entries: vec![],
+        })
+    }
+
+    /// Writes a lattice with the given key.
+    ///
+    /// # Arguments
+    ///
+    /// * `document` - A document ID
+    /// * `sentence` - A sentence number in the document
+    /// * `lattice` - A lattice
+    pub fn write(
+        &mut self,
+        document: &str,
+        sentence: u64,
+        lattice: &Lattice,
+    ) -> Result<(), ContainerError> {
+        if self
+            .keys
+            .get(document)
+            .is_some_and(|sentences| sentences.contains(&sentence))
+        {
+            return Err(ContainerError::DuplicateKey {
+                document: document.to_string(),
+                sentence,
+            });
+        }
+        let mut record = vec![];
+        let label_ids = &mut self.label_ids;
+        encode_compact_nodes(lattice, &mut record, |s| {
+            let num_labels = label_ids.len() as u64;
+            *label_ids.entry(s.to_string()).or_insert(num_labels)
+        });
+        self.writer.write_all(&record)?;
+        self.keys
+            .entry(document.to_string())
+            .or_default()
+            .insert(sentence);
+        self.entries.push(Entry {
+            document: document.to_string(),
+            sentence,
+            offset: self.offset,
+            length: record.len() as u64,
+            checksum: crc32(&record),
+        });
+        self.offset += record.len() as u64;
+        Ok(())
+    }
+
+    /// Writes the string table, the index and the footer, and returns the inner writer.
+    pub fn finish(mut self) -> Result<W, ContainerError> {
+        let mut labels = vec![""; self.label_ids.len()];
+        for (s, &label_id) in &self.label_ids {
+            labels[label_id as usize] = s;
+        }
+        let mut tail = vec![];
+        push_varint(&mut tail, labels.len() as u64);
+        for s in labels {
+            push_varint(&mut tail, s.len() as u64);
+            tail.extend(s.as_bytes());
+        }
+        let index_offset = self.offset + tail.len() as u64;
+        push_varint(&mut tail, self.entries.len() as u64);
+        for entry in &self.entries {
+            push_varint(&mut tail, entry.document.len() as u64);
+            tail.extend(entry.document.as_bytes());
+            push_varint(&mut tail, entry.sentence);
+            push_varint(&mut tail, entry.offset);
+            push_varint(&mut tail, entry.length);
+            tail.extend(&entry.checksum.to_le_bytes());
+        }
+        tail.extend(&self.offset.to_le_bytes());
+        tail.extend(&index_offset.to_le_bytes());
+        let checksum = crc32(&tail);
+        tail.extend(&checksum.to_le_bytes());
+        tail.extend(&[0; 4]);
+        self.writer.write_all(&tail)?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// A reader of a container written by `LatticeWriter` with random access by keys.
+///
+/// The string table and the index are loaded on construction, and each lattice is read when it
+/// is requested. To read a memory-mapped file, wrap the mapped bytes with `io::Cursor`.
+///
+/// # Example
+///
+/// ```
+/// use parattice::PaRattice;
+/// use parattice::container::LatticeReader;
+/// use parattice::container::LatticeWriter;
+/// use std::io::Cursor;
+///
+/// let paradict = vec![
+///     vec![
+///         vec!["blood", "stem", "cell"],
+///         vec!["造血", "幹", "細胞"],
+///         vec!["hematopoietic", "stem", "cell"],
+///     ],
+///     vec![
+///         vec!["造血", "幹", "細胞", "移植"],
+///         vec!["hematopoietic", "stem", "cell", "transplantation"],
+///     ],
+///     vec![vec!["stem", "cell"], vec!["幹", "細胞"]],
+///     vec![
+///         vec!["幹", "細胞", "移植"],
+///         vec!["rescue", "transplant"],
+///         vec!["stem", "cell", "rescue"],
+///     ],
+///     vec![vec!["rescue"], vec!["救命"]],
+///     vec![vec!["blood"], vec!["血液"]],
+/// ];
+/// let parattice = PaRattice::new(paradict);
+/// let words = vec!["造血", "幹", "細胞", "移植"];
+/// let lattice = parattice.get_lattice(&words, true, 2);
+///
+/// let mut writer = LatticeWriter::new(vec![]).unwrap();
+/// writer.write("doc1", 0, &lattice).unwrap();
+/// let bytes = writer.finish().unwrap();
+///
+/// let reader = LatticeReader::new(Cursor::new(bytes)).unwrap();
+/// let new_lattice = reader.get("doc1", 0).unwrap().unwrap();
+/// assert!(lattice == new_lattice);
+/// ```
+pub struct LatticeReader<R> {
+    source: RefCell<R>,
+    labels: String,
+    label_offsets: Vec<usize>,
+    index: HashMap<String, HashMap<u64, usize>>,
+    entries: Vec<Entry>,
+}
+
+fn read_u64(bytes: &[u8]) -> u64 {
+    let mut buf = [0; 8];
+    buf.copy_from_slice(bytes);
+    u64::from_le_bytes(buf)
+}
+
+fn read_string<'a>(reader: &mut ByteReader<'a>) -> Result<&'a str, DecodeError> {
+    let length = read_index(reader)?;
+    let offset = reader.offset;
+    str::from_utf8(reader.read_bytes(length)?).map_err(|_| DecodeError::InvalidUtf8 { offset })
+}
+
+impl<R: Read + Seek> LatticeReader<R> {
+    /// Returns a reader after loading the string table and the index.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - A source such as `BufReader<File>` or `Cursor<&[u8]>`
+    ///
+    /// # Errors
+    ///
+    /// Returns `ContainerError::DuplicateKey` if the index has a key twice, and other errors if
+    /// the source is not a container or it is broken.
+    pub fn new(mut source: R) -> Result<LatticeReader<R>, ContainerError> {
+        let mut header = [0; HEADER_LENGTH as usize];
+        source.seek(SeekFrom::Start(0))?;
+        source.read_exact(&mut header)?;
+        if header[..8] != CONTAINER_MAGIC {
+            return Err(ContainerError::NotContainer);
+        }
+        let version = u16::from_le_bytes([header[8], header[9]]);
+        if version != CONTAINER_VERSION {
+            return Err(DecodeError::UnsupportedVersion(version).into());
+        }
+        let end = source.seek(SeekFrom::End(0))?;
+        if end < HEADER_LENGTH + FOOTER_LENGTH {
+            return Err(DecodeError::Truncated {
+                offset: end as usize,
+            }
+            .into());
+        }
+        let mut footer = [0; FOOTER_LENGTH as usize];
+        source.seek(SeekFrom::End(-(FOOTER_LENGTH as i64)))?;
+        source.read_exact(&mut footer)?;
+        let table_offset = read_u64(&footer[..8]);
+        let index_offset = read_u64(&footer[8..16]);
+        if table_offset < HEADER_LENGTH
+            || index_offset < table_offset
+            || index_offset > end - FOOTER_LENGTH
+        {
+            return Err(DecodeError::Truncated {
+                offset: table_offset as usize,
+            }
+            .into());
+        }
+        let mut tail = vec![0; (end - table_offset - 4) as usize];
+        source.seek(SeekFrom::Start(table_offset))?;
+        source.read_exact(&mut tail)?;
+        let checksum_offset = tail.len() - 4;
+        let checksum = u32::from_le_bytes([
+            tail[checksum_offset],
+            tail[checksum_offset + 1],
+            tail[checksum_offset + 2],
+            tail[checksum_offset + 3],
+        ]);
+        let actual_checksum = crc32(&tail[..checksum_offset]);
+        if checksum != actual_checksum {
+            return Err(DecodeError::ChecksumMismatch {
+                expected: checksum,
+                actual: actual_checksum,
+            }
+            .into());
+        }
+        let mut reader = ByteReader {
+            data: &tail[..(index_offset - table_offset) as usize],
+            offset: 0,
+        };
+        let num_labels = read_index(&mut reader)?;
+        let mut labels = String::new();
+        // each label takes at least 1 byte
+        let mut label_offsets = Vec::with_capacity(num_labels.min(reader.data.len()) + 1);
+        label_offsets.push(0);
+        for _ in 0..num_labels {
+            labels.push_str(read_string(&mut reader)?);
+            label_offsets.push(labels.len());
+        }
+        let mut reader = ByteReader {
+            data: &tail[(index_offset - table_offset) as usize..checksum_offset - 16],
+            offset: 0,
+        };
+        let num_entries = read_index(&mut reader)?;
+        let mut index: HashMap<String, HashMap<u64, usize>> = HashMap::new();
+        // each entry takes at least 8 bytes
+        let mut entries = Vec::with_capacity(num_entries.min(reader.data.len() / 8));
+        for _ in 0..num_entries {
+            let document = read_string(&mut reader)?.to_string();
+            let sentence = reader.read_varint()?;
+            let offset = reader.read_varint()?;
+            let length = reader.read_varint()?;
+            let checksum = reader.read_u32()?;
+            if offset < HEADER_LENGTH || length > table_offset - offset.min(table_offset) {
+                return Err(DecodeError::Truncated {
+                    offset: offset as usize,
+                }
+                .into());
+            }
+            if index
+                .entry(document.clone())
+                .or_default()
+                .insert(sentence, entries.len())
+                .is_some()
+            {
+                return Err(ContainerError::DuplicateKey { document, sentence });
+            }
+            entries.push(Entry {
+                document,
+                sentence,
+                offset,
+                length,
+                checksum,
+            });
+        }
+        Ok(LatticeReader {
+            source: RefCell::new(source),
+            labels,
+            label_offsets,
+            index,
+            entries,
+        })
+    }
+
+    /// Returns the lattice of the given key, or None if it doesn't exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `document` - A document ID
+    /// * `sentence` - A sentence number in the document
+    pub fn get(
+        &self,
+        document: &str,
+        sentence: u64,
+    ) -> Result<Option<Lattice<'_>>, ContainerError> {
+        let entry = match self
+            .index
+            .get(document)
+            .and_then(|sentences| sentences.get(&sentence))
+        {
+            Some(&entry_id) => &self.entries[entry_id],
+            None => return Ok(None),
+        };
+        let mut record = vec![0; entry.length as usize];
+        {
+            let mut source = self.source.borrow_mut();
+            source.seek(SeekFrom::Start(entry.offset))?;
+            source.read_exact(&mut record)?;
+        }
+        let actual_checksum = crc32(&record);
+        if entry.checksum != actual_checksum {
+            return Err(DecodeError::ChecksumMismatch {
+                expected: entry.checksum,
+                actual: actual_checksum,
+            }
+            .into());
+        }
+        let mut reader = ByteReader {
+            data: &record,
+            offset: 0,
+        };
+        let (lattice, _) = decode_compact_nodes(&mut reader, |label_id| self.label(label_id))?;
+        if reader.offset != record.len() {
+            return Err(DecodeError::InvalidSection(FLAG_COMPACT).into());
+        }
+        Ok(Some(lattice))
+    }
+
+    /// Returns true if the container has a lattice of the given key.
+    pub fn contains(&self, document: &str, sentence: u64) -> bool {
+        self.index
+            .get(document)
+            .is_some_and(|sentences| sentences.contains_key(&sentence))
+    }
+
+    /// Returns keys of lattices in the written order.
+    pub fn keys(&self) -> impl Iterator<Item = (&str, u64)> {
+        self.entries
+            .iter()
+            .map(|entry| (entry.document.as_str(), entry.sentence))
+    }
+
+    /// Returns the number of lattices.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if the container has no lattices.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the inner reader.
+    pub fn into_inner(self) -> R {
+        self.source.into_inner()
+    }
+
+    fn label(&self, label_id: usize) -> Option<&str> {
+        let begin = *self.label_offsets.get(label_id)?;
+        let end = *self.label_offsets.get(label_id + 1)?;
+        Some(&self.labels[begin..end])
+    }
+}
@@ -0,0 +1,230 @@
+use crate::lattice::Lattice;
+
+use std::collections::HashSet;
+
+/// Placement of edge labels in DOT.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DotEdgeLabels {
+    /// Labels are drawn on edges
+    OnEdges,
+    /// Labels are drawn as box-shaped nodes between lattice nodes
+    AsNodes,
+}
+
+/// Options of `Lattice::dump_dot_with_options`.
+///
+/// The default options produce the same output as `Lattice::dump_dot(false)`.
+pub struct DotOptions<'a> {
+    /// If true, node numbers are printed
+    pub is_numbered: bool,
+    /// A color of forward main edges
+    pub forward_main_color: String,
+    /// A color of backward main edges
+    pub backward_main_color: String,
+    /// Placement of edge labels
+    pub edge_labels: DotEdgeLabels,
+    /// If true, trunk nodes are aligned on a straight line
+    pub align_trunk: bool,
+    /// Fill colors of nodes indexed by their depths, or empty to disable coloring
+    pub depth_colors: Vec<String>,
+    /// Paths to be highlighted (e.g. results of LatticeKMP)
+    pub highlighted_paths: Vec<Vec<(&'a str, usize)>>,
+    /// A color of highlighted edges
+    pub highlight_color: String,
+}
+
+impl<'a> Default for DotOptions<'a> {
+    fn default() -> DotOptions<'a> {
+        DotOptions {
+            is_numbered: false,
+            forward_main_color: "#ff0000".to_string(),
+            backward_main_color: "#0000ff".to_string(),
+            edge_labels: DotEdgeLabels::AsNodes,
+            align_trunk: false,
+            depth_colors: vec![],
+            highlighted_paths: vec![],
+            highlight_color: "#00a000".to_string(),
+        }
+    }
+}
+
+fn escape_dot(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn format_attributes(attributes: &[String]) -> String {
+    if attributes.is_empty() {
+        String::new()
+    } else {
+        format!(" [{}]", attributes.join(","))
+    }
+}
+
+impl<'a> Lattice<'a> {
+    /// Returns a DOT string of the lattice with the given options.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - Options of rendering
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use parattice::dot::DotOptions;
+    /// use parattice::LatticeKMP;
+    /// use parattice::PaRattice;
+    ///
+    /// let paradict = vec![
+    ///     vec![
+    ///         vec!["blood", "stem", "cell"],
+    ///         vec!["造血", "幹", "細胞"],
+    ///         vec!["hematopoietic", "stem", "cell"],
+    ///     ],
+    ///     vec![
+    ///         vec!["造血", "幹", "細胞", "移植"],
+    ///         vec!["hematopoietic", "stem", "cell", "transplantation"],
+    ///     ],
+    ///     vec![vec!["stem", "cell"], vec!["幹", "細胞"]],
+    ///     vec![
+    ///         vec!["幹", "細胞", "移植"],
+    ///         vec!["rescue", "transplant"],
+    ///         vec!["stem", "cell", "rescue"],
+    ///     ],
+    ///     vec![vec!["rescue"], vec!["救命"]],
+    ///     vec![vec!["blood"], vec!["血液"]],
+    /// ];
+    /// let parattice = PaRattice::new(paradict);
+    /// let words = vec!["造血", "幹", "細胞", "移植"];
+    /// let lattice = parattice.get_lattice(&words, true, 2);
+    ///
+    /// let kmp = LatticeKMP::new(vec!["幹", "細胞"]);
+    /// let options = DotOptions {
+    ///     is_numbered: true,
+    ///     align_trunk: true,
+    ///     highlighted_paths: kmp.search(&lattice),
+    ///     ..DotOptions::default()
+    /// };
+    /// let dot = lattice.dump_dot_with_options(&options);
+    /// println!("{}", dot);
+    /// ```
+    pub fn dump_dot_with_options(&self, options: &DotOptions) -> String {
+        let mut highlighted_edges = HashSet::new();
+        for path in &options.highlighted_paths {
+            for i in 1..path.len() {
+                highlighted_edges.insert((path[i - 1].1, path[i].0, path[i].1));
+            }
+        }
+        let is_trunk_edge = |i: usize, edge: (&str, usize)| {
+            self.trunk.contains_key(&i)
+                && self.trunk.contains_key(&edge.1)
+                && self.lattice[i].forward_main == Some(edge)
+        };
+        let mut result = "digraph { graph [rankdir=LR];\n".to_string();
+        for (i, node) in self.lattice.iter().enumerate() {
+            let mut attributes = if options.is_numbered {
+                vec![
+                    format!("label=\"{}\"", i),
+                    "shape=plaintext".to_string(),
+                    "width=\"0.1\"".to_string(),
+                ]
+            } else {
+                vec![
+                    "label=\"\"".to_string(),
+                    "shape=circle".to_string(),
+                    "width=\"0.1\"".to_string(),
+                ]
+            };
+            if !options.depth_colors.is_empty() {
+                let color = &options.depth_colors[node.depth % options.depth_colors.len()];
+                attributes.push(format!("style=filled,fillcolor=\"{}\"", color));
+            }
+            if options.align_trunk && self.trunk.contains_key(&i) {
+                attributes.push("group=\"trunk\"".to_string());
+            }
+            result += &format!("\"{}\"{};\n", i, format_attributes(&attributes));
+            for (j, &edge) in node.forwards.iter().enumerate() {
+                let is_forward_main = node.forward_main == Some(edge);
+                let is_backward_main = self.lattice[edge.1].backward_main == Some((edge.0, i));
+                let is_highlighted = highlighted_edges.contains(&(i, edge.0, edge.1));
+                let mut highlight_attributes = vec![];
+                if is_highlighted {
+                    highlight_attributes.push(format!("color=\"{}\"", options.highlight_color));
+                    highlight_attributes.push("penwidth=2".to_string());
+                }
+                let mut alignment_attributes = vec![];
+                if options.align_trunk && is_trunk_edge(i, edge) {
+                    alignment_attributes.push("weight=10".to_string());
+                }
+                match options.edge_labels {
+                    DotEdgeLabels::AsNodes => {
+                        let label_node = format!("{}-{}-{}", i, j, edge.1);
+                        let mut attributes = vec![
+                            format!("label=\"{}\"", escape_dot(edge.0)),
+                            "shape=box".to_string(),
+                        ];
+                        if is_highlighted {
+                            attributes.push(format!("color=\"{}\"", options.highlight_color));
+                        }
+                        if options.align_trunk && is_trunk_edge(i, edge) {
+                            attributes.push("group=\"trunk\"".to_string());
+                        }
+                        result +=
+                            &format!("\"{}\"{};\n", label_node, format_attributes(&attributes));
+                        let mut attributes = vec!["arrowhead=none".to_string()];
+                        if is_highlighted {
+                            attributes.extend(highlight_attributes.iter().cloned());
+                        } else if is_forward_main {
+                            attributes.push(format!("color=\"{}\"", options.forward_main_color));
+                        }
+                        attributes.extend(alignment_attributes.iter().cloned());
+                        result += &format!(
+                            "\"{}\" -> \"{}\"{};\n",
+                            i,
+                            label_node,
+                            format_attributes(&attributes)
+                        );
+                        let mut attributes = vec![];
+                        if is_highlighted {
+                            attributes.extend(highlight_attributes);
+                        } else if is_backward_main {
+                            attributes.push(format!("color=\"{}\"", options.backward_main_color));
+                        }
+                        attributes.extend(alignment_attributes);
+                        result += &format!(
+                            "\"{}\" -> \"{}\"{};\n",
+                            label_node,
+                            edge.1,
+                            format_attributes(&attributes)
+                        );
+                    }
+                    DotEdgeLabels::OnEdges => {
+                        let mut attributes = vec![format!("label=\"{}\"", escape_dot(edge.0))];
+                        if is_highlighted {
+                            attributes.extend(highlight_attributes);
+                        } else {
+                            let mut colors = vec![];
+                            if is_forward_main {
+                                colors.push(options.forward_main_color.as_str());
+                            }
+                            if is_backward_main {
+                                colors.push(options.backward_main_color.as_str());
+                            }
+                            if !colors.is_empty() {
+                                attributes.push(format!("color=\"{}\"", colors.join(":")));
+                            }
+                        }
+                        attributes.extend(alignment_attributes);
+                        result += &format!(
+                            "\"{}\" -> \"{}\"{};\n",
+                            i,
+                            edge.1,
+                            format_attributes(&attributes)
+                        );
+                    }
+                }
+            }
+        }
+        result += "}";
+        result
+    }
+}
@@ -0,0 +1,339 @@
+use std::error;
+use std::fmt;
+use std::io;
+
+/// An error of parsing a text representation of a lattice.
+#[derive(Debug, PartialEq)]
+pub struct ParseError {
+    /// A line number starting from 1, or 0 if the error is not related to a specific line
+    pub line: usize,
+    pub message: String,
+}
+
+impl ParseError {
+    pub(crate) fn new<S: Into<String>>(line: usize, message: S) -> ParseError {
+        ParseError {
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.line == 0 {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "line {}: {}", self.line, self.message)
+        }
+    }
+}
+
+impl error::Error for ParseError {}
+
+/// An error of editing a lattice.
+#[derive(Debug, PartialEq)]
+pub enum EditError {
+    /// The edge doesn't exist
+    EdgeNotFound,
+    /// The edge already exists
+    DuplicateEdge,
+    /// Trunk edges can't be removed
+    TrunkEdge,
+    /// The trunk position or span is out of the trunk or empty
+    InvalidTrunkSpan,
+    /// A branch must have at least one label
+    EmptyBranch,
+}
+
+impl fmt::Display for EditError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let message = match self {
+            EditError::EdgeNotFound => "the edge doesn't exist",
+            EditError::DuplicateEdge => "the edge already exists",
+            EditError::TrunkEdge => "trunk edges can't be removed",
+            EditError::InvalidTrunkSpan => "invalid trunk span",
+            EditError::EmptyBranch => "a branch must have at least one label",
+        };
+        write!(f, "{}", message)
+    }
+}
+
+impl error::Error for EditError {}
+
+/// A violation of lattice invariants found by `Lattice::validate`.
+#[derive(Debug, PartialEq)]
+pub enum ValidationError<T> {
+    /// The lattice has no nodes
+    Empty,
+    /// An edge refers to a node that doesn't exist
+    NodeOutOfRange { node: usize, edge: (T, usize) },
+    /// A forward edge doesn't have the corresponding backward edge
+    MissingBackwardEdge {
+        source: usize,
+        label: T,
+        target: usize,
+    },
+    /// A backward edge doesn't have the corresponding forward edge
+    MissingForwardEdge {
+        source: usize,
+        label: T,
+        target: usize,
+    },
+    /// An edge doesn't go from left to right
+    NotLeftToRight {
+        source: usize,
+        label: T,
+        target: usize,
+    },
+    /// A forward main edge is not in the forward edges of the node
+    ForwardMainNotFound { node: usize, edge: (T, usize) },
+    /// A backward main edge is not in the backward edges of the node
+    BackwardMainNotFound { node: usize, edge: (T, usize) },
+    /// A node other than the last node has no forward main edge
+    MissingForwardMain { node: usize },
+    /// A node other than the first node has no backward main edge
+    MissingBackwardMain { node: usize },
+    /// A node other than the first node has no backward edges
+    ExtraSource { node: usize },
+    /// A node other than the last node has no forward edges
+    ExtraSink { node: usize },
+    /// A node on the path of forward main edges from the first node has a wrong trunk position
+    TrunkPositionMismatch {
+        node: usize,
+        expected: usize,
+        actual: Option<usize>,
+    },
+    /// A trunk node is not on the path of forward main edges from the first node to the last
+    /// node
+    TrunkNodeNotOnPath { node: usize },
+    /// `capacity` is not equal to the number of edges
+    CapacityMismatch { capacity: usize, num_edges: usize },
+}
+
+impl<T: fmt::Display> fmt::Display for ValidationError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ValidationError::Empty => write!(f, "the lattice has no nodes"),
+            ValidationError::NodeOutOfRange { node, edge } => write!(
+                f,
+                "node {}: edge ({}, {}) refers to a node out of range",
+                node, edge.0, edge.1
+            ),
+            ValidationError::MissingBackwardEdge {
+                source,
+                label,
+                target,
+            } => write!(
+                f,
+                "edge {} -{}-> {} has no backward edge",
+                source, label, target
+            ),
+            ValidationError::MissingForwardEdge {
+                source,
+                label,
+                target,
+            } => write!(
+                f,
+                "edge {} -{}-> {} has no forward edge",
+                source, label, target
+            ),
+            ValidationError::NotLeftToRight {
+                source,
+                label,
+                target,
+            } => write!(
+                f,
+                "edge {} -{}-> {} doesn't go from left to right",
+                source, label, target
+            ),
+            ValidationError::ForwardMainNotFound { node, edge } => write!(
+                f,
+                "node {}: forward main edge ({}, {}) is not a forward edge",
+                node, edge.0, edge.1
+            ),
+            ValidationError::BackwardMainNotFound { node, edge } => write!(
+                f,
+                "node {}: backward main edge ({}, {}) is not a backward edge",
+                node, edge.0, edge.1
+            ),
+            ValidationError::MissingForwardMain { node } => {
+                write!(f, "node {}: no forward main edge", node)
+            }
+            ValidationError::MissingBackwardMain { node } => {
+                write!(f, "node {}: no backward main edge", node)
+            }
+            ValidationError::ExtraSource { node } => {
+                write!(f, "node {}: no backward edges", node)
+            }
+            ValidationError::ExtraSink { node } => write!(f, "node {}: no forward edges", node),
+            ValidationError::TrunkPositionMismatch {
+                node,
+                expected,
+                actual,
+            } => match actual {
+                Some(actual) => write!(
+                    f,
+                    "node {}: trunk position {} is expected, but {} is given",
+                    node, expected, actual
+                ),
+                None => write!(
+                    f,
+                    "node {}: trunk position {} is expected, but it is not in the trunk",
+                    node, expected
+                ),
+            },
+            ValidationError::TrunkNodeNotOnPath { node } => write!(
+                f,
+                "node {}: the trunk node is not on the path of forward main edges",
+                node
+            ),
+            ValidationError::CapacityMismatch {
+                capacity,
+                num_edges,
+            } => write!(
+                f,
+                "capacity {} is not equal to the number of edges {}",
+                capacity, num_edges
+            ),
+        }
+    }
+}
+
+impl<T: fmt::Debug + fmt::Display> error::Error for ValidationError<T> {}
+
+/// An error of decoding binary data of a lattice.
+#[derive(Debug, PartialEq)]
+pub enum DecodeError {
+    /// The data ends in the middle of an item that starts at the byte offset
+    Truncated { offset: usize },
+    /// A label at the byte offset is not valid UTF-8
+    InvalidUtf8 { offset: usize },
+    /// An edge of the node refers to a node that doesn't exist
+    NodeOutOfRange { node: usize, target: usize },
+    /// The number of nodes differs from the header
+    NodeCountMismatch { expected: usize, actual: usize },
+    /// Forward main edges from the first node don't reach the last node
+    MissingTrunk,
+    /// The format version is not supported
+    UnsupportedVersion(u16),
+    /// The flags contain unsupported sections
+    UnsupportedFlags(u16),
+    /// The checksum of the data doesn't match
+    ChecksumMismatch { expected: u32, actual: u32 },
+    /// The section of the flag is inconsistent with the lattice
+    InvalidSection(u16),
+    /// A variable-length integer at the byte offset is too long
+    InvalidVarint { offset: usize },
+    /// An edge of the node refers to a label that is not in the string table
+    LabelOutOfRange { node: usize, label: usize },
+    /// A main edge of the node is out of range or not one of its edges, or main edges of the
+    /// node off the trunk don't lead to the trunk
+    InvalidMainEdge { node: usize },
+    /// The given trunk differs from the trunk made from forward main edges
+    TrunkMismatch,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DecodeError::Truncated { offset } => {
+                write!(f, "offset {}: the data is truncated", offset)
+            }
+            DecodeError::InvalidUtf8 { offset } => {
+                write!(f, "offset {}: the label is not valid UTF-8", offset)
+            }
+            DecodeError::NodeOutOfRange { node, target } => write!(
+                f,
+                "node {}: an edge refers to node {} out of range",
+                node, target
+            ),
+            DecodeError::NodeCountMismatch { expected, actual } => write!(
+                f,
+                "{} nodes are expected, but {} nodes are given",
+                expected, actual
+            ),
+            DecodeError::MissingTrunk => write!(f, "the trunk doesn't reach the last node"),
+            DecodeError::UnsupportedVersion(version) => {
+                write!(f, "format version {} is not supported", version)
+            }
+            DecodeError::UnsupportedFlags(flags) => {
+                write!(f, "flags {:#06x} are not supported", flags)
+            }
+            DecodeError::ChecksumMismatch { expected, actual } => write!(
+                f,
+                "checksum {:#010x} is expected, but {:#010x} is given",
+                expected, actual
+            ),
+            DecodeError::InvalidSection(flag) => {
+                write!(f, "the section of flag {:#06x} is invalid", flag)
+            }
+            DecodeError::InvalidVarint { offset } => {
+                write!(f, "offset {}: the integer is too long", offset)
+            }
+            DecodeError::LabelOutOfRange { node, label } => write!(
+                f,
+                "node {}: an edge refers to label {} out of range",
+                node, label
+            ),
+            DecodeError::InvalidMainEdge { node } => {
+                write!(f, "node {}: the main edge is out of range", node)
+            }
+            DecodeError::TrunkMismatch => {
+                write!(f, "the trunk doesn't follow forward main edges")
+            }
+        }
+    }
+}
+
+impl error::Error for DecodeError {}
+
+/// An error of reading or writing a lattice container.
+#[derive(Debug)]
+pub enum ContainerError {
+    /// An I/O error of the underlying reader or writer
+    Io(io::Error),
+    /// The data doesn't start with the magic number of containers
+    NotContainer,
+    /// The container or a lattice in it is broken
+    Decode(DecodeError),
+    /// A lattice of the key is already written, or the index of the container has the key twice
+    DuplicateKey { document: String, sentence: u64 },
+}
+
+impl From<io::Error> for ContainerError {
+    fn from(e: io::Error) -> ContainerError {
+        ContainerError::Io(e)
+    }
+}
+
+impl From<DecodeError> for ContainerError {
+    fn from(e: DecodeError) -> ContainerError {
+        ContainerError::Decode(e)
+    }
+}
+
+impl fmt::Display for ContainerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ContainerError::Io(e) => write!(f, "I/O error: {}", e),
+            ContainerError::NotContainer => write!(f, "the data is not a lattice container"),
+            ContainerError::Decode(e) => write!(f, "broken container: {}", e),
+            ContainerError::DuplicateKey { document, sentence } => write!(
+                f,
+                "duplicate lattices of document {:?} sentence {}",
+                document, sentence
+            ),
+        }
+    }
+}
+
+impl error::Error for ContainerError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            ContainerError::Io(e) => Some(e),
+            ContainerError::Decode(e) => Some(e),
+            _ => None,
+        }
+    }
+}
@@ -1,11 +1,14 @@
+use crate::error::DecodeError;
 use crate::lattice::Lattice;
 use crate::lattice_kmp::LatticeKMP;
 use crate::parattice::PaRattice;
 
 use libc::c_char;
+use libc::c_int;
 use std::ffi::CStr;
 use std::ffi::CString;
 use std::mem;
+use std::ptr;
 use std::slice;
 use std::str;
 
@@ -74,14 +77,72 @@ pub unsafe extern "C" fn parattice_parattice_get_lattice(
     ))
 }
 
+/// Returns a lattice from the given binary data, or null if the data is broken. Use
+/// `parattice_lattice_try_new_from_bytes` to know why the data is rejected.
+///
+/// # Safety
+///
+/// `data` must point to `length` bytes that outlive the lattice.
 #[no_mangle]
 pub unsafe extern "C" fn parattice_lattice_new_from_bytes<'a>(
     data: *const u8,
     length: usize,
 ) -> *mut Lattice<'a> {
-    Box::into_raw(Box::new(Lattice::new_from_bytes(slice::from_raw_parts(
-        data, length,
-    ))))
+    let mut error_code = 0;
+    parattice_lattice_try_new_from_bytes(data, length, &mut error_code)
+}
+
+/// Returns a lattice from the given binary data, or null if the data is broken. `error_code` is
+/// set to 0 on success, or one of the following codes:
+///
+/// * 1 - The data is truncated
+/// * 2 - A label is not valid UTF-8
+/// * 3 - An edge refers to a node out of range
+/// * 4 - The number of nodes differs from the header
+/// * 5 - The trunk doesn't reach the last node
+/// * 6 - The format version is not supported
+/// * 7 - The data has unsupported optional sections
+/// * 8 - The checksum doesn't match
+/// * 9 - An optional section is broken
+/// * 10 - An integer is too long
+/// * 11 - A label is not in the string table
+/// * 12 - A main edge is out of range
+/// * 13 - The trunk doesn't follow forward main edges
+///
+/// # Safety
+///
+/// `data` must point to `length` bytes that outlive the lattice, and `error_code` must be a
+/// valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn parattice_lattice_try_new_from_bytes<'a>(
+    data: *const u8,
+    length: usize,
+    error_code: *mut c_int,
+) -> *mut Lattice<'a> {
+    match Lattice::try_from_bytes(slice::from_raw_parts(data, length)) {
+        Ok(lattice) => {
+            *error_code = 0;
+            Box::into_raw(Box::new(lattice))
+        }
+        Err(e) => {
+            *error_code = match e {
+                DecodeError::Truncated { .. } => 1,
+                DecodeError::InvalidUtf8 { .. } => 2,
+                DecodeError::NodeOutOfRange { .. } => 3,
+                DecodeError::NodeCountMismatch { .. } => 4,
+                DecodeError::MissingTrunk => 5,
+                DecodeError::UnsupportedVersion(_) => 6,
+                DecodeError::UnsupportedFlags(_) => 7,
+                DecodeError::ChecksumMismatch { .. } => 8,
+                DecodeError::InvalidSection(_) => 9,
+                DecodeError::InvalidVarint { .. } => 10,
+                DecodeError::LabelOutOfRange { .. } => 11,
+                DecodeError::InvalidMainEdge { .. } => 12,
+                DecodeError::TrunkMismatch => 13,
+            };
+            ptr::null_mut()
+        }
+    }
 }
 
 #[no_mangle]
@@ -121,6 +182,18 @@ pub unsafe extern "C" fn parattice_lattice_dump_dot(
     c_string.into_raw()
 }
 
+/// Returns the lattice in GraphML. The string must be freed with `parattice_free_string`.
+///
+/// # Safety
+///
+/// `lattice` must be a valid pointer to a lattice.
+#[no_mangle]
+pub unsafe extern "C" fn parattice_lattice_dump_graphml(lattice: *const Lattice) -> *mut c_char {
+    let s = (*lattice).dump_graphml();
+    let c_string = CString::new(s).unwrap();
+    c_string.into_raw()
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn parattice_lattice_get_trunk_span(
     lattice: *const Lattice,
@@ -0,0 +1,277 @@
+use crate::lattice::Lattice;
+use crate::lattice::LatticeNode;
+use crate::lattice::SearchIndexNode;
+use crate::lattice_graph::search_index_nodes;
+use crate::lattice_graph::trunk_span;
+use crate::lattice_graph::trunk_spans;
+use crate::lattice_graph::LatticeGraph;
+
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::slice;
+
+pub(crate) const NONE: u32 = u32::MAX;
+
+/// An immutable lattice with compact memory layout.
+///
+/// Edges of all nodes are stored in flat arrays with offsets (CSR), and labels are interned in
+/// a sorted table, so edges of each node keep the same order as `Lattice`.
+#[derive(Debug, Eq, PartialEq)]
+pub struct FrozenLattice<'a> {
+    pub(crate) labels: Vec<&'a str>,
+    pub(crate) forward_offsets: Vec<u32>,
+    pub(crate) forward_edges: Vec<(u32, u32)>,
+    pub(crate) backward_offsets: Vec<u32>,
+    pub(crate) backward_edges: Vec<(u32, u32)>,
+    pub(crate) forward_main: Vec<u32>,
+    pub(crate) backward_main: Vec<u32>,
+    pub(crate) trunk_positions: Vec<u32>,
+    pub(crate) depths: Vec<u32>,
+}
+
+/// An iterator over edges of a node of `FrozenLattice`.
+pub struct FrozenEdges<'b, 'a> {
+    labels: &'b [&'a str],
+    edges: slice::Iter<'b, (u32, u32)>,
+}
+
+impl<'b, 'a> Iterator for FrozenEdges<'b, 'a> {
+    type Item = (&'a str, usize);
+
+    fn next(&mut self) -> Option<(&'a str, usize)> {
+        self.edges
+            .next()
+            .map(|&(label_id, node_id)| (self.labels[label_id as usize], node_id as usize))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.edges.size_hint()
+    }
+}
+
+fn to_u32(x: usize) -> u32 {
+    assert!(x < NONE as usize, "the lattice is too large to freeze");
+    x as u32
+}
+
+/// Returns offsets, edges and main edge IDs of the given edge sets.
+fn flatten_edges<'a, 'b, I>(
+    node_edges: I,
+    label_ids: &HashMap<&'a str, u32>,
+    num_nodes: usize,
+    num_edges: usize,
+) -> (Vec<u32>, Vec<(u32, u32)>, Vec<u32>)
+where
+    'a: 'b,
+    I: Iterator<Item = (&'b BTreeSet<(&'a str, usize)>, Option<(&'a str, usize)>)>,
+{
+    let mut offsets = Vec::with_capacity(num_nodes + 1);
+    let mut edges = Vec::with_capacity(num_edges);
+    let mut main = Vec::with_capacity(num_nodes);
+    offsets.push(0);
+    for (node_edges, main_edge) in node_edges {
+        main.push(main_edge.map_or(NONE, |main_edge| {
+            to_u32(edges.len() + node_edges.range(..main_edge).count())
+        }));
+        for &(s, node_id) in node_edges {
+            edges.push((label_ids[s], to_u32(node_id)));
+        }
+        offsets.push(to_u32(edges.len()));
+    }
+    (offsets, edges, main)
+}
+
+impl<'a> Lattice<'a> {
+    /// Returns an immutable copy of the lattice with compact memory layout.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lattice has `u32::MAX` or more nodes or edges.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use parattice::PaRattice;
+    ///
+    /// let paradict = vec![
+    ///     vec![
+    ///         vec!["blood", "stem", "cell"],
+    ///         vec!["造血", "幹", "細胞"],
+    ///         vec!["hematopoietic", "stem", "cell"],
+    ///     ],
+    ///     vec![
+    ///         vec!["造血", "幹", "細胞", "移植"],
+    ///         vec!["hematopoietic", "stem", "cell", "transplantation"],
+    ///     ],
+    ///     vec![vec!["stem", "cell"], vec!["幹", "細胞"]],
+    ///     vec![
+    ///         vec!["幹", "細胞", "移植"],
+    ///         vec!["rescue", "transplant"],
+    ///         vec!["stem", "cell", "rescue"],
+    ///     ],
+    ///     vec![vec!["rescue"], vec!["救命"]],
+    ///     vec![vec!["blood"], vec!["血液"]],
+    /// ];
+    /// let parattice = PaRattice::new(paradict);
+    /// let words = vec!["造血", "幹", "細胞", "移植"];
+    /// let lattice = parattice.get_lattice(&words, true, 2);
+    ///
+    /// let frozen = lattice.freeze();
+    /// assert_eq!(lattice.get_trunk_spans(), frozen.get_trunk_spans());
+    /// ```
+    pub fn freeze(&self) -> FrozenLattice<'a> {
+        let mut labels: Vec<&'a str> = self
+            .lattice
+            .iter()
+            .flat_map(|node| node.forwards.iter().map(|&(s, _)| s))
+            .collect();
+        labels.sort();
+        labels.dedup();
+        let label_ids: HashMap<&'a str, u32> = labels
+            .iter()
+            .enumerate()
+            .map(|(i, &s)| (s, to_u32(i)))
+            .collect();
+        let (forward_offsets, forward_edges, forward_main) = flatten_edges(
+            self.lattice
+                .iter()
+                .map(|node| (&node.forwards, node.forward_main)),
+            &label_ids,
+            self.lattice.len(),
+            self.capacity,
+        );
+        let (backward_offsets, backward_edges, backward_main) = flatten_edges(
+            self.lattice
+                .iter()
+                .map(|node| (&node.backwards, node.backward_main)),
+            &label_ids,
+            self.lattice.len(),
+            self.capacity,
+        );
+        let mut trunk_positions = vec![NONE; self.lattice.len()];
+        for (&node_id, &position) in &self.trunk {
+            trunk_positions[node_id] = to_u32(position);
+        }
+        let depths = self.lattice.iter().map(|node| to_u32(node.depth)).collect();
+        FrozenLattice {
+            labels,
+            forward_offsets,
+            forward_edges,
+            backward_offsets,
+            backward_edges,
+            forward_main,
+            backward_main,
+            trunk_positions,
+            depths,
+        }
+    }
+}
+
+impl<'a> FrozenLattice<'a> {
+    /// Returns the number of edges.
+    pub fn num_edges(&self) -> usize {
+        self.forward_edges.len()
+    }
+
+    /// Returns the interned labels in ascending order.
+    pub fn labels(&self) -> &[&'a str] {
+        &self.labels
+    }
+
+    /// Returns the depth of the given node.
+    pub fn depth(&self, node_id: usize) -> usize {
+        self.depths[node_id] as usize
+    }
+
+    /// Returns a mutable lattice of the frozen lattice.
+    pub fn to_lattice(&self) -> Lattice<'a> {
+        let nodes: Vec<LatticeNode<'a>> = (0..self.num_nodes())
+            .map(|node_id| {
+                let mut node = LatticeNode::new(
+                    self.forward_main(node_id),
+                    self.backward_main(node_id),
+                    self.depth(node_id),
+                );
+                node.forwards.extend(self.forwards(node_id));
+                node.backwards.extend(self.backwards(node_id));
+                node
+            })
+            .collect();
+        Lattice::from_nodes(&nodes).0
+    }
+
+    /// Returns a trunk path of the given path.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - A path of the lattice. (e.g. a result of LatticeKMP)
+    pub fn get_trunk_span(&self, path: Vec<(&'a str, usize)>) -> Vec<(&'a str, usize)> {
+        trunk_span(self, path)
+    }
+
+    /// Returns trunk positions of the left and the right trunk nodes for each node ID.
+    pub fn get_trunk_spans(&self) -> Vec<(usize, usize)> {
+        trunk_spans(self)
+    }
+
+    /// Returns a vector of SearchIndexNode for the search index such as Elasticsearch.
+    pub fn dump_for_search_index(&self) -> Vec<SearchIndexNode<'a>> {
+        search_index_nodes(self, self.num_edges())
+    }
+
+    fn edges<'b>(
+        &'b self,
+        offsets: &[u32],
+        edges: &'b [(u32, u32)],
+        node_id: usize,
+    ) -> FrozenEdges<'b, 'a> {
+        FrozenEdges {
+            labels: &self.labels,
+            edges: edges[offsets[node_id] as usize..offsets[node_id + 1] as usize].iter(),
+        }
+    }
+
+    fn main_edge(&self, edges: &[(u32, u32)], edge_id: u32) -> Option<(&'a str, usize)> {
+        if edge_id == NONE {
+            return None;
+        }
+        let (label_id, node_id) = edges[edge_id as usize];
+        Some((self.labels[label_id as usize], node_id as usize))
+    }
+}
+
+impl<'a> LatticeGraph<&'a str> for FrozenLattice<'a> {
+    type Edges<'b>
+        = FrozenEdges<'b, 'a>
+    where
+        Self: 'b;
+
+    fn num_nodes(&self) -> usize {
+        self.trunk_positions.len()
+    }
+
+    fn forwards(&self, node_id: usize) -> FrozenEdges<'_, 'a> {
+        self.edges(&self.forward_offsets, &self.forward_edges, node_id)
+    }
+
+    fn backwards(&self, node_id: usize) -> FrozenEdges<'_, 'a> {
+        self.edges(&self.backward_offsets, &self.backward_edges, node_id)
+    }
+
+    fn forward_main(&self, node_id: usize) -> Option<(&'a str, usize)> {
+        self.main_edge(&self.forward_edges, self.forward_main[node_id])
+    }
+
+    fn backward_main(&self, node_id: usize) -> Option<(&'a str, usize)> {
+        self.main_edge(&self.backward_edges, self.backward_main[node_id])
+    }
+
+    fn trunk_position(&self, node_id: usize) -> Option<usize> {
+        let position = self.trunk_positions[node_id];
+        if position == NONE {
+            None
+        } else {
+            Some(position as usize)
+        }
+    }
+}
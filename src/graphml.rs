@@ -0,0 +1,82 @@
+use crate::lattice::Lattice;
+use crate::utils::escape_xml;
+
+impl<'a> Lattice<'a> {
+    /// Returns a GraphML string of the lattice.
+    ///
+    /// Nodes have `depth` and `trunk_position` attributes, where `trunk_position` is given only
+    /// for trunk nodes. Edges have `label`, `forward_main` and `backward_main` attributes.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use parattice::PaRattice;
+    ///
+    /// let paradict = vec![
+    ///     vec![
+    ///         vec!["blood", "stem", "cell"],
+    ///         vec!["造血", "幹", "細胞"],
+    ///         vec!["hematopoietic", "stem", "cell"],
+    ///     ],
+    ///     vec![
+    ///         vec!["造血", "幹", "細胞", "移植"],
+    ///         vec!["hematopoietic", "stem", "cell", "transplantation"],
+    ///     ],
+    ///     vec![vec!["stem", "cell"], vec!["幹", "細胞"]],
+    ///     vec![
+    ///         vec!["幹", "細胞", "移植"],
+    ///         vec!["rescue", "transplant"],
+    ///         vec!["stem", "cell", "rescue"],
+    ///     ],
+    ///     vec![vec!["rescue"], vec!["救命"]],
+    ///     vec![vec!["blood"], vec!["血液"]],
+    /// ];
+    /// let parattice = PaRattice::new(paradict);
+    /// let words = vec!["造血", "幹", "細胞", "移植"];
+    /// let lattice = parattice.get_lattice(&words, true, 2);
+    ///
+    /// let graphml = lattice.dump_graphml();
+    /// println!("{}", graphml);
+    /// ```
+    pub fn dump_graphml(&self) -> String {
+        let mut result = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+                          <graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n\
+                          <key id=\"depth\" for=\"node\" attr.name=\"depth\" attr.type=\"int\"/>\n\
+                          <key id=\"trunk_position\" for=\"node\" attr.name=\"trunk_position\" attr.type=\"int\"/>\n\
+                          <key id=\"label\" for=\"edge\" attr.name=\"label\" attr.type=\"string\"/>\n\
+                          <key id=\"forward_main\" for=\"edge\" attr.name=\"forward_main\" attr.type=\"boolean\"/>\n\
+                          <key id=\"backward_main\" for=\"edge\" attr.name=\"backward_main\" attr.type=\"boolean\"/>\n\
+                          <graph id=\"lattice\" edgedefault=\"directed\">\n"
+            .to_string();
+        for (i, node) in self.lattice.iter().enumerate() {
+            result += &format!(
+                "<node id=\"n{}\"><data key=\"depth\">{}</data>",
+                i, node.depth
+            );
+            if let Some(position) = self.trunk.get(&i) {
+                result += &format!("<data key=\"trunk_position\">{}</data>", position);
+            }
+            result += "</node>\n";
+        }
+        let mut edge_id = 0;
+        for (i, node) in self.lattice.iter().enumerate() {
+            for &edge in &node.forwards {
+                result += &format!(
+                    "<edge id=\"e{}\" source=\"n{}\" target=\"n{}\">\
+                     <data key=\"label\">{}</data>\
+                     <data key=\"forward_main\">{}</data>\
+                     <data key=\"backward_main\">{}</data></edge>\n",
+                    edge_id,
+                    i,
+                    edge.1,
+                    escape_xml(edge.0),
+                    node.forward_main == Some(edge),
+                    self.lattice[edge.1].backward_main == Some((edge.0, i)),
+                );
+                edge_id += 1;
+            }
+        }
+        result += "</graph>\n</graphml>\n";
+        result
+    }
+}
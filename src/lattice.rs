@@ -1,24 +1,58 @@
+use crate::binary::BinaryOptions;
+use crate::binary::Layout;
+use crate::binary::MAGIC;
+use crate::dot::DotOptions;
+use crate::error::DecodeError;
+use crate::lattice_graph::search_index_nodes;
+use crate::lattice_graph::trunk_span;
+use crate::lattice_graph::trunk_spans;
+
+use std::cmp;
 use std::collections::BTreeSet;
 use std::collections::HashMap;
 use std::collections::VecDeque;
+use std::hash::Hash;
 use std::str;
 use std::usize;
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A type of edge labels.
+///
+/// Labels are `&str` by default, but token IDs such as `u32` can be used to avoid hashing and
+/// comparing strings. The default value is used as a placeholder label of the first element of
+/// a path.
+pub trait Label: Copy + Ord + Hash + Default {}
+
+impl<T: Copy + Ord + Hash + Default> Label for T {}
+
 #[derive(Debug, Eq, PartialEq)]
-pub struct LatticeNode<'a> {
-    pub forwards: BTreeSet<(&'a str, usize)>,
-    pub backwards: BTreeSet<(&'a str, usize)>,
-    pub forward_main: Option<(&'a str, usize)>,
-    pub backward_main: Option<(&'a str, usize)>,
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "T: Serialize",
+        deserialize = "T: Ord + Deserialize<'de>"
+    ))
+)]
+pub struct GenericLatticeNode<T> {
+    pub forwards: BTreeSet<(T, usize)>,
+    pub backwards: BTreeSet<(T, usize)>,
+    pub forward_main: Option<(T, usize)>,
+    pub backward_main: Option<(T, usize)>,
     pub depth: usize,
 }
 
-impl<'a> LatticeNode<'a> {
-    pub fn new<T1: Into<Option<(&'a str, usize)>>, T2: Into<Option<(&'a str, usize)>>>(
+/// A node of a lattice with `&'a str` labels by default.
+pub type LatticeNode<'a, T = &'a str> = GenericLatticeNode<T>;
+
+impl<'a, T: Label> LatticeNode<'a, T> {
+    pub fn new<T1: Into<Option<(T, usize)>>, T2: Into<Option<(T, usize)>>>(
         forward_main: T1,
         backward_main: T2,
         depth: usize,
-    ) -> LatticeNode<'a> {
+    ) -> LatticeNode<'a, T> {
         let mut forwards = BTreeSet::new();
         let mut backwards = BTreeSet::new();
         let forward_main = forward_main.into();
@@ -38,15 +72,23 @@ impl<'a> LatticeNode<'a> {
         }
     }
 
-    pub fn insert_forward(&mut self, edge_str: &'a str, edge_target: usize) {
+    pub fn insert_forward(&mut self, edge_str: T, edge_target: usize) {
         self.forwards.insert((edge_str, edge_target));
     }
 
-    pub fn insert_backward(&mut self, edge_str: &'a str, edge_target: usize) {
+    pub fn insert_backward(&mut self, edge_str: T, edge_target: usize) {
         self.backwards.insert((edge_str, edge_target));
     }
 }
 
+/// Weights of edges keyed by the source node ID, the label and the target node ID.
+pub type EdgeWeights<'a> = HashMap<(usize, &'a str, usize), f64>;
+
+/// Weights of edges of a lattice with owned labels keyed by the source node ID, the label and
+/// the target node ID.
+pub type OwnedEdgeWeights = HashMap<(usize, String, usize), f64>;
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct SearchIndexNode<'a> {
     pub text: &'a str,
     pub offset: (usize, usize),
@@ -54,14 +96,249 @@ pub struct SearchIndexNode<'a> {
     pub length: usize,
 }
 
-#[derive(Debug, Eq, PartialEq)]
-pub struct Lattice<'a> {
-    pub lattice: Vec<LatticeNode<'a>>,
+/// A paraphrase lattice.
+///
+/// With the `serde` feature, a lattice is serialized as follows:
+///
+/// ```json
+/// {
+///   "nodes": [
+///     {
+///       "forwards": [["造血", 1], ["blood", 2]],
+///       "backwards": [],
+///       "forward_main": ["造血", 1],
+///       "backward_main": null,
+///       "depth": 0
+///     },
+///     ...
+///   ],
+///   "trunk": [0, 1, 3],
+///   "capacity": 4
+/// }
+/// ```
+///
+/// * `nodes` - Nodes from left to right. Each edge is a pair of a label and the node ID of the
+///   other end. `forward_main` and `backward_main` are edges on the main branch, and they are
+///   null only for the first and the last nodes respectively.
+/// * `trunk` - Node IDs of the trunk from left to right
+/// * `capacity` - A number of edges
+///
+/// Deserialization fails if the trunk or the capacity is inconsistent with the nodes.
+///
+/// `Lattice` borrows labels from the input, so labels that contain escaped characters, such as
+/// `"\u9020\u8840"`, can't be deserialized into it from JSON strings. Deserialize such input into
+/// `GenericLattice<String>` and borrow labels from it with `to_lattice()`.
+///
+/// Edge labels are `&str` by default. Lattices of other label types are generated by
+/// `PaRattice` of the same label type, and they support the operations that don't need label
+/// strings, such as `LatticeKMP` and `get_trunk_spans()`.
+#[derive(Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(bound(serialize = "T: Serialize")))]
+pub struct GenericLattice<T> {
+    #[cfg_attr(feature = "serde", serde(rename = "nodes"))]
+    pub lattice: Vec<GenericLatticeNode<T>>,
+    #[cfg_attr(feature = "serde", serde(with = "trunk_as_node_ids"))]
     pub trunk: HashMap<usize, usize>,
     pub capacity: usize,
 }
 
-fn usize_to_vec(x: usize) -> Vec<u8> {
+/// A paraphrase lattice with `&'a str` labels by default. See `GenericLattice`.
+pub type Lattice<'a, T = &'a str> = GenericLattice<T>;
+
+impl GenericLattice<String> {
+    /// Returns a lattice that borrows labels of the lattice with owned labels.
+    ///
+    /// Node IDs, the trunk and the capacity are kept as they are.
+    pub fn to_lattice(&self) -> Lattice<'_> {
+        fn borrow_edge(edge: &(String, usize)) -> (&str, usize) {
+            (edge.0.as_str(), edge.1)
+        }
+        Lattice {
+            lattice: self
+                .lattice
+                .iter()
+                .map(|node| LatticeNode {
+                    forwards: node.forwards.iter().map(borrow_edge).collect(),
+                    backwards: node.backwards.iter().map(borrow_edge).collect(),
+                    forward_main: node.forward_main.as_ref().map(borrow_edge),
+                    backward_main: node.backward_main.as_ref().map(borrow_edge),
+                    depth: node.depth,
+                })
+                .collect(),
+            trunk: self.trunk.clone(),
+            capacity: self.capacity,
+        }
+    }
+}
+
+impl<'a> Lattice<'a> {
+    /// Returns a lattice that owns labels of the lattice. This is the inverse of
+    /// `GenericLattice::<String>::to_lattice()`.
+    pub fn to_owned_lattice(&self) -> GenericLattice<String> {
+        fn own_edge(edge: &(&str, usize)) -> (String, usize) {
+            (edge.0.to_string(), edge.1)
+        }
+        GenericLattice {
+            lattice: self
+                .lattice
+                .iter()
+                .map(|node| GenericLatticeNode {
+                    forwards: node.forwards.iter().map(own_edge).collect(),
+                    backwards: node.backwards.iter().map(own_edge).collect(),
+                    forward_main: node.forward_main.as_ref().map(own_edge),
+                    backward_main: node.backward_main.as_ref().map(own_edge),
+                    depth: node.depth,
+                })
+                .collect(),
+            trunk: self.trunk.clone(),
+            capacity: self.capacity,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+mod trunk_as_node_ids {
+    use serde::ser::Error;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::collections::HashMap;
+
+    pub fn serialize<S: Serializer>(
+        trunk: &HashMap<usize, usize>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut node_ids = vec![None; trunk.len()];
+        for (&node_id, &orig_node_id) in trunk {
+            match node_ids.get_mut(orig_node_id) {
+                Some(entry @ None) => *entry = Some(node_id),
+                _ => {
+                    return Err(S::Error::custom(format!(
+                        "invalid trunk position {} of node {}",
+                        orig_node_id, node_id
+                    )))
+                }
+            }
+        }
+        // all positions are filled because they are distinct and less than the length
+        let node_ids: Vec<usize> = node_ids.into_iter().map(Option::unwrap).collect();
+        node_ids.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<HashMap<usize, usize>, D::Error> {
+        let node_ids = Vec::<usize>::deserialize(deserializer)?;
+        Ok(node_ids
+            .into_iter()
+            .enumerate()
+            .map(|(orig_node_id, node_id)| (node_id, orig_node_id))
+            .collect())
+    }
+}
+
+/// Deserializes a lattice after checking that the trunk follows forward main edges from the
+/// first node, main edges lead to the trunk, and the capacity is equal to the number of edges.
+#[cfg(feature = "serde")]
+impl<'de, T: Ord + Deserialize<'de>> Deserialize<'de> for GenericLattice<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use crate::error::ValidationError;
+        use serde::de::Error;
+
+        #[derive(Deserialize)]
+        #[serde(bound(deserialize = "T: Ord + Deserialize<'de>"))]
+        struct UncheckedLattice<T> {
+            nodes: Vec<GenericLatticeNode<T>>,
+            #[serde(with = "trunk_as_node_ids")]
+            trunk: HashMap<usize, usize>,
+            capacity: usize,
+        }
+
+        let lattice = UncheckedLattice::<T>::deserialize(deserializer)?;
+        if trunk_of_nodes(&lattice.nodes).map_err(D::Error::custom)? != lattice.trunk {
+            return Err(D::Error::custom(DecodeError::TrunkMismatch));
+        }
+        let num_edges = lattice.nodes.iter().fold(0, |sum, x| sum + x.forwards.len());
+        if num_edges != lattice.capacity {
+            return Err(D::Error::custom(ValidationError::<&str>::CapacityMismatch {
+                capacity: lattice.capacity,
+                num_edges,
+            }));
+        }
+        Ok(GenericLattice {
+            lattice: lattice.nodes,
+            trunk: lattice.trunk,
+            capacity: lattice.capacity,
+        })
+    }
+}
+
+/// Returns the trunk that follows forward main edges from the first node after checking edges
+/// and main edges of the nodes.
+pub(crate) fn trunk_of_nodes<T: Ord>(
+    lattice: &[GenericLatticeNode<T>],
+) -> Result<HashMap<usize, usize>, DecodeError> {
+    let num_nodes = lattice.len();
+    for (node_id, node) in lattice.iter().enumerate() {
+        let edges = node.forwards.iter().chain(&node.backwards);
+        for &(_, target) in edges.chain(&node.forward_main).chain(&node.backward_main) {
+            if target >= num_nodes {
+                return Err(DecodeError::NodeOutOfRange {
+                    node: node_id,
+                    target,
+                });
+            }
+        }
+    }
+    if lattice.is_empty() {
+        return Err(DecodeError::MissingTrunk);
+    }
+    let mut trunk = HashMap::new();
+    let mut node_id = 0;
+    let mut orig_node_id = 0;
+    trunk.insert(0, 0);
+    while let Some((_, next_node_id)) = lattice[node_id].forward_main.as_ref() {
+        if *next_node_id <= node_id {
+            return Err(DecodeError::MissingTrunk);
+        }
+        node_id = *next_node_id;
+        orig_node_id += 1;
+        trunk.insert(node_id, orig_node_id);
+    }
+    if node_id != num_nodes - 1 {
+        return Err(DecodeError::MissingTrunk);
+    }
+    check_main_edges(lattice, &trunk)?;
+    Ok(trunk)
+}
+
+/// Checks that main edges are edges of their nodes, and main edges of nodes off the trunk go
+/// left and right so that they reach the trunk in both directions.
+pub(crate) fn check_main_edges<T: Ord>(
+    lattice: &[GenericLatticeNode<T>],
+    trunk: &HashMap<usize, usize>,
+) -> Result<(), DecodeError> {
+    for (node_id, node) in lattice.iter().enumerate() {
+        let is_own_edge = node
+            .forward_main
+            .as_ref()
+            .is_none_or(|edge| node.forwards.contains(edge))
+            && node
+                .backward_main
+                .as_ref()
+                .is_none_or(|edge| node.backwards.contains(edge));
+        let reaches_trunk = trunk.contains_key(&node_id)
+            || match (&node.backward_main, &node.forward_main) {
+                (Some((_, prev)), Some((_, next))) => *prev < node_id && node_id < *next,
+                _ => false,
+            };
+        if !is_own_edge || !reaches_trunk {
+            return Err(DecodeError::InvalidMainEdge { node: node_id });
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn usize_to_vec(x: usize) -> Vec<u8> {
     vec![
         x as u8,
         (x >> 8) as u8,
@@ -85,6 +362,108 @@ fn vec_to_usize(x: &[u8]) -> usize {
         | (x[7] as usize) << 56
 }
 
+pub(crate) struct ByteReader<'a> {
+    pub(crate) data: &'a [u8],
+    pub(crate) offset: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    pub(crate) fn read_bytes(&mut self, length: usize) -> Result<&'a [u8], DecodeError> {
+        if self.data.len() - self.offset < length {
+            return Err(DecodeError::Truncated {
+                offset: self.offset,
+            });
+        }
+        let bytes = &self.data[self.offset..self.offset + length];
+        self.offset += length;
+        Ok(bytes)
+    }
+
+    pub(crate) fn read_usize(&mut self) -> Result<usize, DecodeError> {
+        self.read_bytes(8).map(vec_to_usize)
+    }
+
+    /// Reads an unsigned LEB128 integer.
+    pub(crate) fn read_varint(&mut self) -> Result<u64, DecodeError> {
+        let offset = self.offset;
+        let mut result = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.read_bytes(1)?[0];
+            if shift == 63 && byte > 1 {
+                break;
+            }
+            result |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(result);
+            }
+        }
+        Err(DecodeError::InvalidVarint { offset })
+    }
+
+    pub(crate) fn read_u16(&mut self) -> Result<u16, DecodeError> {
+        self.read_bytes(2)
+            .map(|x| u16::from_le_bytes([x[0], x[1]]))
+    }
+
+    pub(crate) fn read_u32(&mut self) -> Result<u32, DecodeError> {
+        self.read_bytes(4)
+            .map(|x| u32::from_le_bytes([x[0], x[1], x[2], x[3]]))
+    }
+
+    fn read_edge(&mut self) -> Result<(&'a str, usize), DecodeError> {
+        let num_chars = self.read_usize()?;
+        let offset = self.offset;
+        let edge_string = str::from_utf8(self.read_bytes(num_chars)?)
+            .map_err(|_| DecodeError::InvalidUtf8 { offset })?;
+        Ok((edge_string, self.read_usize()?))
+    }
+}
+
+fn index_left_to_right<'a, T: Label>(
+    lattice: &[LatticeNode<'a, T>],
+) -> (Vec<LatticeNode<'a, T>>, Vec<Option<usize>>) {
+    let mut node_id_map = vec![None; lattice.len()];
+    let mut node_id_map_rev = Vec::with_capacity(lattice.len());
+    let mut queue = VecDeque::new();
+    let mut backward_counter = vec![0; lattice.len()];
+    queue.push_back(0);
+    while let Some(node_id) = queue.pop_front() {
+        node_id_map[node_id] = Some(node_id_map_rev.len());
+        node_id_map_rev.push(node_id);
+        for &(_, edge_target) in &lattice[node_id].forwards {
+            backward_counter[edge_target] += 1;
+            if backward_counter[edge_target] == lattice[edge_target].backwards.len() {
+                queue.push_back(edge_target);
+            }
+        }
+    }
+    let mut new_lattice = Vec::with_capacity(node_id_map_rev.len());
+    for &node_id in &node_id_map_rev {
+        let mut new_forwards = BTreeSet::new();
+        let mut new_backwards = BTreeSet::new();
+        for &(s, next_node_id) in &lattice[node_id].forwards {
+            new_forwards.insert((s, node_id_map[next_node_id].unwrap()));
+        }
+        for &(s, prev_node_id) in &lattice[node_id].backwards {
+            new_backwards.insert((s, node_id_map[prev_node_id].unwrap()));
+        }
+        let forward_main = lattice[node_id]
+            .forward_main
+            .map(|(x, i)| (x, node_id_map[i].unwrap()));
+        let backward_main = lattice[node_id]
+            .backward_main
+            .map(|(x, i)| (x, node_id_map[i].unwrap()));
+        new_lattice.push(LatticeNode {
+            forwards: new_forwards,
+            backwards: new_backwards,
+            forward_main,
+            backward_main,
+            depth: lattice[node_id].depth,
+        });
+    }
+    (new_lattice, node_id_map)
+}
+
 impl<'a> Lattice<'a> {
     /// Returns a lattice from the given binary data.
     ///
@@ -92,6 +471,11 @@ impl<'a> Lattice<'a> {
     ///
     /// * `data` - A byte array
     ///
+    /// # Panics
+    ///
+    /// Panics if the data is truncated or corrupted. Use `try_from_bytes` for data that can be
+    /// broken.
+    ///
     /// # Example
     ///
     /// ```
@@ -125,63 +509,89 @@ impl<'a> Lattice<'a> {
     /// let new_lattice = Lattice::new_from_bytes(&bytes);
     /// ```
     pub fn new_from_bytes(data: &'a [u8]) -> Lattice<'a> {
-        let mut lattice = Vec::with_capacity(vec_to_usize(&data[0..8]));
-        let mut offset = 8;
-        while offset < data.len() {
-            let num_forwards = vec_to_usize(&data[offset..offset + 8]);
-            let num_backwards = vec_to_usize(&data[offset + 8..offset + 16]);
-            offset += 16;
+        Lattice::try_from_bytes(data).unwrap()
+    }
+
+    /// Returns a lattice from the given binary data, or an error if the data is truncated or
+    /// corrupted.
+    ///
+    /// Both the current format with a header and the legacy layout without a header are
+    /// supported.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - A byte array
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use parattice::PaRattice;
+    /// use parattice::Lattice;
+    /// use parattice::error::DecodeError;
+    ///
+    /// let paradict = vec![
+    ///     vec![
+    ///         vec!["blood", "stem", "cell"],
+    ///         vec!["造血", "幹", "細胞"],
+    ///         vec!["hematopoietic", "stem", "cell"],
+    ///     ],
+    ///     vec![
+    ///         vec!["造血", "幹", "細胞", "移植"],
+    ///         vec!["hematopoietic", "stem", "cell", "transplantation"],
+    ///     ],
+    ///     vec![vec!["stem", "cell"], vec!["幹", "細胞"]],
+    ///     vec![
+    ///         vec!["幹", "細胞", "移植"],
+    ///         vec!["rescue", "transplant"],
+    ///         vec!["stem", "cell", "rescue"],
+    ///     ],
+    ///     vec![vec!["rescue"], vec!["救命"]],
+    ///     vec![vec!["blood"], vec!["血液"]],
+    /// ];
+    /// let parattice = PaRattice::new(paradict);
+    /// let words = vec!["造血", "幹", "細胞", "移植"];
+    /// let lattice = parattice.get_lattice(&words, true, 2);
+    ///
+    /// let bytes = lattice.to_bytes();
+    /// let new_lattice = Lattice::try_from_bytes(&bytes).unwrap();
+    /// assert!(lattice == new_lattice);
+    /// assert!(Lattice::try_from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    /// ```
+    pub fn try_from_bytes(data: &'a [u8]) -> Result<Lattice<'a>, DecodeError> {
+        if data.starts_with(&MAGIC) {
+            Lattice::try_from_bytes_with_weights(data).map(|(lattice, _)| lattice)
+        } else {
+            Lattice::try_from_legacy_bytes(data)
+        }
+    }
+
+    /// Returns a lattice from binary data of the legacy layout without a header.
+    pub(crate) fn try_from_legacy_bytes(data: &'a [u8]) -> Result<Lattice<'a>, DecodeError> {
+        let mut reader = ByteReader { data, offset: 0 };
+        let num_nodes = reader.read_usize()?;
+        // each node takes at least 16 bytes
+        let mut lattice = Vec::with_capacity(cmp::min(num_nodes, data.len() / 16));
+        while reader.offset < data.len() {
+            let num_forwards = reader.read_usize()?;
+            let num_backwards = reader.read_usize()?;
             let mut forwards = BTreeSet::new();
-            let forward_main = if num_forwards != 0 {
-                let forward_main_num_chars = vec_to_usize(&data[offset..offset + 8]);
-                let forward_main_edge_string =
-                    str::from_utf8(&data[offset + 8..offset + 8 + forward_main_num_chars]).unwrap();
-                let forward_main_edge_target = vec_to_usize(
-                    &data
-                        [offset + 8 + forward_main_num_chars..offset + 16 + forward_main_num_chars],
-                );
-                offset += 16 + forward_main_num_chars;
-                forwards.insert((forward_main_edge_string, forward_main_edge_target));
-                for _ in 1..num_forwards {
-                    let forward_num_chars = vec_to_usize(&data[offset..offset + 8]);
-                    let forward_edge_string =
-                        str::from_utf8(&data[offset + 8..offset + 8 + forward_num_chars]).unwrap();
-                    let forward_edge_target = vec_to_usize(
-                        &data[offset + 8 + forward_num_chars..offset + 16 + forward_num_chars],
-                    );
-                    offset += 16 + forward_num_chars;
-                    forwards.insert((forward_edge_string, forward_edge_target));
+            let mut forward_main = None;
+            for i in 0..num_forwards {
+                let edge = reader.read_edge()?;
+                if i == 0 {
+                    forward_main = Some(edge);
                 }
-                Some((forward_main_edge_string, forward_main_edge_target))
-            } else {
-                None
-            };
+                forwards.insert(edge);
+            }
             let mut backwards = BTreeSet::new();
-            let backward_main = if num_backwards != 0 {
-                let backward_main_num_chars = vec_to_usize(&data[offset..offset + 8]);
-                let backward_main_edge_string =
-                    str::from_utf8(&data[offset + 8..offset + 8 + backward_main_num_chars])
-                        .unwrap();
-                let backward_main_edge_target = vec_to_usize(
-                    &data[offset + 8 + backward_main_num_chars
-                        ..offset + 16 + backward_main_num_chars],
-                );
-                offset += 16 + backward_main_num_chars;
-                backwards.insert((backward_main_edge_string, backward_main_edge_target));
-                for _ in 1..num_backwards {
-                    let backward_num_chars = vec_to_usize(&data[offset..offset + 8]);
-                    let backward_edge_string =
-                        str::from_utf8(&data[offset + 8..offset + 8 + backward_num_chars]).unwrap();
-                    let backward_edge_target = vec_to_usize(
-                        &data[offset + 8 + backward_num_chars..offset + 16 + backward_num_chars],
-                    );
-                    offset += 16 + backward_num_chars;
-                    backwards.insert((backward_edge_string, backward_edge_target));
+            let mut backward_main = None;
+            for i in 0..num_backwards {
+                let edge = reader.read_edge()?;
+                if i == 0 {
+                    backward_main = Some(edge);
                 }
-                Some((backward_main_edge_string, backward_main_edge_target))
-            } else {
-                None
-            };
+                backwards.insert(edge);
+            }
             lattice.push(LatticeNode {
                 forwards,
                 backwards,
@@ -190,23 +600,30 @@ impl<'a> Lattice<'a> {
                 depth: 0,
             });
         }
-        let mut trunk = HashMap::new();
-        let mut node_id = 0;
-        let mut orig_node_id = 0;
-        trunk.insert(0, 0);
-        while let Some(x) = lattice[node_id].forward_main {
-            node_id = x.1;
-            orig_node_id += 1;
-            trunk.insert(node_id, orig_node_id);
+        if lattice.len() != num_nodes {
+            return Err(DecodeError::NodeCountMismatch {
+                expected: num_nodes,
+                actual: lattice.len(),
+            });
         }
-        Lattice {
-            trunk,
+        Lattice::from_decoded_nodes(lattice)
+    }
+
+    /// Returns a lattice of the decoded nodes after checking edges and the trunk.
+    pub(crate) fn from_decoded_nodes(
+        lattice: Vec<LatticeNode<'a>>,
+    ) -> Result<Lattice<'a>, DecodeError> {
+        Ok(Lattice {
+            trunk: trunk_of_nodes(&lattice)?,
             capacity: lattice.iter().fold(0, |sum, x| sum + x.forwards.len()),
             lattice,
-        }
+        })
     }
 
-    /// Returns binary data of the lattice.
+    /// Returns binary data of the lattice in the current format with the compact layout and
+    /// without optional sections.
+    ///
+    /// See `to_bytes_with_options` for the format.
     ///
     /// # Example
     ///
@@ -241,6 +658,19 @@ impl<'a> Lattice<'a> {
     /// let new_lattice = Lattice::new_from_bytes(&bytes);
     /// ```
     pub fn to_bytes(&self) -> Vec<u8> {
+        self.to_bytes_with_options(&BinaryOptions {
+            layout: Layout::Compact,
+            ..BinaryOptions::default()
+        })
+    }
+
+    /// Returns binary data of the lattice in the legacy layout without a header, which is read
+    /// by older versions of this library.
+    ///
+    /// The legacy layout has no room for depths, so they are read back as 0, and the decoded
+    /// lattice differs from the original one if any node has a depth greater than 0. Use
+    /// `to_bytes` to keep depths.
+    pub fn to_legacy_bytes(&self) -> Vec<u8> {
         let mut result = vec![];
         result.append(&mut usize_to_vec(self.lattice.len()));
         for node in &self.lattice {
@@ -313,120 +743,162 @@ impl<'a> Lattice<'a> {
     /// println!("{}", dot);
     /// ```
     pub fn dump_dot(&self, is_numbered: bool) -> String {
-        let mut result = "digraph { graph [rankdir=LR];\n".to_string();
-        for (i, node) in self.lattice.iter().enumerate() {
-            if is_numbered {
-                result = result
-                    + &format!(
-                        "\"{}\" [label=\"{}\",shape=plaintext,width=\"0.1\"];\n",
-                        i, i
-                    );
-            } else {
-                result = result + &format!("\"{}\" [label=\"\",shape=circle,width=\"0.1\"];\n", i);
-            }
-            for (j, &edge) in node.forwards.iter().enumerate() {
-                result = result
-                    + &format!(
-                        "\"{}-{}-{}\" [label=\"{}\",shape=box];\n",
-                        i, j, edge.1, edge.0
-                    );
-                if edge == node.forward_main.unwrap() {
-                    result = result
-                        + &format!(
-                            "\"{}\" -> \"{}-{}-{}\" [arrowhead=none,color=\"#ff0000\"];\n",
-                            i, i, j, edge.1
-                        );
-                } else {
-                    result = result
-                        + &format!(
-                            "\"{}\" -> \"{}-{}-{}\" [arrowhead=none];\n",
-                            i, i, j, edge.1
-                        );
-                }
-                if self.lattice[edge.1].backward_main == Some((edge.0, i)) {
-                    result = result
-                        + &format!(
-                            "\"{}-{}-{}\" -> \"{}\" [color=\"#0000ff\"];\n",
-                            i, j, edge.1, edge.1
-                        );
-                } else {
-                    result = result + &format!("\"{}-{}-{}\" -> \"{}\";\n", i, j, edge.1, edge.1);
-                }
-            }
-        }
-        result += "}";
-        result
+        self.dump_dot_with_options(&DotOptions {
+            is_numbered,
+            ..DotOptions::default()
+        })
     }
 
+    /// Returns a vector of SearchIndexNode for the search index such as Elasticsearch.
+    pub fn dump_for_search_index(&self) -> Vec<SearchIndexNode> {
+        search_index_nodes(self, self.capacity)
+    }
+}
+
+impl<'a, T: Label> Lattice<'a, T> {
     /// Returns a trunk path of the given path.
     ///
     /// # Arguments
     ///
     /// * `path` - A path of the lattice. (e.g. a result of LatticeKMP)
-    pub fn get_trunk_span(&self, path: Vec<(&'a str, usize)>) -> Vec<(&'a str, usize)> {
-        let mut new_path: VecDeque<(&str, usize)> = path.into_iter().collect();
-        let mut edge_bw = new_path.pop_front().unwrap();
-        while !self.trunk.contains_key(&edge_bw.1) {
-            let next_edge = self.lattice[edge_bw.1].backward_main.unwrap();
-            new_path.push_front((next_edge.0, edge_bw.1));
-            edge_bw = next_edge;
-        }
-        new_path.push_front(("", edge_bw.1));
-        let mut edge_fw = *new_path.back().unwrap();
-        while !self.trunk.contains_key(&edge_fw.1) {
-            edge_fw = self.lattice[edge_fw.1].forward_main.unwrap();
-            new_path.push_back(edge_fw);
-        }
-        new_path.into_iter().collect()
+    pub fn get_trunk_span(&self, path: Vec<(T, usize)>) -> Vec<(T, usize)> {
+        trunk_span(self, path)
     }
 
     /// Returns trunk node IDs for each node ID.
     pub fn get_trunk_spans(&self) -> Vec<(usize, usize)> {
-        let mut left_trunks = vec![0; self.lattice.len()];
-        let mut right_trunks = vec![self.lattice.len() - 1; self.lattice.len()];
-        for &node_id in self.trunk.keys() {
-            left_trunks[node_id] = node_id;
-            right_trunks[node_id] = node_id;
-        }
-        for node_id in 1..self.lattice.len() - 1 {
-            for edge in &self.lattice[node_id].forwards {
-                if left_trunks[edge.1] == 0
-                    && self.lattice[edge.1].backward_main.unwrap().1 == node_id
-                {
-                    left_trunks[edge.1] = left_trunks[node_id];
+        trunk_spans(self)
+    }
+
+    /// Returns trunk node IDs from left to right.
+    pub(crate) fn trunk_node_ids(&self) -> Vec<usize> {
+        let mut node_ids = vec![0; self.trunk.len()];
+        for (&node_id, &position) in &self.trunk {
+            node_ids[position] = node_id;
+        }
+        node_ids
+    }
+
+    /// Returns a lattice of the given nodes renumbered from left to right, and the new node ID
+    /// of each given node. Node 0 must be the first node, and nodes that are not reachable from
+    /// it must not have any edges.
+    pub(crate) fn from_nodes(
+        nodes: &[LatticeNode<'a, T>],
+    ) -> (Lattice<'a, T>, Vec<Option<usize>>) {
+        let (lattice, node_id_map) = index_left_to_right(nodes);
+        let mut trunk = HashMap::new();
+        let mut node_id = 0;
+        let mut orig_node_id = 0;
+        trunk.insert(0, 0);
+        while let Some((_, x)) = lattice[node_id].forward_main {
+            node_id = x;
+            orig_node_id += 1;
+            trunk.insert(node_id, orig_node_id);
+        }
+        (
+            Lattice {
+                trunk,
+                capacity: lattice.iter().fold(0, |sum, x| sum + x.forwards.len()),
+                lattice,
+            },
+            node_id_map,
+        )
+    }
+
+    /// Returns a lattice of the given edges `(source, label, target)` and the new node ID of
+    /// each given node.
+    ///
+    /// The trunk is given as a path from the first node to the last node. Nodes that are not on
+    /// any path between them are removed. Each branch node takes an edge from or to a trunk node
+    /// as its main edge if any, or otherwise the smallest edge.
+    pub(crate) fn from_edges(
+        num_nodes: usize,
+        edges: &[(usize, T, usize)],
+        trunk: &[(T, usize)],
+    ) -> (Lattice<'a, T>, Vec<Option<usize>>) {
+        let first_node_id = trunk[0].1;
+        let last_node_id = trunk[trunk.len() - 1].1;
+        let mut forwards = vec![vec![]; num_nodes];
+        let mut backwards = vec![vec![]; num_nodes];
+        for &(source, s, target) in edges {
+            forwards[source].push((s, target));
+            backwards[target].push((s, source));
+        }
+        let mut reachable = vec![false; num_nodes];
+        let mut stack = vec![first_node_id];
+        reachable[first_node_id] = true;
+        while let Some(node_id) = stack.pop() {
+            for &(_, target) in &forwards[node_id] {
+                if !reachable[target] {
+                    reachable[target] = true;
+                    stack.push(target);
                 }
             }
         }
-        for node_id in (1..self.lattice.len() - 1).rev() {
-            for edge in &self.lattice[node_id].backwards {
-                if right_trunks[edge.1] == self.lattice.len() - 1
-                    && self.lattice[edge.1].forward_main.unwrap().1 == node_id
-                {
-                    right_trunks[edge.1] = right_trunks[node_id];
+        let mut coreachable = vec![false; num_nodes];
+        let mut stack = vec![last_node_id];
+        coreachable[last_node_id] = true;
+        while let Some(node_id) = stack.pop() {
+            for &(_, source) in &backwards[node_id] {
+                if !coreachable[source] {
+                    coreachable[source] = true;
+                    stack.push(source);
                 }
             }
         }
-        let mut result = Vec::with_capacity(self.lattice.len());
-        for item in left_trunks.into_iter().zip(right_trunks.into_iter()) {
-            result.push((self.trunk[&item.0], self.trunk[&item.1]));
+        // the first node must be placed at 0
+        let mut node_ids: Vec<usize> = (0..num_nodes)
+            .filter(|&i| i != first_node_id && reachable[i] && coreachable[i])
+            .collect();
+        node_ids.insert(0, first_node_id);
+        let mut node_id_map = vec![None; num_nodes];
+        for (i, &node_id) in node_ids.iter().enumerate() {
+            node_id_map[node_id] = Some(i);
         }
-        result
-    }
-
-    /// Returns a vector of SearchIndexNode for the search index such as Elasticsearch.
-    pub fn dump_for_search_index(&self) -> Vec<SearchIndexNode> {
-        let trunk_spans = self.get_trunk_spans();
-        let mut result = Vec::with_capacity(self.capacity);
-        for i in 0..self.lattice.len() - 1 {
-            for (j, edge) in self.lattice[i].forwards.iter().enumerate() {
-                result.push(SearchIndexNode {
-                    text: edge.0,
-                    offset: (trunk_spans[i].0, trunk_spans[edge.1].1),
-                    increment: if j == 0 { 1 } else { 0 },
-                    length: edge.1 - i,
-                });
+        let mut nodes: Vec<LatticeNode<T>> = node_ids
+            .iter()
+            .map(|_| LatticeNode::new(None, None, 0))
+            .collect();
+        for &(source, s, target) in edges {
+            if let (Some(i), Some(j)) = (node_id_map[source], node_id_map[target]) {
+                nodes[i].insert_forward(s, j);
+                nodes[j].insert_backward(s, i);
             }
         }
-        result
+        let mut is_trunk = vec![false; nodes.len()];
+        is_trunk[0] = true;
+        for i in 1..trunk.len() {
+            let source = node_id_map[trunk[i - 1].1].unwrap();
+            let target = node_id_map[trunk[i].1].unwrap();
+            nodes[source].forward_main = Some((trunk[i].0, target));
+            nodes[target].backward_main = Some((trunk[i].0, source));
+            is_trunk[target] = true;
+        }
+        for node in &mut nodes {
+            if node.forward_main.is_none() {
+                node.forward_main = node
+                    .forwards
+                    .iter()
+                    .find(|edge| is_trunk[edge.1])
+                    .or_else(|| node.forwards.iter().next())
+                    .cloned();
+            }
+            if node.backward_main.is_none() {
+                node.backward_main = node
+                    .backwards
+                    .iter()
+                    .find(|edge| is_trunk[edge.1])
+                    .or_else(|| node.backwards.iter().next())
+                    .cloned();
+            }
+        }
+        let (lattice, new_node_id_map) = Lattice::from_nodes(&nodes);
+        (
+            lattice,
+            node_id_map
+                .into_iter()
+                .map(|i| i.and_then(|i| new_node_id_map[i]))
+                .collect(),
+        )
     }
 }
@@ -0,0 +1,254 @@
+use crate::lattice::Label;
+use crate::lattice::Lattice;
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::collections::VecDeque;
+
+/// The version of the encoding hashed by `Lattice::canonical_hash()`, bumped when the encoding
+/// changes.
+const CANONICAL_ENCODING_VERSION: u8 = 1;
+
+struct DfaState<T> {
+    is_final: bool,
+    transitions: BTreeMap<T, usize>,
+}
+
+impl<'a, T: Label> Lattice<'a, T> {
+    /// Returns the canonical form of the lattice.
+    ///
+    /// Lattices that have the same set of paths and the same trunk have the same canonical form
+    /// regardless of their node numbering.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use parattice::PaRattice;
+    ///
+    /// let paradict = vec![
+    ///     vec![
+    ///         vec!["blood", "stem", "cell"],
+    ///         vec!["造血", "幹", "細胞"],
+    ///         vec!["hematopoietic", "stem", "cell"],
+    ///     ],
+    ///     vec![
+    ///         vec!["造血", "幹", "細胞", "移植"],
+    ///         vec!["hematopoietic", "stem", "cell", "transplantation"],
+    ///     ],
+    ///     vec![vec!["stem", "cell"], vec!["幹", "細胞"]],
+    ///     vec![
+    ///         vec!["幹", "細胞", "移植"],
+    ///         vec!["rescue", "transplant"],
+    ///         vec!["stem", "cell", "rescue"],
+    ///     ],
+    ///     vec![vec!["rescue"], vec!["救命"]],
+    ///     vec![vec!["blood"], vec!["血液"]],
+    /// ];
+    /// let parattice = PaRattice::new(paradict);
+    /// let words = vec!["造血", "幹", "細胞", "移植"];
+    /// let lattice1 = parattice.get_lattice(&words, true, 2);
+    /// let lattice2 = parattice.get_lattice(&words, false, 2);
+    ///
+    /// assert_eq!(lattice1.canonicalize(), lattice2.canonicalize());
+    /// ```
+    pub fn canonicalize(&self) -> Lattice<'a, T> {
+        if self.lattice.len() == 1 {
+            return Lattice::from_edges(1, &[], &[(T::default(), 0)]).0;
+        }
+        let last_node_id = self.lattice.len() - 1;
+        // determinization
+        let mut states = vec![DfaState {
+            is_final: false,
+            transitions: BTreeMap::new(),
+        }];
+        let mut subsets = vec![vec![0]];
+        let mut subset_ids = HashMap::new();
+        subset_ids.insert(vec![0], 0);
+        let mut queue = VecDeque::new();
+        queue.push_back(0);
+        while let Some(state_id) = queue.pop_front() {
+            let mut next_subsets: BTreeMap<T, BTreeSet<usize>> = BTreeMap::new();
+            for &node_id in &subsets[state_id] {
+                for &(s, target) in &self.lattice[node_id].forwards {
+                    next_subsets.entry(s).or_default().insert(target);
+                }
+            }
+            for (s, next_subset) in next_subsets {
+                let next_subset: Vec<usize> = next_subset.into_iter().collect();
+                let next_state_id = match subset_ids.get(&next_subset) {
+                    Some(&next_state_id) => next_state_id,
+                    None => {
+                        let next_state_id = states.len();
+                        states.push(DfaState {
+                            is_final: next_subset.contains(&last_node_id),
+                            transitions: BTreeMap::new(),
+                        });
+                        subset_ids.insert(next_subset.clone(), next_state_id);
+                        subsets.push(next_subset);
+                        queue.push_back(next_state_id);
+                        next_state_id
+                    }
+                };
+                states[state_id].transitions.insert(s, next_state_id);
+            }
+        }
+        // minimization: states that have the same suffixes are merged
+        let mut classes = vec![None; states.len()];
+        let mut signatures = HashMap::new();
+        let mut stack = vec![(0, false)];
+        while let Some((state_id, visited)) = stack.pop() {
+            if classes[state_id].is_some() {
+                continue;
+            }
+            if !visited {
+                stack.push((state_id, true));
+                for &next_state_id in states[state_id].transitions.values() {
+                    if classes[next_state_id].is_none() {
+                        stack.push((next_state_id, false));
+                    }
+                }
+                continue;
+            }
+            let signature: (bool, Vec<(T, usize)>) = (
+                states[state_id].is_final,
+                states[state_id]
+                    .transitions
+                    .iter()
+                    .map(|(&s, &next_state_id)| (s, classes[next_state_id].unwrap()))
+                    .collect(),
+            );
+            let num_classes = signatures.len();
+            classes[state_id] = Some(*signatures.entry(signature).or_insert(num_classes));
+        }
+        // canonical numbering in the breadth-first order of labels
+        let mut class_states = vec![0; signatures.len()];
+        for (state_id, class) in classes.iter().enumerate() {
+            class_states[class.unwrap()] = state_id;
+        }
+        let mut node_ids = vec![None; signatures.len()];
+        let mut class_order = vec![classes[0].unwrap()];
+        node_ids[classes[0].unwrap()] = Some(0);
+        let mut i = 0;
+        while i < class_order.len() {
+            for &next_state_id in states[class_states[class_order[i]]].transitions.values() {
+                let next_class = classes[next_state_id].unwrap();
+                if node_ids[next_class].is_none() {
+                    node_ids[next_class] = Some(class_order.len());
+                    class_order.push(next_class);
+                }
+            }
+            i += 1;
+        }
+        // the last node is separated from final states that have outgoing edges
+        let last_class = signatures[&(true, vec![])];
+        let new_last_node_id = node_ids[last_class].unwrap();
+        let mut edges = vec![];
+        for (node_id, &class) in class_order.iter().enumerate() {
+            let state = &states[class_states[class]];
+            for (&s, &next_state_id) in &state.transitions {
+                let next_class = classes[next_state_id].unwrap();
+                edges.push((node_id, s, node_ids[next_class].unwrap()));
+                if next_class != last_class && states[next_state_id].is_final {
+                    edges.push((node_id, s, new_last_node_id));
+                }
+            }
+        }
+        let mut trunk = vec![(T::default(), 0)];
+        let mut state_id = 0;
+        let mut node_id = 0;
+        while let Some((s, next_node_id)) = self.lattice[node_id].forward_main {
+            state_id = states[state_id].transitions[&s];
+            let new_node_id = if next_node_id == last_node_id {
+                new_last_node_id
+            } else {
+                node_ids[classes[state_id].unwrap()].unwrap()
+            };
+            trunk.push((s, new_node_id));
+            node_id = next_node_id;
+        }
+        Lattice::from_edges(class_order.len(), &edges, &trunk).0
+    }
+}
+
+impl<'a> Lattice<'a> {
+    /// Returns a hash value of the canonical form of the lattice.
+    ///
+    /// The value is stable across processes and platforms, so it can be stored to find
+    /// duplicated lattices.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use parattice::PaRattice;
+    ///
+    /// let paradict = vec![
+    ///     vec![
+    ///         vec!["blood", "stem", "cell"],
+    ///         vec!["造血", "幹", "細胞"],
+    ///         vec!["hematopoietic", "stem", "cell"],
+    ///     ],
+    ///     vec![
+    ///         vec!["造血", "幹", "細胞", "移植"],
+    ///         vec!["hematopoietic", "stem", "cell", "transplantation"],
+    ///     ],
+    ///     vec![vec!["stem", "cell"], vec!["幹", "細胞"]],
+    ///     vec![
+    ///         vec!["幹", "細胞", "移植"],
+    ///         vec!["rescue", "transplant"],
+    ///         vec!["stem", "cell", "rescue"],
+    ///     ],
+    ///     vec![vec!["rescue"], vec!["救命"]],
+    ///     vec![vec!["blood"], vec!["血液"]],
+    /// ];
+    /// let parattice = PaRattice::new(paradict);
+    /// let words = vec!["造血", "幹", "細胞", "移植"];
+    /// let lattice1 = parattice.get_lattice(&words, true, 2);
+    /// let lattice2 = parattice.get_lattice(&words, false, 2);
+    ///
+    /// assert_eq!(lattice1.canonical_hash(), lattice2.canonical_hash());
+    /// ```
+    pub fn canonical_hash(&self) -> u64 {
+        // FNV-1a
+        let mut hash = 0xcbf2_9ce4_8422_2325;
+        for &b in &self.canonicalize().canonical_bytes() {
+            hash ^= u64::from(b);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+        hash
+    }
+
+    /// Returns the encoding of a canonical lattice hashed by `canonical_hash()`.
+    ///
+    /// The encoding is independent of the binary format so that hash values don't change when
+    /// the format changes. Integers are 8-byte little-endian. The encoding consists of
+    /// `CANONICAL_ENCODING_VERSION` (1 byte), the number of nodes, forward edges of each node,
+    /// and trunk node IDs. Edges are encoded as the number of edges followed by the byte length
+    /// of the label, the label in UTF-8 and the target node ID of each edge.
+    fn canonical_bytes(&self) -> Vec<u8> {
+        let push_u64 = |result: &mut Vec<u8>, n: usize| {
+            result.extend_from_slice(&(n as u64).to_le_bytes());
+        };
+        let mut result = vec![CANONICAL_ENCODING_VERSION];
+        push_u64(&mut result, self.lattice.len());
+        for node in &self.lattice {
+            push_u64(&mut result, node.forwards.len());
+            for &(s, target) in &node.forwards {
+                push_u64(&mut result, s.len());
+                result.extend_from_slice(s.as_bytes());
+                push_u64(&mut result, target);
+            }
+        }
+        let mut trunk = vec![0];
+        let mut node_id = 0;
+        while let Some((_, next_node_id)) = self.lattice[node_id].forward_main {
+            trunk.push(next_node_id);
+            node_id = next_node_id;
+        }
+        push_u64(&mut result, trunk.len());
+        for node_id in trunk {
+            push_u64(&mut result, node_id);
+        }
+        result
+    }
+}
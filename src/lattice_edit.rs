@@ -0,0 +1,376 @@
+use crate::error::EditError;
+use crate::lattice::Label;
+use crate::lattice::Lattice;
+use crate::lattice::LatticeNode;
+
+use std::mem;
+
+impl<'a, T: Label> Lattice<'a, T> {
+    /// Removes edges that don't satisfy the predicate, and returns the new node ID of each old
+    /// node, or None if the node is removed.
+    ///
+    /// Trunk edges are always kept. Nodes that are no longer on any path from the first node to
+    /// the last node are removed, main edges of the remaining nodes are reassigned if they are
+    /// removed, and nodes are numbered from left to right again.
+    ///
+    /// # Arguments
+    ///
+    /// * `pred` - A predicate given the label, the source and the target node IDs of an edge, and
+    ///   the source node
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use parattice::PaRattice;
+    ///
+    /// let paradict = vec![
+    ///     vec![
+    ///         vec!["blood", "stem", "cell"],
+    ///         vec!["造血", "幹", "細胞"],
+    ///         vec!["hematopoietic", "stem", "cell"],
+    ///     ],
+    ///     vec![
+    ///         vec!["造血", "幹", "細胞", "移植"],
+    ///         vec!["hematopoietic", "stem", "cell", "transplantation"],
+    ///     ],
+    ///     vec![vec!["stem", "cell"], vec!["幹", "細胞"]],
+    ///     vec![
+    ///         vec!["幹", "細胞", "移植"],
+    ///         vec!["rescue", "transplant"],
+    ///         vec!["stem", "cell", "rescue"],
+    ///     ],
+    ///     vec![vec!["rescue"], vec!["救命"]],
+    ///     vec![vec!["blood"], vec!["血液"]],
+    /// ];
+    /// let parattice = PaRattice::new(paradict);
+    /// let words = vec!["造血", "幹", "細胞", "移植"];
+    /// let mut lattice = parattice.get_lattice(&words, true, 2);
+    ///
+    /// lattice.retain_edges(|s, _, _, _| s != "blood");
+    ///
+    /// // removes paraphrases nested more than once
+    /// lattice.retain_edges(|_, _, _, node| node.depth <= 1);
+    /// ```
+    pub fn retain_edges<F>(&mut self, mut pred: F) -> Vec<Option<usize>>
+    where
+        F: FnMut(T, usize, usize, &LatticeNode<'a, T>) -> bool,
+    {
+        let mut nodes = mem::take(&mut self.lattice);
+        let mut removed_edges = vec![];
+        for (i, node) in nodes.iter().enumerate() {
+            for &edge in &node.forwards {
+                let is_trunk_edge = self.trunk.contains_key(&i)
+                    && self.trunk.contains_key(&edge.1)
+                    && node.forward_main == Some(edge);
+                if !is_trunk_edge && !pred(edge.0, i, edge.1, node) {
+                    removed_edges.push((i, edge.0, edge.1));
+                }
+            }
+        }
+        for (source, s, target) in removed_edges {
+            nodes[source].forwards.remove(&(s, target));
+            nodes[target].backwards.remove(&(s, source));
+        }
+        let (lattice, node_id_map) = Lattice::from_nodes_with_pruning(nodes);
+        *self = lattice;
+        node_id_map
+    }
+
+    /// Adds a branch between two trunk positions, and returns the new node ID of each old
+    /// node.
+    ///
+    /// The branch is a path of the given labels. Nodes are numbered from left to right again,
+    /// and new nodes are numbered after the old nodes before re-numbering. A branch of one label
+    /// must not duplicate an existing edge.
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - A trunk position where the branch starts
+    /// * `end` - A trunk position where the branch ends
+    /// * `labels` - Labels of the branch
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use parattice::PaRattice;
+    ///
+    /// let paradict = vec![
+    ///     vec![
+    ///         vec!["blood", "stem", "cell"],
+    ///         vec!["造血", "幹", "細胞"],
+    ///         vec!["hematopoietic", "stem", "cell"],
+    ///     ],
+    ///     vec![
+    ///         vec!["造血", "幹", "細胞", "移植"],
+    ///         vec!["hematopoietic", "stem", "cell", "transplantation"],
+    ///     ],
+    ///     vec![vec!["stem", "cell"], vec!["幹", "細胞"]],
+    ///     vec![
+    ///         vec!["幹", "細胞", "移植"],
+    ///         vec!["rescue", "transplant"],
+    ///         vec!["stem", "cell", "rescue"],
+    ///     ],
+    ///     vec![vec!["rescue"], vec!["救命"]],
+    ///     vec![vec!["blood"], vec!["血液"]],
+    /// ];
+    /// let parattice = PaRattice::new(paradict);
+    /// let words = vec!["造血", "幹", "細胞", "移植"];
+    /// let mut lattice = parattice.get_lattice(&words, true, 2);
+    ///
+    /// // 幹 細胞 -> stem cells
+    /// lattice.add_branch(1, 3, &["stem", "cells"]).unwrap();
+    /// ```
+    pub fn add_branch(
+        &mut self,
+        start: usize,
+        end: usize,
+        labels: &[T],
+    ) -> Result<Vec<Option<usize>>, EditError> {
+        if start >= end || end >= self.trunk.len() {
+            return Err(EditError::InvalidTrunkSpan);
+        }
+        if labels.is_empty() {
+            return Err(EditError::EmptyBranch);
+        }
+        let trunk_node_ids = self.trunk_node_ids();
+        if labels.len() == 1
+            && self.lattice[trunk_node_ids[start]]
+                .forwards
+                .contains(&(labels[0], trunk_node_ids[end]))
+        {
+            return Err(EditError::DuplicateEdge);
+        }
+        let mut nodes = mem::take(&mut self.lattice);
+        let mut node_ids = vec![trunk_node_ids[start]];
+        for _ in 1..labels.len() {
+            node_ids.push(nodes.len());
+            nodes.push(LatticeNode::new(None, None, 0));
+        }
+        node_ids.push(trunk_node_ids[end]);
+        for (i, &s) in labels.iter().enumerate() {
+            let (source, target) = (node_ids[i], node_ids[i + 1]);
+            nodes[source].insert_forward(s, target);
+            nodes[target].insert_backward(s, source);
+            if i != 0 {
+                nodes[source].backward_main = Some((labels[i - 1], node_ids[i - 1]));
+                nodes[source].forward_main = Some((s, target));
+            }
+        }
+        let (lattice, node_id_map) = Lattice::from_nodes_with_pruning(nodes);
+        *self = lattice;
+        Ok(node_id_map)
+    }
+
+    /// Removes an edge that is not on the trunk, and returns the new node ID of each old node,
+    /// or None if the node is removed.
+    ///
+    /// Nodes that are no longer on any path from the first node to the last node are removed,
+    /// and nodes are numbered from left to right again.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - A source node ID
+    /// * `label` - A label
+    /// * `target` - A target node ID
+    pub fn remove_edge(
+        &mut self,
+        source: usize,
+        label: T,
+        target: usize,
+    ) -> Result<Vec<Option<usize>>, EditError> {
+        let edge = self.find_edge(source, label, target)?;
+        if self.trunk.contains_key(&source)
+            && self.trunk.contains_key(&target)
+            && self.lattice[source].forward_main == Some(edge)
+        {
+            return Err(EditError::TrunkEdge);
+        }
+        let mut nodes = mem::take(&mut self.lattice);
+        nodes[source].forwards.remove(&edge);
+        nodes[target].backwards.remove(&(edge.0, source));
+        let (lattice, node_id_map) = Lattice::from_nodes_with_pruning(nodes);
+        *self = lattice;
+        Ok(node_id_map)
+    }
+
+    /// Changes the label of an edge. Node IDs are not changed.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - A source node ID
+    /// * `label` - A label
+    /// * `target` - A target node ID
+    /// * `new_label` - A new label
+    pub fn relabel_edge(
+        &mut self,
+        source: usize,
+        label: T,
+        target: usize,
+        new_label: T,
+    ) -> Result<(), EditError> {
+        let edge = self.find_edge(source, label, target)?;
+        let new_edge = (new_label, target);
+        if self.lattice[source].forwards.contains(&new_edge) {
+            return Err(EditError::DuplicateEdge);
+        }
+        let source_node = &mut self.lattice[source];
+        source_node.forwards.remove(&edge);
+        source_node.forwards.insert(new_edge);
+        if source_node.forward_main == Some(edge) {
+            source_node.forward_main = Some(new_edge);
+        }
+        let target_node = &mut self.lattice[target];
+        target_node.backwards.remove(&(edge.0, source));
+        target_node.backwards.insert((new_label, source));
+        if target_node.backward_main == Some((edge.0, source)) {
+            target_node.backward_main = Some((new_label, source));
+        }
+        Ok(())
+    }
+
+    /// Replaces a token of the trunk. Node IDs are not changed.
+    ///
+    /// # Arguments
+    ///
+    /// * `position` - A position of the token in the trunk starting from 0
+    /// * `new_label` - A new label
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use parattice::PaRattice;
+    ///
+    /// let paradict = vec![
+    ///     vec![
+    ///         vec!["blood", "stem", "cell"],
+    ///         vec!["造血", "幹", "細胞"],
+    ///         vec!["hematopoietic", "stem", "cell"],
+    ///     ],
+    ///     vec![
+    ///         vec!["造血", "幹", "細胞", "移植"],
+    ///         vec!["hematopoietic", "stem", "cell", "transplantation"],
+    ///     ],
+    ///     vec![vec!["stem", "cell"], vec!["幹", "細胞"]],
+    ///     vec![
+    ///         vec!["幹", "細胞", "移植"],
+    ///         vec!["rescue", "transplant"],
+    ///         vec!["stem", "cell", "rescue"],
+    ///     ],
+    ///     vec![vec!["rescue"], vec!["救命"]],
+    ///     vec![vec!["blood"], vec!["血液"]],
+    /// ];
+    /// let parattice = PaRattice::new(paradict);
+    /// let words = vec!["造血", "幹", "細胞", "移植"];
+    /// let mut lattice = parattice.get_lattice(&words, true, 2);
+    ///
+    /// // fix a typo
+    /// lattice.replace_trunk_token(3, "移殖").unwrap();
+    /// ```
+    pub fn replace_trunk_token(
+        &mut self,
+        position: usize,
+        new_label: T,
+    ) -> Result<(), EditError> {
+        if position + 1 >= self.trunk.len() {
+            return Err(EditError::InvalidTrunkSpan);
+        }
+        let source = self.trunk_node_ids()[position];
+        let (label, target) = self.lattice[source].forward_main.unwrap();
+        self.relabel_edge(source, label, target, new_label)
+    }
+
+    fn find_edge(
+        &self,
+        source: usize,
+        label: T,
+        target: usize,
+    ) -> Result<(T, usize), EditError> {
+        self.lattice
+            .get(source)
+            .and_then(|node| {
+                node.forwards
+                    .iter()
+                    .find(|&&(s, i)| s == label && i == target)
+                    .cloned()
+            })
+            .ok_or(EditError::EdgeNotFound)
+    }
+
+    /// Returns a lattice of the given nodes and the new node ID of each given node.
+    ///
+    /// The trunk is kept by following forward main edges from the first node to the last node.
+    /// Nodes that are not on any path from the first node to the last node are removed, main
+    /// edges of branch nodes that are missing from their edge sets are replaced, and nodes are
+    /// numbered from left to right.
+    pub(crate) fn from_nodes_with_pruning(
+        mut nodes: Vec<LatticeNode<'a, T>>,
+    ) -> (Lattice<'a, T>, Vec<Option<usize>>) {
+        let mut is_trunk = vec![false; nodes.len()];
+        is_trunk[0] = true;
+        let mut node_id = 0;
+        while let Some((_, next_node_id)) = nodes[node_id].forward_main {
+            is_trunk[next_node_id] = true;
+            node_id = next_node_id;
+        }
+        let last_node_id = node_id;
+        let mut reachable = vec![false; nodes.len()];
+        reachable[0] = true;
+        let mut stack = vec![0];
+        while let Some(node_id) = stack.pop() {
+            for &(_, target) in &nodes[node_id].forwards {
+                if !reachable[target] {
+                    reachable[target] = true;
+                    stack.push(target);
+                }
+            }
+        }
+        let mut coreachable = vec![false; nodes.len()];
+        coreachable[last_node_id] = true;
+        let mut stack = vec![last_node_id];
+        while let Some(node_id) = stack.pop() {
+            for &(_, source) in &nodes[node_id].backwards {
+                if !coreachable[source] {
+                    coreachable[source] = true;
+                    stack.push(source);
+                }
+            }
+        }
+        let alive: Vec<bool> = reachable
+            .iter()
+            .zip(&coreachable)
+            .map(|(&x, &y)| x && y)
+            .collect();
+        for (i, node) in nodes.iter_mut().enumerate() {
+            if !alive[i] {
+                node.forwards.clear();
+                node.backwards.clear();
+                continue;
+            }
+            node.forwards.retain(|edge| alive[edge.1]);
+            node.backwards.retain(|edge| alive[edge.1]);
+            if node
+                .forward_main
+                .is_some_and(|edge| !node.forwards.contains(&edge))
+            {
+                node.forward_main = node
+                    .forwards
+                    .iter()
+                    .find(|edge| is_trunk[edge.1])
+                    .or_else(|| node.forwards.iter().next())
+                    .cloned();
+            }
+            if node
+                .backward_main
+                .is_some_and(|edge| !node.backwards.contains(&edge))
+            {
+                node.backward_main = node
+                    .backwards
+                    .iter()
+                    .find(|edge| is_trunk[edge.1])
+                    .or_else(|| node.backwards.iter().next())
+                    .cloned();
+            }
+        }
+        Lattice::from_nodes(&nodes)
+    }
+}
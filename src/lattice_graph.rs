@@ -0,0 +1,149 @@
+use crate::lattice::Label;
+use crate::lattice::Lattice;
+use crate::lattice::SearchIndexNode;
+
+use std::collections::btree_set;
+use std::collections::VecDeque;
+use std::iter::Cloned;
+
+/// Read-only traversal of a lattice, implemented by `Lattice`, `FrozenLattice` and `LatticeView`.
+///
+/// Nodes are numbered from left to right. Node 0 is the first node, and the last node has the
+/// largest ID.
+pub trait LatticeGraph<T: Label> {
+    /// An iterator over edges of a node.
+    type Edges<'b>: Iterator<Item = (T, usize)>
+    where
+        Self: 'b;
+
+    /// Returns the number of nodes.
+    fn num_nodes(&self) -> usize;
+
+    /// Returns forward edges of the given node in ascending order of labels and node IDs.
+    fn forwards(&self, node_id: usize) -> Self::Edges<'_>;
+
+    /// Returns backward edges of the given node in ascending order of labels and node IDs.
+    fn backwards(&self, node_id: usize) -> Self::Edges<'_>;
+
+    /// Returns the forward main edge of the given node, or None for the last node.
+    fn forward_main(&self, node_id: usize) -> Option<(T, usize)>;
+
+    /// Returns the backward main edge of the given node, or None for the first node.
+    fn backward_main(&self, node_id: usize) -> Option<(T, usize)>;
+
+    /// Returns the trunk position of the given node, or None if it is not a trunk node.
+    fn trunk_position(&self, node_id: usize) -> Option<usize>;
+}
+
+impl<'a, T: Label> LatticeGraph<T> for Lattice<'a, T> {
+    type Edges<'b>
+        = Cloned<btree_set::Iter<'b, (T, usize)>>
+    where
+        Self: 'b;
+
+    fn num_nodes(&self) -> usize {
+        self.lattice.len()
+    }
+
+    fn forwards(&self, node_id: usize) -> Self::Edges<'_> {
+        self.lattice[node_id].forwards.iter().cloned()
+    }
+
+    fn backwards(&self, node_id: usize) -> Self::Edges<'_> {
+        self.lattice[node_id].backwards.iter().cloned()
+    }
+
+    fn forward_main(&self, node_id: usize) -> Option<(T, usize)> {
+        self.lattice[node_id].forward_main
+    }
+
+    fn backward_main(&self, node_id: usize) -> Option<(T, usize)> {
+        self.lattice[node_id].backward_main
+    }
+
+    fn trunk_position(&self, node_id: usize) -> Option<usize> {
+        self.trunk.get(&node_id).cloned()
+    }
+}
+
+/// Returns a trunk path of the given path by following main edges to the nearest trunk nodes.
+pub(crate) fn trunk_span<T: Label, G: LatticeGraph<T>>(
+    lattice: &G,
+    path: Vec<(T, usize)>,
+) -> Vec<(T, usize)> {
+    let mut new_path: VecDeque<(T, usize)> = path.into_iter().collect();
+    let mut edge_bw = new_path.pop_front().unwrap();
+    while lattice.trunk_position(edge_bw.1).is_none() {
+        let next_edge = lattice.backward_main(edge_bw.1).unwrap();
+        new_path.push_front((next_edge.0, edge_bw.1));
+        edge_bw = next_edge;
+    }
+    new_path.push_front((T::default(), edge_bw.1));
+    let mut edge_fw = *new_path.back().unwrap();
+    while lattice.trunk_position(edge_fw.1).is_none() {
+        edge_fw = lattice.forward_main(edge_fw.1).unwrap();
+        new_path.push_back(edge_fw);
+    }
+    new_path.into_iter().collect()
+}
+
+/// Returns trunk positions of the left and the right trunk nodes for each node ID.
+pub(crate) fn trunk_spans<T: Label, G: LatticeGraph<T>>(lattice: &G) -> Vec<(usize, usize)> {
+    let num_nodes = lattice.num_nodes();
+    let mut left_trunks = vec![0; num_nodes];
+    let mut right_trunks = vec![num_nodes - 1; num_nodes];
+    for node_id in 0..num_nodes {
+        if lattice.trunk_position(node_id).is_some() {
+            left_trunks[node_id] = node_id;
+            right_trunks[node_id] = node_id;
+        }
+    }
+    for node_id in 1..num_nodes - 1 {
+        for (_, next_node_id) in lattice.forwards(node_id) {
+            if left_trunks[next_node_id] == 0
+                && lattice.backward_main(next_node_id).unwrap().1 == node_id
+            {
+                left_trunks[next_node_id] = left_trunks[node_id];
+            }
+        }
+    }
+    for node_id in (1..num_nodes - 1).rev() {
+        for (_, prev_node_id) in lattice.backwards(node_id) {
+            if right_trunks[prev_node_id] == num_nodes - 1
+                && lattice.forward_main(prev_node_id).unwrap().1 == node_id
+            {
+                right_trunks[prev_node_id] = right_trunks[node_id];
+            }
+        }
+    }
+    left_trunks
+        .into_iter()
+        .zip(right_trunks)
+        .map(|(left, right)| {
+            (
+                lattice.trunk_position(left).unwrap(),
+                lattice.trunk_position(right).unwrap(),
+            )
+        })
+        .collect()
+}
+
+/// Returns tokens for the search index, where `num_edges` is the number of edges of the lattice.
+pub(crate) fn search_index_nodes<'a, G: LatticeGraph<&'a str>>(
+    lattice: &G,
+    num_edges: usize,
+) -> Vec<SearchIndexNode<'a>> {
+    let trunk_spans = trunk_spans(lattice);
+    let mut result = Vec::with_capacity(num_edges);
+    for i in 0..lattice.num_nodes() - 1 {
+        for (j, (s, node_id)) in lattice.forwards(i).enumerate() {
+            result.push(SearchIndexNode {
+                text: s,
+                offset: (trunk_spans[i].0, trunk_spans[node_id].1),
+                increment: if j == 0 { 1 } else { 0 },
+                length: node_id - i,
+            });
+        }
+    }
+    result
+}
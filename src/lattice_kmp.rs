@@ -1,14 +1,17 @@
-use crate::lattice::Lattice;
+use crate::lattice::Label;
+use crate::lattice_graph::LatticeGraph;
 
 use std::collections::HashSet;
 use std::collections::VecDeque;
+use std::marker::PhantomData;
 
-pub struct LatticeKMP<'a> {
-    pattern: Vec<&'a str>,
+pub struct LatticeKMP<'a, T = &'a str> {
+    pattern: Vec<T>,
     cpattern: Vec<usize>,
+    _marker: PhantomData<&'a ()>,
 }
 
-impl<'a> LatticeKMP<'a> {
+impl<'a, T: Label> LatticeKMP<'a, T> {
     /// Returns LatticeKMP with the given pattern.
     ///
     /// # Arguments
@@ -23,7 +26,7 @@ impl<'a> LatticeKMP<'a> {
     /// let pattern = vec!["幹", "細胞"];
     /// let kmp = LatticeKMP::new(pattern);
     /// ```
-    pub fn new(pattern: Vec<&'a str>) -> LatticeKMP<'a> {
+    pub fn new(pattern: Vec<T>) -> LatticeKMP<'a, T> {
         let mut cpattern = vec![0];
         let mut j;
         for i in 1..pattern.len() {
@@ -36,6 +39,7 @@ impl<'a> LatticeKMP<'a> {
         LatticeKMP {
             pattern,
             cpattern,
+            _marker: PhantomData,
         }
     }
 
@@ -43,7 +47,7 @@ impl<'a> LatticeKMP<'a> {
     ///
     /// # Arguments
     ///
-    /// * `lattice` - A lattice
+    /// * `lattice` - A lattice such as `Lattice` and `FrozenLattice`
     ///
     /// # Example
     ///
@@ -80,20 +84,20 @@ impl<'a> LatticeKMP<'a> {
     ///
     /// let results = kmp.search(&lattice);
     /// ```
-    pub fn search(&self, lattice: &'a Lattice) -> Vec<Vec<(&'a str, usize)>> {
+    pub fn search<G: LatticeGraph<T>>(&self, lattice: &G) -> Vec<Vec<(T, usize)>> {
         let mut added_candidates = HashSet::new();
         let mut queue = VecDeque::new();
         let mut candidates = VecDeque::new();
         let mut results = vec![];
         queue.push_back((0, 0));
         candidates.push_back(VecDeque::new());
-        candidates[0].push_back(("", 0));
+        candidates[0].push_back((T::default(), 0));
         while let Some(item) = queue.pop_front() {
             let candidate = candidates.pop_front().unwrap();
-            if lattice.lattice[item.0].forward_main.is_none() {
+            if lattice.forward_main(item.0).is_none() {
                 continue;
             }
-            for edge in &lattice.lattice[item.0].forwards {
+            for edge in lattice.forwards(item.0) {
                 let mut j = item.1;
                 while j > 0 && edge.0 != self.pattern[j] {
                     j = self.cpattern[j - 1];
@@ -102,13 +106,13 @@ impl<'a> LatticeKMP<'a> {
                     j += 1;
                 }
                 let mut new_candidate = VecDeque::new();
-                new_candidate.push_back(*edge);
+                new_candidate.push_back(edge);
                 let mut k = candidate.len();
                 while new_candidate.len() < j {
                     k -= 1;
                     new_candidate.push_front(candidate[k]);
                 }
-                new_candidate.push_front(("", candidate[k - 1].1));
+                new_candidate.push_front((T::default(), candidate[k - 1].1));
                 if j == self.pattern.len() {
                     results.push(new_candidate.clone().into_iter().collect());
                     j = self.cpattern[j - 1];
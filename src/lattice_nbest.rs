@@ -0,0 +1,145 @@
+use crate::lattice::Label;
+use crate::lattice::Lattice;
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+struct Candidate {
+    cost: f64,
+    edge_id: usize,
+    rank: usize,
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    // reversed for the min-heap
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .cost
+            .total_cmp(&self.cost)
+            .then_with(|| other.edge_id.cmp(&self.edge_id))
+            .then_with(|| other.rank.cmp(&self.rank))
+    }
+}
+
+impl<'a, T: Label> Lattice<'a, T> {
+    /// Returns the `k` lowest-cost paths with their total costs in ascending order of cost.
+    ///
+    /// Each path starts with `(T::default(), 0)` and continues with pairs of an edge label and the
+    /// target node ID, the same form as results of LatticeKMP.
+    ///
+    /// # Arguments
+    ///
+    /// * `k` - A maximum number of paths
+    /// * `cost` - A cost of an edge given its label, source and target node IDs
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use parattice::PaRattice;
+    ///
+    /// let paradict = vec![
+    ///     vec![
+    ///         vec!["blood", "stem", "cell"],
+    ///         vec!["造血", "幹", "細胞"],
+    ///         vec!["hematopoietic", "stem", "cell"],
+    ///     ],
+    ///     vec![
+    ///         vec!["造血", "幹", "細胞", "移植"],
+    ///         vec!["hematopoietic", "stem", "cell", "transplantation"],
+    ///     ],
+    ///     vec![vec!["stem", "cell"], vec!["幹", "細胞"]],
+    ///     vec![
+    ///         vec!["幹", "細胞", "移植"],
+    ///         vec!["rescue", "transplant"],
+    ///         vec!["stem", "cell", "rescue"],
+    ///     ],
+    ///     vec![vec!["rescue"], vec!["救命"]],
+    ///     vec![vec!["blood"], vec!["血液"]],
+    /// ];
+    /// let parattice = PaRattice::new(paradict);
+    /// let words = vec!["造血", "幹", "細胞", "移植"];
+    /// let lattice = parattice.get_lattice(&words, true, 2);
+    ///
+    /// // the shortest paraphrases
+    /// let results = lattice.n_best(5, |_, _, _| 1.0);
+    /// for (path, cost) in &results {
+    ///     println!("{:?} {}", path, cost);
+    /// }
+    /// ```
+    pub fn n_best<F>(&self, k: usize, cost: F) -> Vec<(Vec<(T, usize)>, f64)>
+    where
+        F: Fn(T, usize, usize) -> f64,
+    {
+        if k == 0 {
+            return vec![];
+        }
+        // k-best lists of (cost, edge, rank in the list of the edge target) from each node to
+        // the last node
+        let mut best = vec![vec![]; self.lattice.len()];
+        best[self.lattice.len() - 1].push((0.0, None, 0));
+        for node_id in (0..self.lattice.len() - 1).rev() {
+            let edges: Vec<(T, usize)> =
+                self.lattice[node_id].forwards.iter().cloned().collect();
+            let edge_costs: Vec<f64> = edges
+                .iter()
+                .map(|&(s, target)| cost(s, node_id, target))
+                .collect();
+            let mut heap = BinaryHeap::new();
+            for (edge_id, &(_, target)) in edges.iter().enumerate() {
+                if let Some(&(target_cost, _, _)) = best[target].first() {
+                    heap.push(Candidate {
+                        cost: edge_costs[edge_id] + target_cost,
+                        edge_id,
+                        rank: 0,
+                    });
+                }
+            }
+            let mut node_best = Vec::with_capacity(k);
+            while let Some(candidate) = heap.pop() {
+                let target = edges[candidate.edge_id].1;
+                node_best.push((
+                    candidate.cost,
+                    Some(edges[candidate.edge_id]),
+                    candidate.rank,
+                ));
+                if node_best.len() == k {
+                    break;
+                }
+                if let Some(&(target_cost, _, _)) = best[target].get(candidate.rank + 1) {
+                    heap.push(Candidate {
+                        cost: edge_costs[candidate.edge_id] + target_cost,
+                        edge_id: candidate.edge_id,
+                        rank: candidate.rank + 1,
+                    });
+                }
+            }
+            best[node_id] = node_best;
+        }
+        let mut result = Vec::with_capacity(best[0].len());
+        for &(total_cost, edge, rank) in &best[0] {
+            let mut path = vec![(T::default(), 0)];
+            let mut next = edge.map(|edge| (edge, rank));
+            while let Some((edge, rank)) = next {
+                path.push(edge);
+                let (_, next_edge, next_rank) = best[edge.1][rank];
+                next = next_edge.map(|edge| (edge, next_rank));
+            }
+            result.push((path, total_cost));
+        }
+        result
+    }
+}
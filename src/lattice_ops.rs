@@ -0,0 +1,378 @@
+use crate::lattice::Label;
+use crate::lattice::Lattice;
+use crate::lattice::LatticeNode;
+use crate::parattice::PaRattice;
+
+use std::collections::HashMap;
+use std::collections::VecDeque;
+
+impl<'a, T: Label> Lattice<'a, T> {
+    /// Returns a lattice that contains paths of both lattices.
+    ///
+    /// The first and the last nodes of the lattices are merged, and duplicated edges are
+    /// shrinked. The trunk of the result is the trunk of `self`.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - A lattice
+    ///
+    /// # Panics
+    ///
+    /// Panics if only one of the lattices has no edges.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use parattice::PaRattice;
+    ///
+    /// let paradict = vec![
+    ///     vec![
+    ///         vec!["blood", "stem", "cell"],
+    ///         vec!["造血", "幹", "細胞"],
+    ///         vec!["hematopoietic", "stem", "cell"],
+    ///     ],
+    ///     vec![
+    ///         vec!["造血", "幹", "細胞", "移植"],
+    ///         vec!["hematopoietic", "stem", "cell", "transplantation"],
+    ///     ],
+    ///     vec![vec!["stem", "cell"], vec!["幹", "細胞"]],
+    ///     vec![
+    ///         vec!["幹", "細胞", "移植"],
+    ///         vec!["rescue", "transplant"],
+    ///         vec!["stem", "cell", "rescue"],
+    ///     ],
+    ///     vec![vec!["rescue"], vec!["救命"]],
+    ///     vec![vec!["blood"], vec!["血液"]],
+    /// ];
+    /// let parattice = PaRattice::new(paradict);
+    /// let words1 = vec!["造血", "幹", "細胞", "移植"];
+    /// let words2 = vec!["造血", "幹", "細胞", "の", "移植"];
+    /// let lattice1 = parattice.get_lattice(&words1, true, 2);
+    /// let lattice2 = parattice.get_lattice(&words2, true, 2);
+    ///
+    /// let lattice = lattice1.union(&lattice2);
+    /// ```
+    pub fn union(&self, other: &Lattice<'a, T>) -> Lattice<'a, T> {
+        let self_len = self.lattice.len();
+        let other_len = other.lattice.len();
+        if self_len == 1 || other_len == 1 {
+            assert!(
+                self_len == other_len,
+                "a lattice without edges can't be merged with other lattices"
+            );
+            return Lattice::from_edges(1, &[], &[(T::default(), 0)]).0;
+        }
+        let other_node_id = |i: usize| {
+            if i == 0 {
+                0
+            } else if i == other_len - 1 {
+                self_len - 1
+            } else {
+                self_len + i - 1
+            }
+        };
+        let mut nodes: Vec<LatticeNode<'a, T>> = self
+            .lattice
+            .iter()
+            .map(|node| remap_node(node, |i| i))
+            .collect();
+        for &(s, target) in &other.lattice[0].forwards {
+            nodes[0].insert_forward(s, other_node_id(target));
+        }
+        for &(s, source) in &other.lattice[other_len - 1].backwards {
+            nodes[self_len - 1].insert_backward(s, other_node_id(source));
+        }
+        for node in &other.lattice[1..other_len - 1] {
+            nodes.push(remap_node(node, other_node_id));
+        }
+        PaRattice::shrink_lattice(&mut nodes);
+        Lattice::from_nodes(&nodes).0
+    }
+
+    /// Returns a lattice that contains paths shared by both lattices, or None if there is no
+    /// such path.
+    ///
+    /// The trunk of the result is the path that contains the most trunk edges of `self`. Ties
+    /// are broken by the number of trunk edges of `other`.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - A lattice
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use parattice::PaRattice;
+    ///
+    /// let paradict = vec![
+    ///     vec![
+    ///         vec!["blood", "stem", "cell"],
+    ///         vec!["造血", "幹", "細胞"],
+    ///         vec!["hematopoietic", "stem", "cell"],
+    ///     ],
+    ///     vec![
+    ///         vec!["造血", "幹", "細胞", "移植"],
+    ///         vec!["hematopoietic", "stem", "cell", "transplantation"],
+    ///     ],
+    ///     vec![vec!["stem", "cell"], vec!["幹", "細胞"]],
+    ///     vec![
+    ///         vec!["幹", "細胞", "移植"],
+    ///         vec!["rescue", "transplant"],
+    ///         vec!["stem", "cell", "rescue"],
+    ///     ],
+    ///     vec![vec!["rescue"], vec!["救命"]],
+    ///     vec![vec!["blood"], vec!["血液"]],
+    /// ];
+    /// let parattice = PaRattice::new(paradict);
+    /// let words1 = vec!["造血", "幹", "細胞", "移植"];
+    /// let words2 = vec!["blood", "stem", "cell", "rescue"];
+    /// let lattice1 = parattice.get_lattice(&words1, true, 2);
+    /// let lattice2 = parattice.get_lattice(&words2, true, 2);
+    ///
+    /// let lattice = lattice1.intersect(&lattice2).unwrap();
+    /// ```
+    pub fn intersect(&self, other: &Lattice<'a, T>) -> Option<Lattice<'a, T>> {
+        self.intersect_with_node_pairs(other)
+            .map(|(lattice, _)| lattice)
+    }
+
+    /// Returns the intersection of the lattices and the pair of node IDs of `self` and `other`
+    /// for each node of the intersection.
+    pub(crate) fn intersect_with_node_pairs(
+        &self,
+        other: &Lattice<'a, T>,
+    ) -> Option<(Self, Vec<(usize, usize)>)> {
+        let last_pair = (self.lattice.len() - 1, other.lattice.len() - 1);
+        let mut pairs = vec![(0, 0)];
+        let mut pair_ids = HashMap::new();
+        pair_ids.insert((0, 0), 0);
+        let mut edges = vec![];
+        let mut queue = VecDeque::new();
+        queue.push_back((0, 0));
+        while let Some((i, j)) = queue.pop_front() {
+            let pair_id = pair_ids[&(i, j)];
+            for &(s, self_target) in &self.lattice[i].forwards {
+                for &(_, other_target) in other.lattice[j].forwards.range((s, 0)..=(s, usize::MAX))
+                {
+                    let target = (self_target, other_target);
+                    let target_id = *pair_ids.entry(target).or_insert_with(|| {
+                        pairs.push(target);
+                        queue.push_back(target);
+                        pairs.len() - 1
+                    });
+                    edges.push((pair_id, s, target_id));
+                }
+            }
+        }
+        let last_pair_id = *pair_ids.get(&last_pair)?;
+        // numbers of trunk edges of self and other on the best path to the last pair
+        let mut best_counts = vec![None; pairs.len()];
+        let mut best_edges = vec![None; pairs.len()];
+        best_counts[last_pair_id] = Some((0, 0));
+        let mut forwards = vec![vec![]; pairs.len()];
+        for (edge_id, &(source, _, _)) in edges.iter().enumerate() {
+            forwards[source].push(edge_id);
+        }
+        let mut order: Vec<usize> = (0..pairs.len()).collect();
+        order.sort_by_key(|&pair_id| pairs[pair_id]);
+        for &pair_id in order.iter().rev() {
+            let (i, j) = pairs[pair_id];
+            for &edge_id in &forwards[pair_id] {
+                let (_, s, target_id) = edges[edge_id];
+                if let Some((self_count, other_count)) = best_counts[target_id] {
+                    let (self_target, other_target) = pairs[target_id];
+                    let count = (
+                        self_count + self.is_trunk_edge(i, (s, self_target)) as usize,
+                        other_count + other.is_trunk_edge(j, (s, other_target)) as usize,
+                    );
+                    if best_counts[pair_id].is_none_or(|best_count| count > best_count) {
+                        best_counts[pair_id] = Some(count);
+                        best_edges[pair_id] = Some(edge_id);
+                    }
+                }
+            }
+        }
+        let mut trunk = vec![(T::default(), 0)];
+        while let Some(edge_id) = best_edges[trunk[trunk.len() - 1].1] {
+            let (_, s, target_id) = edges[edge_id];
+            trunk.push((s, target_id));
+        }
+        let (lattice, node_id_map) = Lattice::from_edges(pairs.len(), &edges, &trunk);
+        let mut node_pairs = vec![(0, 0); lattice.lattice.len()];
+        for (pair_id, new_node_id) in node_id_map.into_iter().enumerate() {
+            if let Some(new_node_id) = new_node_id {
+                node_pairs[new_node_id] = pairs[pair_id];
+            }
+        }
+        Some((lattice, node_pairs))
+    }
+
+    /// Returns a lattice that contains paths of `self` followed by paths of `other`.
+    ///
+    /// The last node of `self` is merged with the first node of `other`, and the trunk of the
+    /// result is the trunk of `self` followed by the trunk of `other`.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - A lattice
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use parattice::PaRattice;
+    ///
+    /// let paradict = vec![
+    ///     vec![
+    ///         vec!["blood", "stem", "cell"],
+    ///         vec!["造血", "幹", "細胞"],
+    ///         vec!["hematopoietic", "stem", "cell"],
+    ///     ],
+    ///     vec![
+    ///         vec!["造血", "幹", "細胞", "移植"],
+    ///         vec!["hematopoietic", "stem", "cell", "transplantation"],
+    ///     ],
+    ///     vec![vec!["stem", "cell"], vec!["幹", "細胞"]],
+    ///     vec![
+    ///         vec!["幹", "細胞", "移植"],
+    ///         vec!["rescue", "transplant"],
+    ///         vec!["stem", "cell", "rescue"],
+    ///     ],
+    ///     vec![vec!["rescue"], vec!["救命"]],
+    ///     vec![vec!["blood"], vec!["血液"]],
+    /// ];
+    /// let parattice = PaRattice::new(paradict);
+    /// let words1 = vec!["造血", "幹", "細胞", "移植"];
+    /// let words2 = vec!["blood", "stem", "cell"];
+    /// let lattice1 = parattice.get_lattice(&words1, true, 2);
+    /// let lattice2 = parattice.get_lattice(&words2, true, 2);
+    ///
+    /// let lattice = lattice1.concat(&lattice2);
+    /// ```
+    pub fn concat(&self, other: &Lattice<'a, T>) -> Lattice<'a, T> {
+        let offset = self.lattice.len() - 1;
+        let mut nodes: Vec<LatticeNode<'a, T>> = self
+            .lattice
+            .iter()
+            .map(|node| remap_node(node, |i| i))
+            .collect();
+        for node in &other.lattice {
+            nodes.push(remap_node(node, |i| offset + i));
+        }
+        // merge the last node of self and the first node of other
+        let first_node = nodes.remove(offset + 1);
+        nodes[offset].forwards = first_node.forwards;
+        nodes[offset].forward_main = first_node.forward_main;
+        Lattice::from_nodes(&nodes).0
+    }
+
+    /// Returns a sub-lattice between two trunk positions and the original node ID of each node
+    /// of the sub-lattice.
+    ///
+    /// The sub-lattice contains the trunk between the positions and every branch that is fully
+    /// contained within the span. Node IDs are numbered from left to right.
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - A trunk position of the first node
+    /// * `end` - A trunk position of the last node
+    ///
+    /// # Panics
+    ///
+    /// Panics if `start` is greater than `end` or `end` is out of the trunk.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use parattice::PaRattice;
+    ///
+    /// let paradict = vec![
+    ///     vec![
+    ///         vec!["blood", "stem", "cell"],
+    ///         vec!["造血", "幹", "細胞"],
+    ///         vec!["hematopoietic", "stem", "cell"],
+    ///     ],
+    ///     vec![
+    ///         vec!["造血", "幹", "細胞", "移植"],
+    ///         vec!["hematopoietic", "stem", "cell", "transplantation"],
+    ///     ],
+    ///     vec![vec!["stem", "cell"], vec!["幹", "細胞"]],
+    ///     vec![
+    ///         vec!["幹", "細胞", "移植"],
+    ///         vec!["rescue", "transplant"],
+    ///         vec!["stem", "cell", "rescue"],
+    ///     ],
+    ///     vec![vec!["rescue"], vec!["救命"]],
+    ///     vec![vec!["blood"], vec!["血液"]],
+    /// ];
+    /// let parattice = PaRattice::new(paradict);
+    /// let words = vec!["造血", "幹", "細胞", "移植"];
+    /// let lattice = parattice.get_lattice(&words, true, 2);
+    ///
+    /// // 幹 細胞
+    /// let (sub_lattice, node_ids) = lattice.slice(1, 3);
+    /// ```
+    pub fn slice(&self, start: usize, end: usize) -> (Lattice<'a, T>, Vec<usize>) {
+        assert!(start <= end, "start must not be greater than end");
+        assert!(end < self.trunk.len(), "end is out of the trunk");
+        let mut start_node_id = 0;
+        let mut end_node_id = 0;
+        for (&node_id, &position) in &self.trunk {
+            if position == start {
+                start_node_id = node_id;
+            }
+            if position == end {
+                end_node_id = node_id;
+            }
+        }
+        // nodes between the trunk nodes are numbered between them, and edges out of the range
+        // are removed
+        let num_nodes = end_node_id - start_node_id + 1;
+        let mut nodes: Vec<LatticeNode<'a, T>> = self.lattice[start_node_id..=end_node_id]
+            .iter()
+            .map(|node| {
+                let mut node = remap_node(node, |i| i.wrapping_sub(start_node_id));
+                node.forwards.retain(|edge| edge.1 < num_nodes);
+                node.backwards.retain(|edge| edge.1 < num_nodes);
+                node
+            })
+            .collect();
+        nodes[0].backwards.clear();
+        nodes[0].backward_main = None;
+        let last_node = nodes.last_mut().unwrap();
+        last_node.forwards.clear();
+        last_node.forward_main = None;
+        let (lattice, node_id_map) = Lattice::from_nodes_with_pruning(nodes);
+        let mut orig_node_ids = vec![0; lattice.lattice.len()];
+        for (i, new_node_id) in node_id_map.into_iter().enumerate() {
+            if let Some(new_node_id) = new_node_id {
+                orig_node_ids[new_node_id] = start_node_id + i;
+            }
+        }
+        (lattice, orig_node_ids)
+    }
+
+    fn is_trunk_edge(&self, node_id: usize, edge: (T, usize)) -> bool {
+        self.trunk.contains_key(&node_id) && self.lattice[node_id].forward_main == Some(edge)
+    }
+}
+
+fn remap_node<'a, T: Label, F>(node: &LatticeNode<'a, T>, node_id_map: F) -> LatticeNode<'a, T>
+where
+    F: Fn(usize) -> usize,
+{
+    LatticeNode {
+        forwards: node
+            .forwards
+            .iter()
+            .map(|&(s, i)| (s, node_id_map(i)))
+            .collect(),
+        backwards: node
+            .backwards
+            .iter()
+            .map(|&(s, i)| (s, node_id_map(i)))
+            .collect(),
+        forward_main: node.forward_main.map(|(s, i)| (s, node_id_map(i))),
+        backward_main: node.backward_main.map(|(s, i)| (s, node_id_map(i))),
+        depth: node.depth,
+    }
+}
@@ -0,0 +1,150 @@
+use crate::lattice::Label;
+use crate::lattice::Lattice;
+
+use rand::Rng;
+
+impl<'a, T: Label> Lattice<'a, T> {
+    /// Returns paths sampled uniformly from all paths of the lattice.
+    ///
+    /// Each path starts with `(T::default(), 0)` and continues with pairs of an edge label and the
+    /// target node ID, the same form as results of LatticeKMP.
+    ///
+    /// # Arguments
+    ///
+    /// * `rng` - A random number generator (seed it to get reproducible results)
+    /// * `n` - A number of paths to be sampled
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use parattice::PaRattice;
+    /// use rand::rngs::StdRng;
+    /// use rand::SeedableRng;
+    ///
+    /// let paradict = vec![
+    ///     vec![
+    ///         vec!["blood", "stem", "cell"],
+    ///         vec!["造血", "幹", "細胞"],
+    ///         vec!["hematopoietic", "stem", "cell"],
+    ///     ],
+    ///     vec![
+    ///         vec!["造血", "幹", "細胞", "移植"],
+    ///         vec!["hematopoietic", "stem", "cell", "transplantation"],
+    ///     ],
+    ///     vec![vec!["stem", "cell"], vec!["幹", "細胞"]],
+    ///     vec![
+    ///         vec!["幹", "細胞", "移植"],
+    ///         vec!["rescue", "transplant"],
+    ///         vec!["stem", "cell", "rescue"],
+    ///     ],
+    ///     vec![vec!["rescue"], vec!["救命"]],
+    ///     vec![vec!["blood"], vec!["血液"]],
+    /// ];
+    /// let parattice = PaRattice::new(paradict);
+    /// let words = vec!["造血", "幹", "細胞", "移植"];
+    /// let lattice = parattice.get_lattice(&words, true, 2);
+    ///
+    /// let mut rng = StdRng::seed_from_u64(42);
+    /// let paths = lattice.sample_paths(&mut rng, 10);
+    /// ```
+    pub fn sample_paths<R: Rng>(&self, rng: &mut R, n: usize) -> Vec<Vec<(T, usize)>> {
+        self.sample_paths_weighted(rng, n, |_, _, _| 1.0)
+    }
+
+    /// Returns paths sampled with probabilities proportional to the product of edge weights.
+    ///
+    /// # Arguments
+    ///
+    /// * `rng` - A random number generator (seed it to get reproducible results)
+    /// * `n` - A number of paths to be sampled
+    /// * `weight` - A non-negative weight of an edge given its label, source and target node IDs
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use parattice::PaRattice;
+    /// use rand::rngs::StdRng;
+    /// use rand::SeedableRng;
+    ///
+    /// let paradict = vec![
+    ///     vec![
+    ///         vec!["blood", "stem", "cell"],
+    ///         vec!["造血", "幹", "細胞"],
+    ///         vec!["hematopoietic", "stem", "cell"],
+    ///     ],
+    ///     vec![
+    ///         vec!["造血", "幹", "細胞", "移植"],
+    ///         vec!["hematopoietic", "stem", "cell", "transplantation"],
+    ///     ],
+    ///     vec![vec!["stem", "cell"], vec!["幹", "細胞"]],
+    ///     vec![
+    ///         vec!["幹", "細胞", "移植"],
+    ///         vec!["rescue", "transplant"],
+    ///         vec!["stem", "cell", "rescue"],
+    ///     ],
+    ///     vec![vec!["rescue"], vec!["救命"]],
+    ///     vec![vec!["blood"], vec!["血液"]],
+    /// ];
+    /// let parattice = PaRattice::new(paradict);
+    /// let words = vec!["造血", "幹", "細胞", "移植"];
+    /// let lattice = parattice.get_lattice(&words, true, 2);
+    ///
+    /// // prefer edges on the trunk
+    /// let mut rng = StdRng::seed_from_u64(42);
+    /// let paths = lattice.sample_paths_weighted(&mut rng, 10, |_, i, j| {
+    ///     if lattice.trunk.contains_key(&i) && lattice.trunk.contains_key(&j) {
+    ///         2.0
+    ///     } else {
+    ///         1.0
+    ///     }
+    /// });
+    /// ```
+    pub fn sample_paths_weighted<R, F>(
+        &self,
+        rng: &mut R,
+        n: usize,
+        weight: F,
+    ) -> Vec<Vec<(T, usize)>>
+    where
+        R: Rng,
+        F: Fn(T, usize, usize) -> f64,
+    {
+        // total weights of paths from each node to the last node
+        let mut forward_weights = vec![0.0; self.lattice.len()];
+        forward_weights[self.lattice.len() - 1] = 1.0;
+        for node_id in (0..self.lattice.len() - 1).rev() {
+            forward_weights[node_id] = self.lattice[node_id]
+                .forwards
+                .iter()
+                .map(|&(s, target)| weight(s, node_id, target) * forward_weights[target])
+                .sum();
+        }
+        if forward_weights[0] <= 0.0 {
+            return vec![];
+        }
+        let mut result = Vec::with_capacity(n);
+        for _ in 0..n {
+            let mut path = vec![(T::default(), 0)];
+            let mut node_id = 0;
+            while let Some(fallback) = self.lattice[node_id].forward_main {
+                let mut threshold = rng.gen::<f64>() * forward_weights[node_id];
+                let mut next_edge = fallback;
+                for &(s, target) in &self.lattice[node_id].forwards {
+                    let w = weight(s, node_id, target) * forward_weights[target];
+                    if w <= 0.0 {
+                        continue;
+                    }
+                    next_edge = (s, target);
+                    if threshold < w {
+                        break;
+                    }
+                    threshold -= w;
+                }
+                path.push(next_edge);
+                node_id = next_edge.1;
+            }
+            result.push(path);
+        }
+        result
+    }
+}
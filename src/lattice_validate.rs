@@ -0,0 +1,169 @@
+use crate::error::ValidationError;
+use crate::lattice::Label;
+use crate::lattice::Lattice;
+
+use std::collections::HashSet;
+
+impl<'a, T: Label> Lattice<'a, T> {
+    /// Checks invariants of the lattice, and returns every violation if any.
+    ///
+    /// The checks are: symmetry of forward and backward edges, main edges present in the edge
+    /// sets, edges from left to right, the single first and last nodes, the trunk that follows
+    /// forward main edges from the first node to the last node, and `capacity` equal to the
+    /// number of edges.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use parattice::PaRattice;
+    ///
+    /// let paradict = vec![
+    ///     vec![
+    ///         vec!["blood", "stem", "cell"],
+    ///         vec!["造血", "幹", "細胞"],
+    ///         vec!["hematopoietic", "stem", "cell"],
+    ///     ],
+    ///     vec![
+    ///         vec!["造血", "幹", "細胞", "移植"],
+    ///         vec!["hematopoietic", "stem", "cell", "transplantation"],
+    ///     ],
+    ///     vec![vec!["stem", "cell"], vec!["幹", "細胞"]],
+    ///     vec![
+    ///         vec!["幹", "細胞", "移植"],
+    ///         vec!["rescue", "transplant"],
+    ///         vec!["stem", "cell", "rescue"],
+    ///     ],
+    ///     vec![vec!["rescue"], vec!["救命"]],
+    ///     vec![vec!["blood"], vec!["血液"]],
+    /// ];
+    /// let parattice = PaRattice::new(paradict);
+    /// let words = vec!["造血", "幹", "細胞", "移植"];
+    /// let mut lattice = parattice.get_lattice(&words, true, 2);
+    /// assert!(lattice.validate().is_ok());
+    ///
+    /// lattice.capacity += 1;
+    /// for error in lattice.validate().unwrap_err() {
+    ///     println!("{}", error);
+    /// }
+    /// ```
+    pub fn validate(&self) -> Result<(), Vec<ValidationError<T>>> {
+        let mut errors = vec![];
+        if self.lattice.is_empty() {
+            errors.push(ValidationError::Empty);
+            return Err(errors);
+        }
+        let num_nodes = self.lattice.len();
+        let last_node_id = num_nodes - 1;
+        let mut num_edges = 0;
+        for (i, node) in self.lattice.iter().enumerate() {
+            num_edges += node.forwards.len();
+            for &(s, j) in &node.forwards {
+                if j >= num_nodes {
+                    errors.push(ValidationError::NodeOutOfRange {
+                        node: i,
+                        edge: (s, j),
+                    });
+                    continue;
+                }
+                if !self.lattice[j].backwards.contains(&(s, i)) {
+                    errors.push(ValidationError::MissingBackwardEdge {
+                        source: i,
+                        label: s,
+                        target: j,
+                    });
+                }
+                if i >= j {
+                    errors.push(ValidationError::NotLeftToRight {
+                        source: i,
+                        label: s,
+                        target: j,
+                    });
+                }
+            }
+            for &(s, j) in &node.backwards {
+                if j >= num_nodes {
+                    errors.push(ValidationError::NodeOutOfRange {
+                        node: i,
+                        edge: (s, j),
+                    });
+                    continue;
+                }
+                if !self.lattice[j].forwards.contains(&(s, i)) {
+                    errors.push(ValidationError::MissingForwardEdge {
+                        source: j,
+                        label: s,
+                        target: i,
+                    });
+                }
+            }
+            match node.forward_main {
+                Some(edge) if !node.forwards.contains(&edge) => {
+                    errors.push(ValidationError::ForwardMainNotFound { node: i, edge });
+                }
+                None if i != last_node_id => {
+                    errors.push(ValidationError::MissingForwardMain { node: i });
+                }
+                _ => {}
+            }
+            match node.backward_main {
+                Some(edge) if !node.backwards.contains(&edge) => {
+                    errors.push(ValidationError::BackwardMainNotFound { node: i, edge });
+                }
+                None if i != 0 => {
+                    errors.push(ValidationError::MissingBackwardMain { node: i });
+                }
+                _ => {}
+            }
+            if i != 0 && node.backwards.is_empty() {
+                errors.push(ValidationError::ExtraSource { node: i });
+            }
+            if i != last_node_id && node.forwards.is_empty() {
+                errors.push(ValidationError::ExtraSink { node: i });
+            }
+        }
+        // the trunk must follow forward main edges from the first node to the last node
+        let mut visited = HashSet::new();
+        let mut node_id = 0;
+        loop {
+            let position = visited.len();
+            let actual = self.trunk.get(&node_id).cloned();
+            if actual != Some(position) {
+                errors.push(ValidationError::TrunkPositionMismatch {
+                    node: node_id,
+                    expected: position,
+                    actual,
+                });
+            }
+            visited.insert(node_id);
+            match self.lattice[node_id].forward_main {
+                Some((_, next_node_id))
+                    if next_node_id < num_nodes && !visited.contains(&next_node_id) =>
+                {
+                    node_id = next_node_id;
+                }
+                _ => break,
+            }
+        }
+        let mut trunk_node_ids: Vec<usize> = self
+            .trunk
+            .keys()
+            .filter(|node_id| !visited.contains(node_id))
+            .cloned()
+            .collect();
+        trunk_node_ids.sort();
+        for node_id in trunk_node_ids {
+            errors.push(ValidationError::TrunkNodeNotOnPath { node: node_id });
+        }
+        if num_edges != self.capacity {
+            errors.push(ValidationError::CapacityMismatch {
+                capacity: self.capacity,
+                num_edges,
+            });
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
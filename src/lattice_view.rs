@@ -0,0 +1,450 @@
+use crate::binary::read_header;
+use crate::binary::read_section;
+use crate::binary::FLAG_INDEXED;
+use crate::error::DecodeError;
+use crate::frozen_lattice::FrozenLattice;
+use crate::frozen_lattice::NONE;
+use crate::lattice::ByteReader;
+use crate::lattice::Lattice;
+use crate::lattice::LatticeNode;
+use crate::lattice::SearchIndexNode;
+use crate::lattice_graph::search_index_nodes;
+use crate::lattice_graph::trunk_span;
+use crate::lattice_graph::trunk_spans;
+use crate::lattice_graph::LatticeGraph;
+
+use std::slice;
+use std::str;
+
+// the number of labels, nodes and edges, and the length of labels
+const HEADER_LENGTH: usize = 16;
+
+fn push_u32s<I: IntoIterator<Item = u32>>(result: &mut Vec<u8>, xs: I) {
+    for x in xs {
+        result.extend(&x.to_le_bytes());
+    }
+}
+
+/// Returns the lattice section in the indexed layout and byte offsets of nodes in it.
+///
+/// Integers are little-endian u32, so they are aligned to 4 bytes when the binary data is. The
+/// section consists of the following items, the same arrays as `FrozenLattice`:
+///
+/// * The number of labels, nodes and edges, and the length of labels in bytes
+/// * Offsets of labels (`number of labels + 1`)
+/// * Offsets of forward edges of nodes (`number of nodes + 1`)
+/// * Forward edges as pairs of the label ID and the target node ID (`number of edges * 2`)
+/// * Offsets of backward edges of nodes (`number of nodes + 1`)
+/// * Backward edges (`number of edges * 2`)
+/// * Edge IDs of forward and backward main edges, or `u32::MAX` if none (`number of nodes * 2`)
+/// * Trunk positions of nodes, or `u32::MAX` if not on the trunk (`number of nodes`)
+/// * Depths of nodes (`number of nodes`)
+/// * Labels in UTF-8
+///
+/// The offset of a node is the offset of its first forward edge.
+pub(crate) fn encode_indexed(lattice: &FrozenLattice) -> (Vec<u8>, Vec<usize>) {
+    let labels_length: usize = lattice.labels.iter().map(|s| s.len()).sum();
+    let mut result = vec![];
+    push_u32s(
+        &mut result,
+        [
+            lattice.labels.len() as u32,
+            lattice.trunk_positions.len() as u32,
+            lattice.forward_edges.len() as u32,
+            labels_length as u32,
+        ],
+    );
+    let mut label_offset = 0;
+    result.extend(&0u32.to_le_bytes());
+    for s in &lattice.labels {
+        label_offset += s.len();
+        result.extend(&(label_offset as u32).to_le_bytes());
+    }
+    push_u32s(&mut result, lattice.forward_offsets.iter().cloned());
+    let edges_offset = result.len();
+    let offsets = lattice
+        .forward_offsets
+        .iter()
+        .take(lattice.trunk_positions.len())
+        .map(|&x| edges_offset + x as usize * 8)
+        .collect();
+    push_u32s(
+        &mut result,
+        lattice.forward_edges.iter().flat_map(|&(s, j)| [s, j]),
+    );
+    push_u32s(&mut result, lattice.backward_offsets.iter().cloned());
+    push_u32s(
+        &mut result,
+        lattice.backward_edges.iter().flat_map(|&(s, j)| [s, j]),
+    );
+    push_u32s(&mut result, lattice.forward_main.iter().cloned());
+    push_u32s(&mut result, lattice.backward_main.iter().cloned());
+    push_u32s(&mut result, lattice.trunk_positions.iter().cloned());
+    push_u32s(&mut result, lattice.depths.iter().cloned());
+    for s in &lattice.labels {
+        result.extend(s.as_bytes());
+    }
+    (result, offsets)
+}
+
+fn u32_at(array: &[u8], i: usize) -> u32 {
+    u32::from_le_bytes([
+        array[i * 4],
+        array[i * 4 + 1],
+        array[i * 4 + 2],
+        array[i * 4 + 3],
+    ])
+}
+
+fn label_at<'a>(label_offsets: &[u8], labels: &'a str, label_id: usize) -> &'a str {
+    &labels[u32_at(label_offsets, label_id) as usize..u32_at(label_offsets, label_id + 1) as usize]
+}
+
+/// Returns true if the offsets start from 0, don't decrease and end at `end`.
+fn check_offsets(offsets: &[u8], end: usize) -> bool {
+    let num_offsets = offsets.len() / 4;
+    u32_at(offsets, 0) == 0
+        && u32_at(offsets, num_offsets - 1) as usize == end
+        && (1..num_offsets).all(|i| u32_at(offsets, i - 1) <= u32_at(offsets, i))
+}
+
+/// A read-only lattice over binary data in the indexed layout.
+///
+/// Edges, main edges and trunk positions are read directly from the bytes without allocation,
+/// so binary data such as a memory-mapped file can be searched without decoding.
+#[derive(Clone, Copy, Debug)]
+pub struct LatticeView<'a> {
+    label_offsets: &'a [u8],
+    forward_offsets: &'a [u8],
+    forward_edges: &'a [u8],
+    backward_offsets: &'a [u8],
+    backward_edges: &'a [u8],
+    forward_main: &'a [u8],
+    backward_main: &'a [u8],
+    trunk_positions: &'a [u8],
+    depths: &'a [u8],
+    labels: &'a str,
+}
+
+/// An iterator over edges of a node of `LatticeView`.
+pub struct ViewEdges<'a> {
+    label_offsets: &'a [u8],
+    labels: &'a str,
+    edges: slice::ChunksExact<'a, u8>,
+}
+
+impl<'a> Iterator for ViewEdges<'a> {
+    type Item = (&'a str, usize);
+
+    fn next(&mut self) -> Option<(&'a str, usize)> {
+        self.edges.next().map(|edge| {
+            (
+                label_at(self.label_offsets, self.labels, u32_at(edge, 0) as usize),
+                u32_at(edge, 1) as usize,
+            )
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.edges.size_hint()
+    }
+}
+
+impl<'a> LatticeView<'a> {
+    /// Returns a view of the given binary data after checking its structure.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Binary data written with `Layout::Indexed`
+    ///
+    /// # Errors
+    ///
+    /// Returns `DecodeError::UnsupportedFlags` if the lattice section is not in the indexed
+    /// layout, and other errors if the data is broken.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use parattice::PaRattice;
+    /// use parattice::LatticeKMP;
+    /// use parattice::binary::BinaryOptions;
+    /// use parattice::binary::Layout;
+    /// use parattice::lattice_view::LatticeView;
+    ///
+    /// let paradict = vec![
+    ///     vec![
+    ///         vec!["blood", "stem", "cell"],
+    ///         vec!["造血", "幹", "細胞"],
+    ///         vec!["hematopoietic", "stem", "cell"],
+    ///     ],
+    ///     vec![
+    ///         vec!["造血", "幹", "細胞", "移植"],
+    ///         vec!["hematopoietic", "stem", "cell", "transplantation"],
+    ///     ],
+    ///     vec![vec!["stem", "cell"], vec!["幹", "細胞"]],
+    ///     vec![
+    ///         vec!["幹", "細胞", "移植"],
+    ///         vec!["rescue", "transplant"],
+    ///         vec!["stem", "cell", "rescue"],
+    ///     ],
+    ///     vec![vec!["rescue"], vec!["救命"]],
+    ///     vec![vec!["blood"], vec!["血液"]],
+    /// ];
+    /// let parattice = PaRattice::new(paradict);
+    /// let words = vec!["造血", "幹", "細胞", "移植"];
+    /// let lattice = parattice.get_lattice(&words, true, 2);
+    /// let bytes = lattice.to_bytes_with_options(&BinaryOptions {
+    ///     layout: Layout::Indexed,
+    ///     ..BinaryOptions::default()
+    /// });
+    ///
+    /// let view = LatticeView::new(&bytes).unwrap();
+    /// let kmp = LatticeKMP::new(vec!["幹", "細胞"]);
+    /// for path in kmp.search(&view) {
+    ///     println!("{:?}", view.get_trunk_span(path));
+    /// }
+    /// ```
+    pub fn new(data: &'a [u8]) -> Result<LatticeView<'a>, DecodeError> {
+        let (flags, mut reader) = read_header(data)?;
+        if flags & FLAG_INDEXED == 0 {
+            return Err(DecodeError::UnsupportedFlags(flags));
+        }
+        LatticeView::from_section(read_section(&mut reader)?)
+    }
+
+    /// Returns a view of the lattice section in the indexed layout.
+    pub(crate) fn from_section(data: &'a [u8]) -> Result<LatticeView<'a>, DecodeError> {
+        let mut reader = ByteReader { data, offset: 0 };
+        let num_labels = reader.read_u32()? as usize;
+        let num_nodes = reader.read_u32()? as usize;
+        let num_edges = reader.read_u32()? as usize;
+        let labels_length = reader.read_u32()? as usize;
+        let label_offsets = reader.read_bytes((num_labels + 1) * 4)?;
+        let forward_offsets = reader.read_bytes((num_nodes + 1) * 4)?;
+        let forward_edges = reader.read_bytes(num_edges * 8)?;
+        let backward_offsets = reader.read_bytes((num_nodes + 1) * 4)?;
+        let backward_edges = reader.read_bytes(num_edges * 8)?;
+        let forward_main = reader.read_bytes(num_nodes * 4)?;
+        let backward_main = reader.read_bytes(num_nodes * 4)?;
+        let trunk_positions = reader.read_bytes(num_nodes * 4)?;
+        let depths = reader.read_bytes(num_nodes * 4)?;
+        let offset = reader.offset;
+        let labels = str::from_utf8(reader.read_bytes(labels_length)?)
+            .map_err(|_| DecodeError::InvalidUtf8 { offset })?;
+        if reader.offset != data.len() {
+            return Err(DecodeError::InvalidSection(FLAG_INDEXED));
+        }
+        let view = LatticeView {
+            label_offsets,
+            forward_offsets,
+            forward_edges,
+            backward_offsets,
+            backward_edges,
+            forward_main,
+            backward_main,
+            trunk_positions,
+            depths,
+            labels,
+        };
+        view.check()?;
+        Ok(view)
+    }
+
+    /// Checks that every offset and ID is in range and that the trunk follows forward main
+    /// edges, so that queries never panic.
+    fn check(&self) -> Result<(), DecodeError> {
+        let num_labels = self.label_offsets.len() / 4 - 1;
+        let num_nodes = self.num_nodes();
+        if !check_offsets(self.label_offsets, self.labels.len())
+            || !(0..num_labels).all(|i| {
+                self.labels
+                    .is_char_boundary(u32_at(self.label_offsets, i) as usize)
+            })
+            || !check_offsets(self.forward_offsets, self.num_edges())
+            || !check_offsets(self.backward_offsets, self.num_edges())
+        {
+            return Err(DecodeError::InvalidSection(FLAG_INDEXED));
+        }
+        for node in 0..num_nodes {
+            for &(offsets, edges, main) in &[
+                (self.forward_offsets, self.forward_edges, self.forward_main),
+                (
+                    self.backward_offsets,
+                    self.backward_edges,
+                    self.backward_main,
+                ),
+            ] {
+                let begin = u32_at(offsets, node);
+                let end = u32_at(offsets, node + 1);
+                for edge_id in begin as usize..end as usize {
+                    let label = u32_at(edges, edge_id * 2) as usize;
+                    let target = u32_at(edges, edge_id * 2 + 1) as usize;
+                    if label >= num_labels {
+                        return Err(DecodeError::LabelOutOfRange { node, label });
+                    }
+                    if target >= num_nodes {
+                        return Err(DecodeError::NodeOutOfRange { node, target });
+                    }
+                }
+                let main_id = u32_at(main, node);
+                if main_id != NONE && (main_id < begin || main_id >= end) {
+                    return Err(DecodeError::InvalidMainEdge { node });
+                }
+            }
+        }
+        if num_nodes == 0 {
+            return Err(DecodeError::MissingTrunk);
+        }
+        let mut node_id = 0;
+        let mut position = 0;
+        loop {
+            if self.trunk_position(node_id) != Some(position) {
+                return Err(DecodeError::InvalidSection(FLAG_INDEXED));
+            }
+            match self.forward_main(node_id) {
+                Some((_, next_node_id)) if next_node_id > node_id => {
+                    node_id = next_node_id;
+                    position += 1;
+                }
+                Some(_) => return Err(DecodeError::MissingTrunk),
+                None => break,
+            }
+        }
+        let num_trunk_nodes = (0..num_nodes)
+            .filter(|&i| u32_at(self.trunk_positions, i) != NONE)
+            .count();
+        if node_id != num_nodes - 1 {
+            return Err(DecodeError::MissingTrunk);
+        }
+        if num_trunk_nodes != position + 1 {
+            return Err(DecodeError::InvalidSection(FLAG_INDEXED));
+        }
+        // main edges of other nodes go left and right, so they reach the trunk in both
+        // directions
+        for node in 0..num_nodes {
+            if self.trunk_position(node).is_some() {
+                continue;
+            }
+            match (self.backward_main(node), self.forward_main(node)) {
+                (Some((_, prev)), Some((_, next))) if prev < node && node < next => {}
+                _ => return Err(DecodeError::InvalidMainEdge { node }),
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the number of edges.
+    pub fn num_edges(&self) -> usize {
+        self.forward_edges.len() / 8
+    }
+
+    /// Returns the depth of the given node.
+    pub fn depth(&self, node_id: usize) -> usize {
+        u32_at(self.depths, node_id) as usize
+    }
+
+    /// Returns a trunk path of the given path.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - A path of the lattice. (e.g. a result of LatticeKMP)
+    pub fn get_trunk_span(&self, path: Vec<(&'a str, usize)>) -> Vec<(&'a str, usize)> {
+        trunk_span(self, path)
+    }
+
+    /// Returns trunk positions of the left and the right trunk nodes for each node ID.
+    pub fn get_trunk_spans(&self) -> Vec<(usize, usize)> {
+        trunk_spans(self)
+    }
+
+    /// Returns a vector of SearchIndexNode for the search index such as Elasticsearch.
+    pub fn dump_for_search_index(&self) -> Vec<SearchIndexNode<'a>> {
+        search_index_nodes(self, self.num_edges())
+    }
+
+    /// Returns a lattice decoded from the view.
+    pub fn to_lattice(&self) -> Lattice<'a> {
+        let nodes: Vec<LatticeNode<'a>> = (0..self.num_nodes())
+            .map(|node_id| {
+                let mut node = LatticeNode::new(
+                    self.forward_main(node_id),
+                    self.backward_main(node_id),
+                    self.depth(node_id),
+                );
+                node.forwards.extend(self.forwards(node_id));
+                node.backwards.extend(self.backwards(node_id));
+                node
+            })
+            .collect();
+        // the view is checked on construction
+        Lattice::from_decoded_nodes(nodes).unwrap()
+    }
+
+    /// Returns byte offsets of nodes in the lattice section.
+    pub(crate) fn node_offsets(&self) -> impl Iterator<Item = usize> + 'a {
+        let edges_offset = HEADER_LENGTH + self.label_offsets.len() + self.forward_offsets.len();
+        let forward_offsets = self.forward_offsets;
+        (0..self.num_nodes()).map(move |i| edges_offset + u32_at(forward_offsets, i) as usize * 8)
+    }
+
+    fn edges(&self, offsets: &[u8], edges: &'a [u8], node_id: usize) -> ViewEdges<'a> {
+        let begin = u32_at(offsets, node_id) as usize * 8;
+        let end = u32_at(offsets, node_id + 1) as usize * 8;
+        ViewEdges {
+            label_offsets: self.label_offsets,
+            labels: self.labels,
+            edges: edges[begin..end].chunks_exact(8),
+        }
+    }
+
+    fn main_edge(&self, edges: &[u8], main: &[u8], node_id: usize) -> Option<(&'a str, usize)> {
+        let edge_id = u32_at(main, node_id);
+        if edge_id == NONE {
+            return None;
+        }
+        Some((
+            label_at(
+                self.label_offsets,
+                self.labels,
+                u32_at(edges, edge_id as usize * 2) as usize,
+            ),
+            u32_at(edges, edge_id as usize * 2 + 1) as usize,
+        ))
+    }
+}
+
+impl<'a> LatticeGraph<&'a str> for LatticeView<'a> {
+    type Edges<'b>
+        = ViewEdges<'a>
+    where
+        Self: 'b;
+
+    fn num_nodes(&self) -> usize {
+        self.trunk_positions.len() / 4
+    }
+
+    fn forwards(&self, node_id: usize) -> ViewEdges<'a> {
+        self.edges(self.forward_offsets, self.forward_edges, node_id)
+    }
+
+    fn backwards(&self, node_id: usize) -> ViewEdges<'a> {
+        self.edges(self.backward_offsets, self.backward_edges, node_id)
+    }
+
+    fn forward_main(&self, node_id: usize) -> Option<(&'a str, usize)> {
+        self.main_edge(self.forward_edges, self.forward_main, node_id)
+    }
+
+    fn backward_main(&self, node_id: usize) -> Option<(&'a str, usize)> {
+        self.main_edge(self.backward_edges, self.backward_main, node_id)
+    }
+
+    fn trunk_position(&self, node_id: usize) -> Option<usize> {
+        let position = u32_at(self.trunk_positions, node_id);
+        if position == NONE {
+            None
+        } else {
+            Some(position as usize)
+        }
+    }
+}
@@ -1,6 +1,31 @@
+#[cfg(feature = "arrow")]
+pub mod arrow;
+pub mod ascii;
+pub mod binary;
+pub mod container;
+pub mod dot;
+pub mod error;
+pub mod frozen_lattice;
+pub mod graphml;
 pub mod lattice;
+pub mod lattice_canonical;
+pub mod lattice_edit;
+pub mod lattice_graph;
 pub mod lattice_kmp;
+pub mod lattice_nbest;
+pub mod lattice_ops;
+#[cfg(feature = "sampler")]
+pub mod lattice_sampler;
+pub mod lattice_validate;
+pub mod lattice_view;
+pub mod mermaid;
+pub mod openfst;
+pub mod paraphrase;
 pub mod parattice;
+#[cfg(feature = "protobuf")]
+pub mod protobuf;
+pub mod slf;
+pub mod svg;
 pub mod utils;
 
 pub mod externs;
@@ -0,0 +1,60 @@
+use crate::lattice::Lattice;
+
+impl<'a> Lattice<'a> {
+    /// Returns a Mermaid flowchart of the lattice.
+    ///
+    /// Trunk edges are drawn as thick arrows.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use parattice::PaRattice;
+    ///
+    /// let paradict = vec![
+    ///     vec![
+    ///         vec!["blood", "stem", "cell"],
+    ///         vec!["造血", "幹", "細胞"],
+    ///         vec!["hematopoietic", "stem", "cell"],
+    ///     ],
+    ///     vec![
+    ///         vec!["造血", "幹", "細胞", "移植"],
+    ///         vec!["hematopoietic", "stem", "cell", "transplantation"],
+    ///     ],
+    ///     vec![vec!["stem", "cell"], vec!["幹", "細胞"]],
+    ///     vec![
+    ///         vec!["幹", "細胞", "移植"],
+    ///         vec!["rescue", "transplant"],
+    ///         vec!["stem", "cell", "rescue"],
+    ///     ],
+    ///     vec![vec!["rescue"], vec!["救命"]],
+    ///     vec![vec!["blood"], vec!["血液"]],
+    /// ];
+    /// let parattice = PaRattice::new(paradict);
+    /// let words = vec!["造血", "幹", "細胞", "移植"];
+    /// let lattice = parattice.get_lattice(&words, true, 2);
+    ///
+    /// let mermaid = lattice.dump_mermaid();
+    /// println!("{}", mermaid);
+    /// ```
+    pub fn dump_mermaid(&self) -> String {
+        let mut result = "flowchart LR\n".to_string();
+        for i in 0..self.lattice.len() {
+            result += &format!("    n{}(({}))\n", i, i);
+        }
+        for (i, node) in self.lattice.iter().enumerate() {
+            for &edge in &node.forwards {
+                let is_trunk_edge = self.trunk.contains_key(&i)
+                    && self.trunk.contains_key(&edge.1)
+                    && node.forward_main == Some(edge);
+                result += &format!(
+                    "    n{} {}|\"{}\"| n{}\n",
+                    i,
+                    if is_trunk_edge { "==>" } else { "-->" },
+                    edge.0.replace('"', "#quot;"),
+                    edge.1
+                );
+            }
+        }
+        result
+    }
+}
@@ -0,0 +1,440 @@
+use crate::error::ParseError;
+use crate::lattice::EdgeWeights;
+use crate::lattice::GenericLattice;
+use crate::lattice::Lattice;
+use crate::lattice::OwnedEdgeWeights;
+use crate::utils::lowest_cost_path;
+use crate::utils::topological_order;
+
+use std::borrow::Cow;
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+
+/// Returns the label as a symbol of the text format, where whitespace can't be written. Backslashes
+/// and whitespace are escaped with backslashes, and `<eps>` is escaped as `\<eps>`.
+fn escape_symbol(s: &str) -> Cow<'_, str> {
+    if s != "<eps>" && !s.contains(|c: char| c == '\\' || c.is_whitespace()) {
+        return Cow::Borrowed(s);
+    }
+    let mut result = String::new();
+    if s == "<eps>" {
+        result.push('\\');
+    }
+    for c in s.chars() {
+        match c {
+            '\\' => result += "\\\\",
+            ' ' => result += "\\s",
+            '\t' => result += "\\t",
+            '\n' => result += "\\n",
+            '\r' => result += "\\r",
+            c if c.is_whitespace() => result.extend(c.escape_unicode()),
+            c => result.push(c),
+        }
+    }
+    Cow::Owned(result)
+}
+
+/// Returns the symbol without escapes of `escape_symbol()`. A backslash followed by another
+/// character is the character itself.
+fn unescape_symbol(s: &str, line_number: usize) -> Result<Cow<'_, str>, ParseError> {
+    if !s.contains('\\') {
+        return Ok(Cow::Borrowed(s));
+    }
+    let mut result = String::new();
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('s') => result.push(' '),
+            Some('t') => result.push('\t'),
+            Some('n') => result.push('\n'),
+            Some('r') => result.push('\r'),
+            Some('u') => {
+                let invalid_escape = || ParseError::new(line_number, "an invalid unicode escape");
+                let (hex, rest) = chars
+                    .as_str()
+                    .strip_prefix('{')
+                    .and_then(|rest| rest.split_once('}'))
+                    .ok_or_else(invalid_escape)?;
+                let c = u32::from_str_radix(hex, 16)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .ok_or_else(invalid_escape)?;
+                result.push(c);
+                chars = rest.chars();
+            }
+            Some(c) => result.push(c),
+            None => {
+                return Err(ParseError::new(
+                    line_number,
+                    "a backslash at the end of a symbol",
+                ))
+            }
+        }
+    }
+    Ok(Cow::Owned(result))
+}
+
+/// A way to choose the trunk of a lattice imported from the OpenFst text format.
+pub enum OpenFstTrunk {
+    /// State IDs of the trunk from the start state to the final state
+    States(Vec<usize>),
+    /// The path that has the lowest total weight
+    BestPath,
+}
+
+impl<'a> Lattice<'a> {
+    /// Returns the lattice in the OpenFst text format of an acceptor and its symbol table.
+    ///
+    /// State IDs are node IDs of the lattice, and symbol IDs are assigned in the order of
+    /// labels. The symbol 0 is reserved for `<eps>`. Backslashes and whitespace in labels are
+    /// escaped with backslashes, and a label `<eps>` is written as `\<eps>`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use parattice::PaRattice;
+    ///
+    /// let paradict = vec![
+    ///     vec![
+    ///         vec!["blood", "stem", "cell"],
+    ///         vec!["造血", "幹", "細胞"],
+    ///         vec!["hematopoietic", "stem", "cell"],
+    ///     ],
+    ///     vec![
+    ///         vec!["造血", "幹", "細胞", "移植"],
+    ///         vec!["hematopoietic", "stem", "cell", "transplantation"],
+    ///     ],
+    ///     vec![vec!["stem", "cell"], vec!["幹", "細胞"]],
+    ///     vec![
+    ///         vec!["幹", "細胞", "移植"],
+    ///         vec!["rescue", "transplant"],
+    ///         vec!["stem", "cell", "rescue"],
+    ///     ],
+    ///     vec![vec!["rescue"], vec!["救命"]],
+    ///     vec![vec!["blood"], vec!["血液"]],
+    /// ];
+    /// let parattice = PaRattice::new(paradict);
+    /// let words = vec!["造血", "幹", "細胞", "移植"];
+    /// let lattice = parattice.get_lattice(&words, true, 2);
+    ///
+    /// let (fst, symbols) = lattice.dump_openfst();
+    /// println!("{}", fst);
+    /// ```
+    pub fn dump_openfst(&self) -> (String, String) {
+        self.dump_openfst_impl(None::<fn(&str, usize, usize) -> f64>)
+    }
+
+    /// Returns the weighted lattice in the OpenFst text format of an acceptor and its symbol
+    /// table.
+    ///
+    /// # Arguments
+    ///
+    /// * `weight` - A weight of an edge given its label, source and target node IDs
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use parattice::PaRattice;
+    ///
+    /// let paradict = vec![
+    ///     vec![
+    ///         vec!["blood", "stem", "cell"],
+    ///         vec!["造血", "幹", "細胞"],
+    ///         vec!["hematopoietic", "stem", "cell"],
+    ///     ],
+    ///     vec![
+    ///         vec!["造血", "幹", "細胞", "移植"],
+    ///         vec!["hematopoietic", "stem", "cell", "transplantation"],
+    ///     ],
+    ///     vec![vec!["stem", "cell"], vec!["幹", "細胞"]],
+    ///     vec![
+    ///         vec!["幹", "細胞", "移植"],
+    ///         vec!["rescue", "transplant"],
+    ///         vec!["stem", "cell", "rescue"],
+    ///     ],
+    ///     vec![vec!["rescue"], vec!["救命"]],
+    ///     vec![vec!["blood"], vec!["血液"]],
+    /// ];
+    /// let parattice = PaRattice::new(paradict);
+    /// let words = vec!["造血", "幹", "細胞", "移植"];
+    /// let lattice = parattice.get_lattice(&words, true, 2);
+    ///
+    /// // penalize edges out of the trunk
+    /// let (fst, symbols) = lattice.dump_openfst_weighted(|_, i, j| {
+    ///     if lattice.trunk.contains_key(&i) && lattice.trunk.contains_key(&j) {
+    ///         0.0
+    ///     } else {
+    ///         1.0
+    ///     }
+    /// });
+    /// ```
+    pub fn dump_openfst_weighted<F>(&self, weight: F) -> (String, String)
+    where
+        F: Fn(&str, usize, usize) -> f64,
+    {
+        self.dump_openfst_impl(Some(weight))
+    }
+
+    fn dump_openfst_impl<F>(&self, weight: Option<F>) -> (String, String)
+    where
+        F: Fn(&str, usize, usize) -> f64,
+    {
+        let labels: BTreeSet<&str> = self
+            .lattice
+            .iter()
+            .flat_map(|node| node.forwards.iter().map(|edge| edge.0))
+            .collect();
+        let mut symbols = "<eps>\t0\n".to_string();
+        let mut symbol_ids = HashMap::new();
+        for (i, s) in labels.into_iter().enumerate() {
+            symbols += &format!("{}\t{}\n", escape_symbol(s), i + 1);
+            symbol_ids.insert(s, i + 1);
+        }
+        let mut fst = String::new();
+        for (i, node) in self.lattice.iter().enumerate() {
+            for &(s, target) in &node.forwards {
+                if let Some(weight) = &weight {
+                    fst += &format!(
+                        "{}\t{}\t{}\t{}\n",
+                        i,
+                        target,
+                        symbol_ids[s],
+                        weight(s, i, target)
+                    );
+                } else {
+                    fst += &format!("{}\t{}\t{}\n", i, target, symbol_ids[s]);
+                }
+            }
+        }
+        fst += &format!("{}\n", self.lattice.len() - 1);
+        (fst, symbols)
+    }
+
+    /// Returns a lattice and its edge weights from the OpenFst text format of an acceptor.
+    ///
+    /// The start state is the source state of the first line, and exactly one final state is
+    /// required. Epsilon transitions and cycles are not supported, and `<eps>` is reserved for
+    /// the symbol 0. Missing weights are 0. Symbols escaped with backslashes can't be borrowed
+    /// from the input. Use `GenericLattice::<String>::from_openfst_owned()` for them.
+    ///
+    /// # Arguments
+    ///
+    /// * `fst` - Arcs and a final state in the text format
+    /// * `symbols` - A symbol table
+    /// * `trunk` - A way to choose the trunk
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use parattice::openfst::OpenFstTrunk;
+    /// use parattice::Lattice;
+    ///
+    /// let fst = "0 1 1 0.5\n0 1 2 0.1\n1 2 3\n2\n";
+    /// let symbols = "<eps> 0\nblood 1\n血液 2\ncell 3\n";
+    ///
+    /// let (lattice, weights) =
+    ///     Lattice::from_openfst(fst, symbols, OpenFstTrunk::BestPath).unwrap();
+    /// assert_eq!(Some(("血液", 1)), lattice.lattice[0].forward_main);
+    /// ```
+    pub fn from_openfst(
+        fst: &str,
+        symbols: &'a str,
+        trunk: OpenFstTrunk,
+    ) -> Result<(Lattice<'a>, EdgeWeights<'a>), ParseError> {
+        let mut symbol_table = HashMap::new();
+        for (id, symbol, line_number) in parse_symbols(symbols)? {
+            match symbol {
+                Cow::Borrowed(symbol) => symbol_table.insert(id, symbol),
+                Cow::Owned(_) => {
+                    return Err(ParseError::new(
+                        line_number,
+                        "escaped symbols need owned labels",
+                    ))
+                }
+            };
+        }
+        lattice_from_fst(fst, &symbol_table, trunk)
+    }
+}
+
+impl GenericLattice<String> {
+    /// Returns a lattice with owned labels and its edge weights from the OpenFst text format of
+    /// an acceptor. Symbols escaped with backslashes are unescaped. See `Lattice::from_openfst()`
+    /// for the details.
+    ///
+    /// # Arguments
+    ///
+    /// * `fst` - Arcs and a final state in the text format
+    /// * `symbols` - A symbol table
+    /// * `trunk` - A way to choose the trunk
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use parattice::lattice::GenericLattice;
+    /// use parattice::openfst::OpenFstTrunk;
+    ///
+    /// let fst = "0 1 1\n1\n";
+    /// let symbols = "<eps> 0\nstem\\scell 1\n";
+    ///
+    /// let (lattice, weights) =
+    ///     GenericLattice::from_openfst_owned(fst, symbols, OpenFstTrunk::BestPath).unwrap();
+    /// assert_eq!(Some(("stem cell".to_string(), 1)), lattice.lattice[0].forward_main);
+    /// ```
+    pub fn from_openfst_owned(
+        fst: &str,
+        symbols: &str,
+        trunk: OpenFstTrunk,
+    ) -> Result<(GenericLattice<String>, OwnedEdgeWeights), ParseError> {
+        let symbols = parse_symbols(symbols)?;
+        let symbol_table = symbols
+            .iter()
+            .map(|(id, symbol, _)| (*id, symbol.as_ref()))
+            .collect();
+        let (lattice, weights) = lattice_from_fst(fst, &symbol_table, trunk)?;
+        let weights = weights
+            .into_iter()
+            .map(|((source, s, target), weight)| ((source, s.to_string(), target), weight))
+            .collect();
+        Ok((lattice.to_owned_lattice(), weights))
+    }
+}
+
+/// Returns symbol IDs, unescaped symbols and line numbers of the symbol table.
+fn parse_symbols(symbols: &str) -> Result<Vec<(usize, Cow<'_, str>, usize)>, ParseError> {
+    let mut result = vec![];
+    for (i, line) in symbols.lines().enumerate() {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        match fields.len() {
+            0 => continue,
+            2 => {
+                let id: usize = fields[1]
+                    .parse()
+                    .map_err(|_| ParseError::new(i + 1, "invalid symbol ID"))?;
+                if fields[0] == "<eps>" && id != 0 {
+                    return Err(ParseError::new(i + 1, "<eps> is reserved for the symbol 0"));
+                }
+                result.push((id, unescape_symbol(fields[0], i + 1)?, i + 1));
+            }
+            _ => return Err(ParseError::new(i + 1, "invalid number of fields")),
+        }
+    }
+    Ok(result)
+}
+
+/// Returns a lattice and its edge weights from arcs of the text format with the symbol table.
+fn lattice_from_fst<'b>(
+    fst: &str,
+    symbol_table: &HashMap<usize, &'b str>,
+    trunk: OpenFstTrunk,
+) -> Result<(Lattice<'b>, EdgeWeights<'b>), ParseError> {
+    let mut state_ids = HashMap::new();
+    let mut get_state_id = |state: &str, line: usize| {
+        let state: usize = state
+            .parse()
+            .map_err(|_| ParseError::new(line, "invalid state ID"))?;
+        let num_states = state_ids.len();
+        Ok(*state_ids.entry(state).or_insert(num_states))
+    };
+    let mut arcs = vec![];
+    let mut weights = vec![];
+    let mut finals = vec![];
+    for (i, line) in fst.lines().enumerate() {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        match fields.len() {
+            0 => continue,
+            1 | 2 => finals.push(get_state_id(fields[0], i + 1)?),
+            3 | 4 => {
+                let source = get_state_id(fields[0], i + 1)?;
+                let target = get_state_id(fields[1], i + 1)?;
+                let symbol_id: usize = fields[2]
+                    .parse()
+                    .map_err(|_| ParseError::new(i + 1, "invalid symbol ID"))?;
+                if symbol_id == 0 {
+                    return Err(ParseError::new(
+                        i + 1,
+                        "epsilon transitions are not supported",
+                    ));
+                }
+                let s = *symbol_table
+                    .get(&symbol_id)
+                    .ok_or_else(|| ParseError::new(i + 1, "unknown symbol ID"))?;
+                let weight = if fields.len() == 4 {
+                    fields[3]
+                        .parse()
+                        .map_err(|_| ParseError::new(i + 1, "invalid weight"))?
+                } else {
+                    0.0
+                };
+                arcs.push((source, s, target));
+                weights.push(weight);
+            }
+            _ => return Err(ParseError::new(i + 1, "invalid number of fields")),
+        }
+    }
+    if finals.len() != 1 {
+        return Err(ParseError::new(0, "exactly one final state is required"));
+    }
+    let num_states = state_ids.len();
+    let mut raw_state_ids = vec![0; num_states];
+    for (&raw_state_id, &state_id) in &state_ids {
+        raw_state_ids[state_id] = raw_state_id;
+    }
+    // the state of the first line
+    let start_state_id = 0;
+    let final_state_id = finals[0];
+    if topological_order(num_states, &arcs).is_none() {
+        return Err(ParseError::new(0, "cycles are not supported"));
+    }
+    let trunk_arcs = match trunk {
+        OpenFstTrunk::BestPath => {
+            lowest_cost_path(num_states, &arcs, &weights, start_state_id, final_state_id)
+                .ok_or_else(|| ParseError::new(0, "the final state is not reachable"))?
+        }
+        OpenFstTrunk::States(states) => {
+            if states.first() != Some(&raw_state_ids[start_state_id])
+                || states.last() != Some(&raw_state_ids[final_state_id])
+            {
+                return Err(ParseError::new(
+                    0,
+                    "the trunk must start from the start state and end at the final state",
+                ));
+            }
+            let mut trunk_arcs = vec![];
+            for i in 1..states.len() {
+                let source = state_ids.get(&states[i - 1]);
+                let target = state_ids.get(&states[i]);
+                let arc_id = (0..arcs.len())
+                    .filter(|&arc_id| {
+                        Some(&arcs[arc_id].0) == source && Some(&arcs[arc_id].2) == target
+                    })
+                    .min_by(|&x, &y| weights[x].total_cmp(&weights[y]))
+                    .ok_or_else(|| {
+                        ParseError::new(
+                            0,
+                            format!("no arc from state {} to {}", states[i - 1], states[i]),
+                        )
+                    })?;
+                trunk_arcs.push(arc_id);
+            }
+            trunk_arcs
+        }
+    };
+    let mut trunk = vec![("", start_state_id)];
+    for arc_id in trunk_arcs {
+        trunk.push((arcs[arc_id].1, arcs[arc_id].2));
+    }
+    let (lattice, node_id_map) = Lattice::from_edges(num_states, &arcs, &trunk);
+    let mut edge_weights = EdgeWeights::new();
+    for (&(source, s, target), &weight) in arcs.iter().zip(&weights) {
+        if let (Some(source), Some(target)) = (node_id_map[source], node_id_map[target]) {
+            let w = edge_weights.entry((source, s, target)).or_insert(weight);
+            *w = w.min(weight);
+        }
+    }
+    Ok((lattice, edge_weights))
+}
@@ -0,0 +1,157 @@
+use crate::lattice::Lattice;
+use crate::parattice::PaRattice;
+
+use std::cmp;
+
+/// Options of lattice generation used by `PaRattice::are_paraphrases`.
+pub struct ParaphraseOptions {
+    /// If true, duplicated edges are shrinked
+    pub shrink: bool,
+    /// A number of recursion
+    pub max_depth: usize,
+}
+
+impl Default for ParaphraseOptions {
+    fn default() -> ParaphraseOptions {
+        ParaphraseOptions {
+            shrink: true,
+            max_depth: 2,
+        }
+    }
+}
+
+/// An edge of a shared path aligned to both trunks.
+#[derive(Debug, PartialEq)]
+pub struct AlignedEdge<'a> {
+    pub text: &'a str,
+    /// A trunk span of the first sentence
+    pub offset_a: (usize, usize),
+    /// A trunk span of the second sentence
+    pub offset_b: (usize, usize),
+}
+
+/// A result of `PaRattice::are_paraphrases`.
+#[derive(Debug, PartialEq)]
+pub struct ParaphraseMatch<'a> {
+    /// A path shared by both lattices, or None if the sentences are not paraphrases
+    pub witness: Option<Vec<AlignedEdge<'a>>>,
+    /// 1.0 if the sentences are paraphrases, or otherwise the length of the longest common
+    /// subsequence of any two paths divided by the length of the longest path of both lattices
+    pub score: f64,
+}
+
+impl<'a> PaRattice<'a> {
+    /// Returns whether the given sentences are paraphrases under the dictionary.
+    ///
+    /// The witness is the shared path that contains the most trunk edges of `a`.
+    ///
+    /// # Arguments
+    ///
+    /// * `a` - A sentence
+    /// * `b` - A sentence
+    /// * `options` - Options of lattice generation
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use parattice::paraphrase::ParaphraseOptions;
+    /// use parattice::PaRattice;
+    ///
+    /// let paradict = vec![
+    ///     vec![
+    ///         vec!["blood", "stem", "cell"],
+    ///         vec!["造血", "幹", "細胞"],
+    ///         vec!["hematopoietic", "stem", "cell"],
+    ///     ],
+    ///     vec![
+    ///         vec!["造血", "幹", "細胞", "移植"],
+    ///         vec!["hematopoietic", "stem", "cell", "transplantation"],
+    ///     ],
+    ///     vec![vec!["stem", "cell"], vec!["幹", "細胞"]],
+    ///     vec![
+    ///         vec!["幹", "細胞", "移植"],
+    ///         vec!["rescue", "transplant"],
+    ///         vec!["stem", "cell", "rescue"],
+    ///     ],
+    ///     vec![vec!["rescue"], vec!["救命"]],
+    ///     vec![vec!["blood"], vec!["血液"]],
+    /// ];
+    /// let parattice = PaRattice::new(paradict);
+    /// let a = vec!["造血", "幹", "細胞", "移植"];
+    /// let b = vec!["blood", "stem", "cell", "rescue"];
+    ///
+    /// let result = parattice.are_paraphrases(&a, &b, &ParaphraseOptions::default());
+    /// assert!(result.witness.is_some());
+    /// ```
+    pub fn are_paraphrases(
+        &self,
+        a: &[&'a str],
+        b: &[&'a str],
+        options: &ParaphraseOptions,
+    ) -> ParaphraseMatch<'a> {
+        let lattice_a = self.get_lattice(a, options.shrink, options.max_depth);
+        let lattice_b = self.get_lattice(b, options.shrink, options.max_depth);
+        if let Some((lattice, node_pairs)) = lattice_a.intersect_with_node_pairs(&lattice_b) {
+            let trunk_spans_a = lattice_a.get_trunk_spans();
+            let trunk_spans_b = lattice_b.get_trunk_spans();
+            let mut witness = vec![];
+            let mut node_id = 0;
+            while let Some((s, next_node_id)) = lattice.lattice[node_id].forward_main {
+                let (a_start, b_start) = node_pairs[node_id];
+                let (a_end, b_end) = node_pairs[next_node_id];
+                witness.push(AlignedEdge {
+                    text: s,
+                    offset_a: (trunk_spans_a[a_start].0, trunk_spans_a[a_end].1),
+                    offset_b: (trunk_spans_b[b_start].0, trunk_spans_b[b_end].1),
+                });
+                node_id = next_node_id;
+            }
+            ParaphraseMatch {
+                witness: Some(witness),
+                score: 1.0,
+            }
+        } else {
+            let max_length = cmp::max(
+                longest_path_length(&lattice_a),
+                longest_path_length(&lattice_b),
+            );
+            ParaphraseMatch {
+                witness: None,
+                score: longest_common_subsequence(&lattice_a, &lattice_b) as f64
+                    / max_length as f64,
+            }
+        }
+    }
+}
+
+fn longest_path_length(lattice: &Lattice) -> usize {
+    let mut lengths = vec![0; lattice.lattice.len()];
+    for node_id in (0..lattice.lattice.len()).rev() {
+        for &(_, target) in &lattice.lattice[node_id].forwards {
+            lengths[node_id] = cmp::max(lengths[node_id], lengths[target] + 1);
+        }
+    }
+    lengths[0]
+}
+
+fn longest_common_subsequence(a: &Lattice, b: &Lattice) -> usize {
+    let b_len = b.lattice.len();
+    // lengths of the longest common subsequences from each pair of nodes to the last nodes
+    let mut lengths = vec![0; a.lattice.len() * b_len];
+    for i in (0..a.lattice.len()).rev() {
+        for j in (0..b_len).rev() {
+            let mut length = 0;
+            for &(s, i_next) in &a.lattice[i].forwards {
+                length = cmp::max(length, lengths[i_next * b_len + j]);
+                for &(_, j_next) in b.lattice[j].forwards.range((s, 0)..=(s, usize::MAX)) {
+                    length = cmp::max(length, lengths[i_next * b_len + j_next] + 1);
+                }
+            }
+            for &(_, j_next) in &b.lattice[j].forwards {
+                length = cmp::max(length, lengths[i * b_len + j_next]);
+            }
+            lengths[i * b_len + j] = length;
+        }
+    }
+    lengths[0]
+}
@@ -4,28 +4,45 @@ use std::collections::BTreeSet;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::collections::VecDeque;
+use std::marker::PhantomData;
 use std::mem;
 use std::usize;
 
+use crate::lattice::Label;
 use crate::lattice::Lattice;
 use crate::lattice::LatticeNode;
 use crate::utils::get_two_mut_elems;
 
-pub struct PMANode<'a> {
-    edges: HashMap<&'a str, usize>,
+pub struct PMANode<'a, T = &'a str> {
+    edges: HashMap<T, usize>,
     fail: usize,
     matched: Vec<usize>,
+    _marker: PhantomData<&'a ()>,
 }
 
-pub struct PaRattice<'a> {
-    pma: Vec<PMANode<'a>>,
-    phrases: Vec<(Vec<&'a str>, usize)>,
+impl<'a, T: Label> PMANode<'a, T> {
+    fn new() -> PMANode<'a, T> {
+        PMANode {
+            edges: HashMap::new(),
+            fail: 0,
+            matched: vec![],
+            _marker: PhantomData,
+        }
+    }
+}
+
+pub struct PaRattice<'a, T = &'a str> {
+    pma: Vec<PMANode<'a, T>>,
+    phrases: Vec<(Vec<T>, usize)>,
     dict: Vec<Vec<usize>>,
 }
 
-impl<'a> PaRattice<'a> {
+impl<'a, T: Label> PaRattice<'a, T> {
     /// Returns PaRattice with the given paraphrase corpus.
     ///
+    /// Words can be any label type such as token IDs, and lattices generated by the PaRattice
+    /// have the same label type.
+    ///
     /// # Arguments
     ///
     /// * `dict` - A paraphrase corpus
@@ -55,13 +72,13 @@ impl<'a> PaRattice<'a> {
     ///     vec![vec!["blood"], vec!["血液"]],
     /// ];
     /// let parattice = PaRattice::new(paradict);
+    ///
+    /// // token IDs
+    /// let paradict = vec![vec![vec![1u32, 2], vec![3]]];
+    /// let parattice = PaRattice::new(paradict);
     /// ```
-    pub fn new(dict: Vec<Vec<Vec<&'a str>>>) -> PaRattice<'a> {
-        let mut pma = vec![PMANode {
-            edges: HashMap::new(),
-            fail: 0,
-            matched: vec![],
-        }];
+    pub fn new(dict: Vec<Vec<Vec<T>>>) -> PaRattice<'a, T> {
+        let mut pma = vec![PMANode::new()];
         let mut phrases = vec![];
         let mut ids = vec![];
         for (i, group) in dict.iter().enumerate() {
@@ -73,12 +90,8 @@ impl<'a> PaRattice<'a> {
                         node_id_next
                     } else {
                         let node_id_next = pma.len();
-                        pma[node_id].edges.insert(word, node_id_next);
-                        pma.push(PMANode {
-                            edges: HashMap::new(),
-                            fail: 0,
-                            matched: vec![],
-                        });
+                        pma[node_id].edges.insert(*word, node_id_next);
+                        pma.push(PMANode::new());
                         node_id_next
                     }
                 }
@@ -117,8 +130,8 @@ impl<'a> PaRattice<'a> {
     }
 
     fn backward_match(
-        phrase: &[&str],
-        lattice: &[LatticeNode],
+        phrase: &[T],
+        lattice: &[LatticeNode<T>],
         pos: usize,
         max_depth: usize,
     ) -> Vec<(usize, usize)> {
@@ -146,10 +159,10 @@ impl<'a> PaRattice<'a> {
         result
     }
 
-    fn next_pma_state_id(pma: &[PMANode], state_id: usize, edge_str: &str) -> usize {
+    fn next_pma_state_id(pma: &[PMANode<T>], state_id: usize, edge_str: T) -> usize {
         let mut next_state_id = state_id;
         loop {
-            if let Some(&x) = pma[next_state_id].edges.get(edge_str) {
+            if let Some(&x) = pma[next_state_id].edges.get(&edge_str) {
                 return x;
             }
             if next_state_id == 0 {
@@ -160,9 +173,9 @@ impl<'a> PaRattice<'a> {
     }
 
     fn insert_branch(
-        lattice: &mut Vec<LatticeNode<'a>>,
+        lattice: &mut Vec<LatticeNode<'a, T>>,
         state_id_cache: &mut Vec<BTreeSet<usize>>,
-        phrase: &[&'a str],
+        phrase: &[T],
         start_node_id: usize,
         end_node_id: usize,
         depth: usize,
@@ -171,23 +184,23 @@ impl<'a> PaRattice<'a> {
         assert!(!lattice.is_empty());
         match phrase.len() {
             1 => {
-                lattice[start_node_id].insert_forward(&phrase[0], end_node_id);
-                lattice[end_node_id].insert_backward(&phrase[0], start_node_id);
+                lattice[start_node_id].insert_forward(phrase[0], end_node_id);
+                lattice[end_node_id].insert_backward(phrase[0], start_node_id);
                 end_node_id
             }
             2 => {
-                lattice[start_node_id].insert_forward(&phrase[0], new_node_id);
+                lattice[start_node_id].insert_forward(phrase[0], new_node_id);
                 lattice.push(LatticeNode::new(
                     (phrase[1], end_node_id),
                     (phrase[0], start_node_id),
                     depth,
                 ));
                 state_id_cache.push(BTreeSet::new());
-                lattice[end_node_id].insert_backward(&phrase[1], new_node_id);
+                lattice[end_node_id].insert_backward(phrase[1], new_node_id);
                 new_node_id
             }
             3 => {
-                lattice[start_node_id].insert_forward(&phrase[0], new_node_id);
+                lattice[start_node_id].insert_forward(phrase[0], new_node_id);
                 lattice.push(LatticeNode::new(
                     (phrase[1], new_node_id + 1),
                     (phrase[0], start_node_id),
@@ -200,11 +213,11 @@ impl<'a> PaRattice<'a> {
                     depth,
                 ));
                 state_id_cache.push(BTreeSet::new());
-                lattice[end_node_id].insert_backward(&phrase[2], new_node_id + 1);
+                lattice[end_node_id].insert_backward(phrase[2], new_node_id + 1);
                 new_node_id
             }
             _ => {
-                lattice[start_node_id].insert_forward(&phrase[0], new_node_id);
+                lattice[start_node_id].insert_forward(phrase[0], new_node_id);
                 lattice.push(LatticeNode::new(
                     (phrase[1], new_node_id + 1),
                     (phrase[0], start_node_id),
@@ -229,7 +242,7 @@ impl<'a> PaRattice<'a> {
                 ));
                 state_id_cache.push(BTreeSet::new());
                 lattice[end_node_id]
-                    .insert_backward(&phrase[phrase.len() - 1], new_node_id + phrase.len() - 2);
+                    .insert_backward(phrase[phrase.len() - 1], new_node_id + phrase.len() - 2);
                 new_node_id
             }
         }
@@ -272,7 +285,7 @@ impl<'a> PaRattice<'a> {
     /// let words = vec!["造血", "幹", "細胞", "移植"];
     /// let lattice = parattice.get_lattice(&words, true, 2);
     /// ```
-    pub fn get_lattice(&self, words: &[&'a str], shrink: bool, max_depth: usize) -> Lattice<'a> {
+    pub fn get_lattice(&self, words: &[T], shrink: bool, max_depth: usize) -> Lattice<'a, T> {
         let mut inserted_branches = HashSet::new();
         // generate initial lattice
         let mut lattice = vec![];
@@ -306,7 +319,7 @@ impl<'a> PaRattice<'a> {
             for (lattice_edge_str, lattice_egde_target) in lattice[lattice_node_id].forwards.iter()
             {
                 let pma_state_id_new =
-                    Self::next_pma_state_id(&self.pma, pma_state_id, lattice_edge_str);
+                    Self::next_pma_state_id(&self.pma, pma_state_id, *lattice_edge_str);
                 if !state_id_cache[*lattice_egde_target].contains(&pma_state_id_new) {
                     // queue next node
                     queue.push_back((*lattice_egde_target, pma_state_id_new));
@@ -346,7 +359,7 @@ impl<'a> PaRattice<'a> {
                             let pma_state_id_new = Self::next_pma_state_id(
                                 &self.pma,
                                 *pma_state_id_cached,
-                                &paraphrase[0],
+                                paraphrase[0],
                             );
                             if !state_id_cache_next.contains(&pma_state_id_new) {
                                 // queue added node
@@ -361,24 +374,10 @@ impl<'a> PaRattice<'a> {
         if shrink {
             PaRattice::shrink_lattice(&mut lattice);
         }
-        let new_lattice = PaRattice::index_left_to_right(&lattice);
-        let mut trunk = HashMap::new();
-        let mut node_id = 0;
-        let mut orig_node_id = 0;
-        trunk.insert(0, 0);
-        while let Some((_, x)) = &new_lattice[node_id].forward_main {
-            node_id = *x;
-            orig_node_id += 1;
-            trunk.insert(node_id, orig_node_id);
-        }
-        Lattice {
-            trunk,
-            capacity: new_lattice.iter().fold(0, |sum, x| sum + x.forwards.len()),
-            lattice: new_lattice,
-        }
+        Lattice::from_nodes(&lattice).0
     }
 
-    fn main_branch_bw(g: &[LatticeNode], begin: usize, eos: usize) -> usize {
+    fn main_branch_bw(g: &[LatticeNode<T>], begin: usize, eos: usize) -> usize {
         let mut b = begin;
         while b > eos {
             b = g[b].backward_main.unwrap().1;
@@ -386,7 +385,7 @@ impl<'a> PaRattice<'a> {
         b
     }
 
-    fn main_branch_fw(g: &[LatticeNode], end: usize, eos: usize) -> usize {
+    fn main_branch_fw(g: &[LatticeNode<T>], end: usize, eos: usize) -> usize {
         let mut e = end;
         while e > eos {
             e = g[e].forward_main.unwrap().1;
@@ -394,7 +393,7 @@ impl<'a> PaRattice<'a> {
         e
     }
 
-    fn shrink_lattice(lattice: &mut Vec<LatticeNode>) {
+    pub(crate) fn shrink_lattice(lattice: &mut Vec<LatticeNode<T>>) {
         let mut updated_node_bw: BTreeSet<usize> = (0..lattice.len()).collect();
         let mut updated_node_fw: BTreeSet<usize> = (0..lattice.len()).collect();
         while !updated_node_bw.is_empty() || !updated_node_fw.is_empty() {
@@ -411,6 +410,9 @@ impl<'a> PaRattice<'a> {
             for nodes in backward_map.values() {
                 if nodes.len() >= 2 {
                     for i in 1..nodes.len() {
+                        // the merged node is reachable with the smallest depth
+                        lattice[nodes[0]].depth =
+                            cmp::min(lattice[nodes[0]].depth, lattice[nodes[i]].depth);
                         let backward_tmp =
                             mem::replace(&mut lattice[nodes[i]].backwards, BTreeSet::new());
                         for (edge_str, prev_node_id) in backward_tmp {
@@ -451,6 +453,9 @@ impl<'a> PaRattice<'a> {
             for nodes in forward_map.values() {
                 if nodes.len() >= 2 {
                     for i in 1..nodes.len() {
+                        // the merged node is reachable with the smallest depth
+                        lattice[nodes[0]].depth =
+                            cmp::min(lattice[nodes[0]].depth, lattice[nodes[i]].depth);
                         let forward_tmp =
                             mem::replace(&mut lattice[nodes[i]].forwards, BTreeSet::new());
                         for (edge_str, next_node_id) in forward_tmp {
@@ -480,47 +485,4 @@ impl<'a> PaRattice<'a> {
             }
         }
     }
-
-    fn index_left_to_right(lattice: &[LatticeNode<'a>]) -> Vec<LatticeNode<'a>> {
-        let mut node_id_map = vec![0; lattice.len()];
-        let mut node_id_map_rev = Vec::with_capacity(lattice.len());
-        let mut queue = VecDeque::new();
-        let mut backward_counter = vec![0; lattice.len()];
-        queue.push_back(0);
-        while let Some(node_id) = queue.pop_front() {
-            node_id_map[node_id] = node_id_map_rev.len();
-            node_id_map_rev.push(node_id);
-            for &(_, edge_target) in &lattice[node_id].forwards {
-                backward_counter[edge_target] += 1;
-                if backward_counter[edge_target] == lattice[edge_target].backwards.len() {
-                    queue.push_back(edge_target);
-                }
-            }
-        }
-        let mut new_lattice = Vec::with_capacity(node_id_map_rev.len());
-        for &node_id in &node_id_map_rev {
-            let mut new_forwards = BTreeSet::new();
-            let mut new_backwards = BTreeSet::new();
-            for &(s, next_node_id) in &lattice[node_id].forwards {
-                new_forwards.insert((s, node_id_map[next_node_id]));
-            }
-            for &(s, prev_node_id) in &lattice[node_id].backwards {
-                new_backwards.insert((s, node_id_map[prev_node_id]));
-            }
-            let forward_main = lattice[node_id]
-                .forward_main
-                .map(|(x, i)| (x, node_id_map[i]));
-            let backward_main = lattice[node_id]
-                .backward_main
-                .map(|(x, i)| (x, node_id_map[i]));
-            new_lattice.push(LatticeNode {
-                forwards: new_forwards,
-                backwards: new_backwards,
-                forward_main,
-                backward_main,
-                depth: 0,
-            });
-        }
-        new_lattice
-    }
 }
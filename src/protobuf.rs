@@ -0,0 +1,243 @@
+use crate::error::DecodeError;
+use crate::lattice;
+use crate::lattice::LatticeNode;
+
+use std::collections::HashMap;
+
+pub use prost::Message;
+
+/// An edge between two nodes.
+#[derive(Clone, PartialEq, Message)]
+pub struct Edge {
+    #[prost(string, tag = "1")]
+    pub label: String,
+    /// The node ID of the other end of the edge
+    #[prost(uint64, tag = "2")]
+    pub target: u64,
+    /// True if the edge is the main edge of its direction
+    #[prost(bool, tag = "3")]
+    pub main: bool,
+}
+
+/// A node of a lattice. Node IDs are indices of `Lattice::nodes`.
+#[derive(Clone, PartialEq, Message)]
+pub struct Node {
+    #[prost(message, repeated, tag = "1")]
+    pub forwards: Vec<Edge>,
+    #[prost(message, repeated, tag = "2")]
+    pub backwards: Vec<Edge>,
+    #[prost(uint64, tag = "3")]
+    pub depth: u64,
+}
+
+/// A message of a paraphrase lattice.
+#[derive(Clone, PartialEq, Message)]
+pub struct Lattice {
+    #[prost(message, repeated, tag = "1")]
+    pub nodes: Vec<Node>,
+    /// Node IDs of the trunk in order
+    #[prost(uint64, repeated, tag = "2")]
+    pub trunk: Vec<u64>,
+    #[prost(map = "string, string", tag = "3")]
+    pub metadata: HashMap<String, String>,
+}
+
+/// A message of a token for the search index.
+#[derive(Clone, PartialEq, Message)]
+pub struct SearchIndexNode {
+    #[prost(string, tag = "1")]
+    pub text: String,
+    #[prost(uint64, tag = "2")]
+    pub offset_start: u64,
+    #[prost(uint64, tag = "3")]
+    pub offset_end: u64,
+    #[prost(uint64, tag = "4")]
+    pub increment: u64,
+    #[prost(uint64, tag = "5")]
+    pub length: u64,
+}
+
+/// A message of tokens of a lattice for the search index.
+#[derive(Clone, PartialEq, Message)]
+pub struct SearchIndex {
+    #[prost(message, repeated, tag = "1")]
+    pub nodes: Vec<SearchIndexNode>,
+    #[prost(map = "string, string", tag = "2")]
+    pub metadata: HashMap<String, String>,
+}
+
+fn to_edges<'a>(
+    edges: impl Iterator<Item = &'a (&'a str, usize)>,
+    main: Option<(&'a str, usize)>,
+) -> Vec<Edge> {
+    edges
+        .map(|&edge| Edge {
+            label: edge.0.to_string(),
+            target: edge.1 as u64,
+            main: Some(edge) == main,
+        })
+        .collect()
+}
+
+// edges and the main edge
+type DecodedEdges<'a> = (Vec<(&'a str, usize)>, Option<(&'a str, usize)>);
+
+/// Returns edges and the main edge, or an error if targets are out of range or two or more
+/// edges are main.
+fn from_edges(
+    edges: &[Edge],
+    node: usize,
+    num_nodes: usize,
+) -> Result<DecodedEdges<'_>, DecodeError> {
+    let mut result = Vec::with_capacity(edges.len());
+    let mut main = None;
+    for edge in edges {
+        if edge.target >= num_nodes as u64 {
+            return Err(DecodeError::NodeOutOfRange {
+                node,
+                target: edge.target as usize,
+            });
+        }
+        let new_edge = (edge.label.as_str(), edge.target as usize);
+        if edge.main {
+            if main.is_some() {
+                return Err(DecodeError::InvalidMainEdge { node });
+            }
+            main = Some(new_edge);
+        }
+        result.push(new_edge);
+    }
+    Ok((result, main))
+}
+
+impl<'a> lattice::Lattice<'a> {
+    /// Returns a Protocol Buffers message of the lattice without metadata.
+    ///
+    /// The schema is `proto/parattice.proto` in the repository.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use parattice::PaRattice;
+    /// use parattice::Lattice;
+    /// use parattice::protobuf;
+    /// use parattice::protobuf::Message;
+    ///
+    /// let paradict = vec![
+    ///     vec![
+    ///         vec!["blood", "stem", "cell"],
+    ///         vec!["造血", "幹", "細胞"],
+    ///         vec!["hematopoietic", "stem", "cell"],
+    ///     ],
+    ///     vec![
+    ///         vec!["造血", "幹", "細胞", "移植"],
+    ///         vec!["hematopoietic", "stem", "cell", "transplantation"],
+    ///     ],
+    ///     vec![vec!["stem", "cell"], vec!["幹", "細胞"]],
+    ///     vec![
+    ///         vec!["幹", "細胞", "移植"],
+    ///         vec!["rescue", "transplant"],
+    ///         vec!["stem", "cell", "rescue"],
+    ///     ],
+    ///     vec![vec!["rescue"], vec!["救命"]],
+    ///     vec![vec!["blood"], vec!["血液"]],
+    /// ];
+    /// let parattice = PaRattice::new(paradict);
+    /// let words = vec!["造血", "幹", "細胞", "移植"];
+    /// let lattice = parattice.get_lattice(&words, true, 2);
+    ///
+    /// let mut message = lattice.to_protobuf();
+    /// message.metadata.insert("document".to_string(), "doc1".to_string());
+    /// let bytes = message.encode_to_vec();
+    ///
+    /// let new_message = protobuf::Lattice::decode(&bytes[..]).unwrap();
+    /// let new_lattice = Lattice::try_from_protobuf(&new_message).unwrap();
+    /// assert!(lattice == new_lattice);
+    /// ```
+    pub fn to_protobuf(&self) -> Lattice {
+        let mut trunk = vec![0; self.trunk.len()];
+        for (&node_id, &position) in &self.trunk {
+            trunk[position] = node_id as u64;
+        }
+        Lattice {
+            nodes: self
+                .lattice
+                .iter()
+                .map(|node| Node {
+                    forwards: to_edges(node.forwards.iter(), node.forward_main),
+                    backwards: to_edges(node.backwards.iter(), node.backward_main),
+                    depth: node.depth as u64,
+                })
+                .collect(),
+            trunk,
+            metadata: HashMap::new(),
+        }
+    }
+
+    /// Returns a lattice of the given Protocol Buffers message. Labels refer to the message.
+    ///
+    /// The trunk is made from forward main edges, and it must be equal to `trunk` of the message
+    /// unless it is empty.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - A message
+    pub fn try_from_protobuf(message: &'a Lattice) -> Result<lattice::Lattice<'a>, DecodeError> {
+        let num_nodes = message.nodes.len();
+        let mut nodes = Vec::with_capacity(num_nodes);
+        for (node_id, node) in message.nodes.iter().enumerate() {
+            let (forwards, forward_main) = from_edges(&node.forwards, node_id, num_nodes)?;
+            let (backwards, backward_main) = from_edges(&node.backwards, node_id, num_nodes)?;
+            let mut new_node = LatticeNode::new(forward_main, backward_main, node.depth as usize);
+            new_node.forwards.extend(forwards);
+            new_node.backwards.extend(backwards);
+            nodes.push(new_node);
+        }
+        let lattice = lattice::Lattice::from_decoded_nodes(nodes)?;
+        if !message.trunk.is_empty()
+            && (message.trunk.len() != lattice.trunk.len()
+                || message
+                    .trunk
+                    .iter()
+                    .enumerate()
+                    .any(|(position, &node_id)| {
+                        lattice.trunk.get(&(node_id as usize)) != Some(&position)
+                    }))
+        {
+            return Err(DecodeError::TrunkMismatch);
+        }
+        Ok(lattice)
+    }
+}
+
+impl<'a> From<&lattice::SearchIndexNode<'a>> for SearchIndexNode {
+    fn from(node: &lattice::SearchIndexNode<'a>) -> SearchIndexNode {
+        SearchIndexNode {
+            text: node.text.to_string(),
+            offset_start: node.offset.0 as u64,
+            offset_end: node.offset.1 as u64,
+            increment: node.increment as u64,
+            length: node.length as u64,
+        }
+    }
+}
+
+impl<'a> From<&'a SearchIndexNode> for lattice::SearchIndexNode<'a> {
+    fn from(node: &'a SearchIndexNode) -> lattice::SearchIndexNode<'a> {
+        lattice::SearchIndexNode {
+            text: &node.text,
+            offset: (node.offset_start as usize, node.offset_end as usize),
+            increment: node.increment as usize,
+            length: node.length as usize,
+        }
+    }
+}
+
+impl<'a> From<&[lattice::SearchIndexNode<'a>]> for SearchIndex {
+    fn from(nodes: &[lattice::SearchIndexNode<'a>]) -> SearchIndex {
+        SearchIndex {
+            nodes: nodes.iter().map(SearchIndexNode::from).collect(),
+            metadata: HashMap::new(),
+        }
+    }
+}
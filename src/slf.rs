@@ -0,0 +1,543 @@
+use crate::error::ParseError;
+use crate::lattice::EdgeWeights;
+use crate::lattice::GenericLattice;
+use crate::lattice::Lattice;
+use crate::lattice::OwnedEdgeWeights;
+use crate::utils::lowest_cost_path;
+use crate::utils::topological_order;
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+
+/// Returns the word as it is, or quoted if it is empty, it contains whitespace, it starts with a
+/// quote, or it is `!NULL`. Backslashes, quotes and line breaks in a quoted word are escaped if
+/// neither quote can enclose it.
+fn quote_word(s: &str) -> Cow<'_, str> {
+    if !s.is_empty()
+        && s != "!NULL"
+        && !s.starts_with(['"', '\''])
+        && !s.contains(char::is_whitespace)
+    {
+        return Cow::Borrowed(s);
+    }
+    let needs_escape = s.contains(['\\', '\n', '\r']);
+    if !needs_escape && !s.contains('"') {
+        return Cow::Owned(format!("\"{}\"", s));
+    }
+    if !needs_escape && !s.contains('\'') {
+        return Cow::Owned(format!("'{}'", s));
+    }
+    let mut result = "\"".to_string();
+    for c in s.chars() {
+        match c {
+            '\\' => result += "\\\\",
+            '"' => result += "\\\"",
+            '\n' => result += "\\012",
+            '\r' => result += "\\015",
+            _ => result.push(c),
+        }
+    }
+    result.push('"');
+    Cow::Owned(result)
+}
+
+/// Returns the quoted word without escapes. A backslash escapes the next character, and a
+/// backslash followed by three octal digits is a byte of UTF-8.
+fn unescape_word(s: &str, line_number: usize) -> Result<Cow<'_, str>, ParseError> {
+    if !s.contains('\\') {
+        return Ok(Cow::Borrowed(s));
+    }
+    let mut bytes = vec![];
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        let c = if c == '\\' {
+            let rest = chars.as_str();
+            match rest.get(..3) {
+                Some(digits) if digits.bytes().all(|b| (b'0'..=b'7').contains(&b)) => {
+                    let byte = u8::from_str_radix(digits, 8).map_err(|_| {
+                        ParseError::new(line_number, "an octal escape out of range")
+                    })?;
+                    bytes.push(byte);
+                    chars = rest[3..].chars();
+                    continue;
+                }
+                _ => chars.next().ok_or_else(|| {
+                    ParseError::new(line_number, "a backslash at the end of a word")
+                })?,
+            }
+        } else {
+            c
+        };
+        bytes.extend(c.encode_utf8(&mut [0; 4]).as_bytes());
+    }
+    String::from_utf8(bytes)
+        .map(Cow::Owned)
+        .map_err(|_| ParseError::new(line_number, "an escaped word is not valid UTF-8"))
+}
+
+/// Returns pairs of the key and the value of fields of the line, and whether each value is
+/// quoted. Quoted values are returned with escapes.
+fn split_fields(line: &str, line_number: usize) -> Result<Vec<(&str, &str, bool)>, ParseError> {
+    let mut fields = vec![];
+    let mut rest = line.trim_start();
+    while !rest.is_empty() {
+        let field_end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        let key_end = rest[..field_end]
+            .find('=')
+            .ok_or_else(|| ParseError::new(line_number, "a field without a value"))?;
+        let key = &rest[..key_end];
+        rest = &rest[key_end + 1..];
+        match rest.chars().next() {
+            Some(quote) if quote == '"' || quote == '\'' => {
+                let mut chars = rest.char_indices().skip(1);
+                let mut value_end = None;
+                while let Some((i, c)) = chars.next() {
+                    if c == '\\' {
+                        chars.next();
+                    } else if c == quote {
+                        value_end = Some(i);
+                        break;
+                    }
+                }
+                let value_end = value_end
+                    .ok_or_else(|| ParseError::new(line_number, "an unterminated quote"))?;
+                fields.push((key, &rest[1..value_end], true));
+                rest = &rest[value_end + 1..];
+                if rest.starts_with(|c: char| !c.is_whitespace()) {
+                    return Err(ParseError::new(line_number, "a field after a quote"));
+                }
+            }
+            _ => {
+                let value_end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+                fields.push((key, &rest[..value_end], false));
+                rest = &rest[value_end..];
+            }
+        }
+        rest = rest.trim_start();
+    }
+    Ok(fields)
+}
+
+impl<'a> Lattice<'a> {
+    /// Returns the lattice in HTK Standard Lattice Format (SLF).
+    ///
+    /// Words are written on links, and node IDs are the same as the lattice. Words are quoted if
+    /// they contain whitespace or they would be read as `!NULL`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use parattice::PaRattice;
+    ///
+    /// let paradict = vec![
+    ///     vec![
+    ///         vec!["blood", "stem", "cell"],
+    ///         vec!["造血", "幹", "細胞"],
+    ///         vec!["hematopoietic", "stem", "cell"],
+    ///     ],
+    ///     vec![
+    ///         vec!["造血", "幹", "細胞", "移植"],
+    ///         vec!["hematopoietic", "stem", "cell", "transplantation"],
+    ///     ],
+    ///     vec![vec!["stem", "cell"], vec!["幹", "細胞"]],
+    ///     vec![
+    ///         vec!["幹", "細胞", "移植"],
+    ///         vec!["rescue", "transplant"],
+    ///         vec!["stem", "cell", "rescue"],
+    ///     ],
+    ///     vec![vec!["rescue"], vec!["救命"]],
+    ///     vec![vec!["blood"], vec!["血液"]],
+    /// ];
+    /// let parattice = PaRattice::new(paradict);
+    /// let words = vec!["造血", "幹", "細胞", "移植"];
+    /// let lattice = parattice.get_lattice(&words, true, 2);
+    ///
+    /// let slf = lattice.to_slf();
+    /// println!("{}", slf);
+    /// ```
+    pub fn to_slf(&self) -> String {
+        self.to_slf_impl(None::<fn(&str, usize, usize) -> f64>)
+    }
+
+    /// Returns the lattice in HTK Standard Lattice Format (SLF) with language model scores.
+    ///
+    /// # Arguments
+    ///
+    /// * `score` - A language model score (`l=`) of an edge given its label, source and target
+    ///   node IDs
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use parattice::PaRattice;
+    ///
+    /// let paradict = vec![
+    ///     vec![
+    ///         vec!["blood", "stem", "cell"],
+    ///         vec!["造血", "幹", "細胞"],
+    ///         vec!["hematopoietic", "stem", "cell"],
+    ///     ],
+    ///     vec![
+    ///         vec!["造血", "幹", "細胞", "移植"],
+    ///         vec!["hematopoietic", "stem", "cell", "transplantation"],
+    ///     ],
+    ///     vec![vec!["stem", "cell"], vec!["幹", "細胞"]],
+    ///     vec![
+    ///         vec!["幹", "細胞", "移植"],
+    ///         vec!["rescue", "transplant"],
+    ///         vec!["stem", "cell", "rescue"],
+    ///     ],
+    ///     vec![vec!["rescue"], vec!["救命"]],
+    ///     vec![vec!["blood"], vec!["血液"]],
+    /// ];
+    /// let parattice = PaRattice::new(paradict);
+    /// let words = vec!["造血", "幹", "細胞", "移植"];
+    /// let lattice = parattice.get_lattice(&words, true, 2);
+    ///
+    /// // penalize edges out of the trunk
+    /// let slf = lattice.to_slf_weighted(|_, i, j| {
+    ///     if lattice.trunk.contains_key(&i) && lattice.trunk.contains_key(&j) {
+    ///         0.0
+    ///     } else {
+    ///         -1.0
+    ///     }
+    /// });
+    /// ```
+    pub fn to_slf_weighted<F>(&self, score: F) -> String
+    where
+        F: Fn(&str, usize, usize) -> f64,
+    {
+        self.to_slf_impl(Some(score))
+    }
+
+    fn to_slf_impl<F>(&self, score: Option<F>) -> String
+    where
+        F: Fn(&str, usize, usize) -> f64,
+    {
+        let mut result = format!(
+            "VERSION=1.0\nstart=0 end={}\nN={} L={}\n",
+            self.lattice.len() - 1,
+            self.lattice.len(),
+            self.capacity
+        );
+        for i in 0..self.lattice.len() {
+            result += &format!("I={}\n", i);
+        }
+        let mut link_id = 0;
+        for (i, node) in self.lattice.iter().enumerate() {
+            for &(s, target) in &node.forwards {
+                result += &format!("J={} S={} E={} W={}", link_id, i, target, quote_word(s));
+                if let Some(score) = &score {
+                    result += &format!(" l={}", score(s, i, target));
+                }
+                result += "\n";
+                link_id += 1;
+            }
+        }
+        result
+    }
+
+    /// Returns a lattice and its edge scores from HTK Standard Lattice Format (SLF).
+    ///
+    /// Words are taken from links, or from end nodes of links that have no words. The score of
+    /// an edge is the sum of its acoustic (`a=`) and language model (`l=`) scores, and the path
+    /// of the highest total score becomes the trunk. The start and end nodes are given by
+    /// `start=` and `end=` in the header, or otherwise the nodes without incoming and outgoing
+    /// links. Links of `!NULL` words are removed, and words before and after them are connected
+    /// with the sum of their scores. Words may be quoted, but escaped words in quotes can't be
+    /// borrowed from the input. Use `GenericLattice::<String>::from_slf_owned()` for them.
+    ///
+    /// # Arguments
+    ///
+    /// * `slf` - A lattice in SLF
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use parattice::Lattice;
+    ///
+    /// let slf = "VERSION=1.0\n\
+    ///            N=3 L=3\n\
+    ///            I=0\n\
+    ///            I=1\n\
+    ///            I=2 W=cell\n\
+    ///            J=0 S=0 E=1 W=blood l=-1.5\n\
+    ///            J=1 S=0 E=1 W=血液 l=-0.5\n\
+    ///            J=2 S=1 E=2\n";
+    ///
+    /// let (lattice, scores) = Lattice::from_slf(slf).unwrap();
+    /// assert_eq!(Some(("血液", 1)), lattice.lattice[0].forward_main);
+    /// ```
+    pub fn from_slf(slf: &'a str) -> Result<(Lattice<'a>, EdgeWeights<'a>), ParseError> {
+        let links = parse_slf(slf)?;
+        let mut words = Vec::with_capacity(links.links.len());
+        for ((_, word, _), &line_number) in links.links.iter().zip(&links.line_numbers) {
+            words.push(match word {
+                Some(Cow::Borrowed(word)) => Some(*word),
+                Some(Cow::Owned(_)) => {
+                    return Err(ParseError::new(
+                        line_number,
+                        "escaped words need owned labels",
+                    ))
+                }
+                None => None,
+            });
+        }
+        lattice_from_links(&links, &words)
+    }
+}
+
+impl GenericLattice<String> {
+    /// Returns a lattice with owned labels and its edge scores from HTK Standard Lattice Format
+    /// (SLF). Escaped words in quotes are unescaped. See `Lattice::from_slf()` for the details.
+    ///
+    /// # Arguments
+    ///
+    /// * `slf` - A lattice in SLF
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use parattice::lattice::GenericLattice;
+    ///
+    /// let slf = "N=2 L=1\nJ=0 S=0 E=1 W=\"say \\\"hi\\\"\"\n";
+    ///
+    /// let (lattice, scores) = GenericLattice::from_slf_owned(slf).unwrap();
+    /// assert_eq!(Some(("say \"hi\"".to_string(), 1)), lattice.lattice[0].forward_main);
+    /// ```
+    pub fn from_slf_owned(
+        slf: &str,
+    ) -> Result<(GenericLattice<String>, OwnedEdgeWeights), ParseError> {
+        let links = parse_slf(slf)?;
+        let words: Vec<_> = links.links.iter().map(|link| link.1.as_deref()).collect();
+        let (lattice, scores) = lattice_from_links(&links, &words)?;
+        let scores = scores
+            .into_iter()
+            .map(|((start, s, end), score)| ((start, s.to_string(), end), score))
+            .collect();
+        Ok((lattice.to_owned_lattice(), scores))
+    }
+}
+
+/// Links of a lattice in SLF. Words are `None` for `!NULL`.
+struct SlfLinks<'a> {
+    num_nodes: usize,
+    start_node_id: usize,
+    end_node_id: usize,
+    links: Vec<(usize, Option<Cow<'a, str>>, usize)>,
+    scores: Vec<f64>,
+    line_numbers: Vec<usize>,
+}
+
+/// Returns links of the lattice in SLF after checking nodes of the links, cycles and the start
+/// and end nodes.
+fn parse_slf(slf: &str) -> Result<SlfLinks<'_>, ParseError> {
+    let mut num_nodes = None;
+    let mut start_node_id = None;
+    let mut end_node_id = None;
+    let mut node_words = HashMap::new();
+    let mut links = vec![];
+    let mut scores = vec![];
+    let mut line_numbers = vec![];
+    let parse_node_id = |value: &str, line: usize, num_nodes: Option<usize>| {
+        let node_id: usize = value
+            .parse()
+            .map_err(|_| ParseError::new(line, "invalid node ID"))?;
+        match num_nodes {
+            Some(num_nodes) if node_id < num_nodes => Ok(node_id),
+            Some(_) => Err(ParseError::new(line, "node ID out of range")),
+            None => Err(ParseError::new(
+                line,
+                "N is not given before nodes and links",
+            )),
+        }
+    };
+    for (i, line) in slf.lines().enumerate() {
+        let line_number = i + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut fields = HashMap::new();
+        let mut word = None;
+        for (key, value, quoted) in split_fields(line, line_number)? {
+            if key == "W" || key == "WORD" {
+                word = Some(if quoted {
+                    Some(unescape_word(value, line_number)?)
+                } else if value == "!NULL" {
+                    None
+                } else {
+                    Some(Cow::Borrowed(value))
+                });
+            }
+            fields.insert(key, value);
+        }
+        if let Some(value) = fields.get("I") {
+            let node_id = parse_node_id(value, line_number, num_nodes)?;
+            if let Some(word) = word {
+                node_words.insert(node_id, word);
+            }
+        } else if fields.contains_key("J") {
+            let start = fields.get("S").or_else(|| fields.get("START"));
+            let end = fields.get("E").or_else(|| fields.get("END"));
+            let (start, end) = match (start, end) {
+                (Some(start), Some(end)) => (
+                    parse_node_id(start, line_number, num_nodes)?,
+                    parse_node_id(end, line_number, num_nodes)?,
+                ),
+                _ => return Err(ParseError::new(line_number, "S or E is missing")),
+            };
+            let mut score = 0.0;
+            for key in &["a", "acoustic", "l", "language"] {
+                if let Some(value) = fields.get(key) {
+                    score += value
+                        .parse::<f64>()
+                        .map_err(|_| ParseError::new(line_number, "invalid score"))?;
+                }
+            }
+            links.push((start, word, end));
+            scores.push(score);
+            line_numbers.push(line_number);
+        } else {
+            if let Some(value) = fields.get("N").or_else(|| fields.get("NODES")) {
+                let value = value
+                    .parse()
+                    .map_err(|_| ParseError::new(line_number, "invalid N"))?;
+                // nodes are allocated by N, so it is limited by the input size
+                if value > slf.len() {
+                    return Err(ParseError::new(line_number, "N is too large"));
+                }
+                num_nodes = Some(value);
+            }
+            // start and end may precede N in the header
+            if let Some(&value) = fields.get("start") {
+                start_node_id = Some((value, line_number));
+            }
+            if let Some(&value) = fields.get("end") {
+                end_node_id = Some((value, line_number));
+            }
+        }
+    }
+    let num_nodes = num_nodes.ok_or_else(|| ParseError::new(0, "N is not given"))?;
+    let mut new_links = Vec::with_capacity(links.len());
+    for ((start, word, end), &line_number) in links.into_iter().zip(&line_numbers) {
+        let word = word
+            .or_else(|| node_words.get(&end).cloned())
+            .ok_or_else(|| ParseError::new(line_number, "a link without a word"))?;
+        new_links.push((start, word, end));
+    }
+    let links = new_links;
+    if topological_order(num_nodes, &links).is_none() {
+        return Err(ParseError::new(0, "cycles are not supported"));
+    }
+    let mut has_backwards = vec![false; num_nodes];
+    let mut has_forwards = vec![false; num_nodes];
+    for &(start, _, end) in &links {
+        has_forwards[start] = true;
+        has_backwards[end] = true;
+    }
+    let find_unique_node = |has_edges: &[bool], name: &str| {
+        let mut node_ids = (0..num_nodes).filter(|&i| !has_edges[i]);
+        match (node_ids.next(), node_ids.next()) {
+            (Some(node_id), None) => Ok(node_id),
+            _ => Err(ParseError::new(
+                0,
+                format!("the {} node is not unique", name),
+            )),
+        }
+    };
+    let start_node_id = match start_node_id {
+        Some((value, line_number)) => parse_node_id(value, line_number, Some(num_nodes))?,
+        None => find_unique_node(&has_backwards, "start")?,
+    };
+    let end_node_id = match end_node_id {
+        Some((value, line_number)) => parse_node_id(value, line_number, Some(num_nodes))?,
+        None => find_unique_node(&has_forwards, "end")?,
+    };
+    Ok(SlfLinks {
+        num_nodes,
+        start_node_id,
+        end_node_id,
+        links,
+        scores,
+        line_numbers,
+    })
+}
+
+/// Returns a lattice and its edge scores from the links with the given words, where `None` is
+/// `!NULL`.
+fn lattice_from_links<'b>(
+    links: &SlfLinks,
+    words: &[Option<&'b str>],
+) -> Result<(Lattice<'b>, EdgeWeights<'b>), ParseError> {
+    let num_nodes = links.num_nodes;
+    // the best scores of nodes reachable only through !NULL links, including the node itself
+    let mut null_forwards = vec![vec![]; num_nodes];
+    for (link_id, &(start, _, _)) in links.links.iter().enumerate() {
+        if words[link_id].is_none() {
+            null_forwards[start].push(link_id);
+        }
+    }
+    let mut closures = vec![BTreeMap::new(); num_nodes];
+    // links are acyclic, which is checked by parse_slf()
+    for &node_id in topological_order(num_nodes, &links.links)
+        .unwrap()
+        .iter()
+        .rev()
+    {
+        let mut closure = BTreeMap::new();
+        closure.insert(node_id, 0.0);
+        for &link_id in &null_forwards[node_id] {
+            for (&target, &score) in &closures[links.links[link_id].2] {
+                let score = links.scores[link_id] + score;
+                let best_score = closure.entry(target).or_insert(score);
+                *best_score = f64::max(*best_score, score);
+            }
+        }
+        closures[node_id] = closure;
+    }
+    let mut edges = vec![];
+    let mut scores = vec![];
+    for (link_id, &(start, _, end)) in links.links.iter().enumerate() {
+        let word = match words[link_id] {
+            Some(word) => word,
+            None => continue,
+        };
+        // words after !NULL links from the start node also start from the start node
+        let mut sources = vec![(start, 0.0)];
+        if start != links.start_node_id {
+            if let Some(&score) = closures[links.start_node_id].get(&start) {
+                sources.push((links.start_node_id, score));
+            }
+        }
+        for &(source, source_score) in &sources {
+            for (&target, &target_score) in &closures[end] {
+                edges.push((source, word, target));
+                scores.push(source_score + links.scores[link_id] + target_score);
+            }
+        }
+    }
+    let costs: Vec<f64> = scores.iter().map(|score| -score).collect();
+    let trunk_edges = lowest_cost_path(
+        num_nodes,
+        &edges,
+        &costs,
+        links.start_node_id,
+        links.end_node_id,
+    )
+    .ok_or_else(|| ParseError::new(0, "the end node is not reachable"))?;
+    let mut trunk = vec![("", links.start_node_id)];
+    for edge_id in trunk_edges {
+        trunk.push((edges[edge_id].1, edges[edge_id].2));
+    }
+    let (lattice, node_id_map) = Lattice::from_edges(num_nodes, &edges, &trunk);
+    let mut edge_scores = EdgeWeights::new();
+    for (&(start, s, end), &score) in edges.iter().zip(&scores) {
+        if let (Some(start), Some(end)) = (node_id_map[start], node_id_map[end]) {
+            let w = edge_scores.entry((start, s, end)).or_insert(score);
+            *w = w.max(score);
+        }
+    }
+    Ok((lattice, edge_scores))
+}
@@ -0,0 +1,327 @@
+use crate::lattice::Lattice;
+use crate::utils::escape_xml;
+
+use std::cmp;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+const MARGIN: f64 = 20.0;
+const NODE_RADIUS: f64 = 4.0;
+
+/// Options of `Lattice::dump_svg`.
+pub struct SvgOptions<'a> {
+    /// If true, node numbers are printed
+    pub is_numbered: bool,
+    /// A font size of labels
+    pub font_size: f64,
+    /// Paths to be highlighted (e.g. results of LatticeKMP)
+    pub highlighted_paths: Vec<Vec<(&'a str, usize)>>,
+    /// A color of highlighted edges
+    pub highlight_color: String,
+    /// If true, edges have tooltips that show their node IDs, main edge flags and trunk spans
+    pub tooltips: bool,
+}
+
+impl<'a> Default for SvgOptions<'a> {
+    fn default() -> SvgOptions<'a> {
+        SvgOptions {
+            is_numbered: false,
+            font_size: 14.0,
+            highlighted_paths: vec![],
+            highlight_color: "#ff0000".to_string(),
+            tooltips: true,
+        }
+    }
+}
+
+/// Returns an approximate width of a text, treating CJK characters as full-width.
+fn text_width(s: &str, font_size: f64) -> f64 {
+    s.chars()
+        .map(|c| {
+            if c >= '\u{1100}' {
+                font_size
+            } else {
+                font_size * 0.6
+            }
+        })
+        .sum()
+}
+
+fn find_root(parents: &mut [usize], i: usize) -> usize {
+    let mut root = i;
+    while parents[root] != root {
+        root = parents[root];
+    }
+    let mut i = i;
+    while parents[i] != root {
+        let next = parents[i];
+        parents[i] = root;
+        i = next;
+    }
+    root
+}
+
+impl<'a> Lattice<'a> {
+    /// Returns rows and columns of nodes.
+    ///
+    /// Columns are the lengths of the longest paths from the first node. Trunk nodes are placed
+    /// on the row 0, and each group of connected branch nodes is stacked above (positive rows)
+    /// or below (negative rows) the trunk without overlapping other groups.
+    fn svg_layout(&self) -> (Vec<i64>, Vec<usize>) {
+        let num_nodes = self.lattice.len();
+        let mut columns = vec![0; num_nodes];
+        for (i, node) in self.lattice.iter().enumerate() {
+            for &(_, j) in &node.forwards {
+                columns[j] = cmp::max(columns[j], columns[i] + 1);
+            }
+        }
+        // groups of branch nodes
+        let mut parents: Vec<usize> = (0..num_nodes).collect();
+        for (i, node) in self.lattice.iter().enumerate() {
+            if self.trunk.contains_key(&i) {
+                continue;
+            }
+            for &(_, j) in &node.forwards {
+                if !self.trunk.contains_key(&j) {
+                    let root_i = find_root(&mut parents, i);
+                    let root_j = find_root(&mut parents, j);
+                    parents[cmp::max(root_i, root_j)] = cmp::min(root_i, root_j);
+                }
+            }
+        }
+        let mut groups: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+        for i in 0..num_nodes {
+            if !self.trunk.contains_key(&i) {
+                let root = find_root(&mut parents, i);
+                groups.entry(root).or_default().push(i);
+            }
+        }
+        let mut rows = vec![0; num_nodes];
+        let mut occupied: HashMap<i64, Vec<(usize, usize)>> = HashMap::new();
+        for node_ids in groups.values() {
+            // the range of columns including trunk nodes connected to the group
+            let mut left = usize::MAX;
+            let mut right = 0;
+            let mut sub_rows = HashMap::new();
+            let mut height = 0;
+            for &i in node_ids {
+                let node = &self.lattice[i];
+                for &(_, j) in node.backwards.iter().chain(node.forwards.iter()) {
+                    left = cmp::min(left, columns[j]);
+                    right = cmp::max(right, columns[j]);
+                }
+                let sub_row = sub_rows.entry(columns[i]).or_insert(0);
+                *sub_row += 1;
+                height = cmp::max(height, *sub_row);
+            }
+            let is_free = |occupied: &HashMap<i64, Vec<(usize, usize)>>, row: i64| {
+                occupied
+                    .get(&row)
+                    .is_none_or(|intervals| intervals.iter().all(|&(l, r)| r <= left || right <= l))
+            };
+            let mut offset = 1;
+            let sign = loop {
+                if (offset..offset + height).all(|r| is_free(&occupied, r)) {
+                    break 1;
+                }
+                if (offset..offset + height).all(|r| is_free(&occupied, -r)) {
+                    break -1;
+                }
+                offset += 1;
+            };
+            for r in offset..offset + height {
+                occupied.entry(sign * r).or_default().push((left, right));
+            }
+            let mut sub_rows = HashMap::new();
+            for &i in node_ids {
+                let sub_row = sub_rows.entry(columns[i]).or_insert(0);
+                rows[i] = sign * (offset + *sub_row);
+                *sub_row += 1;
+            }
+        }
+        (rows, columns)
+    }
+
+    /// Returns a standalone SVG string of the lattice.
+    ///
+    /// The trunk is drawn on a straight baseline, and branches are stacked above and below it.
+    /// Graphviz is not required.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - Options of rendering
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use parattice::svg::SvgOptions;
+    /// use parattice::LatticeKMP;
+    /// use parattice::PaRattice;
+    ///
+    /// let paradict = vec![
+    ///     vec![
+    ///         vec!["blood", "stem", "cell"],
+    ///         vec!["造血", "幹", "細胞"],
+    ///         vec!["hematopoietic", "stem", "cell"],
+    ///     ],
+    ///     vec![
+    ///         vec!["造血", "幹", "細胞", "移植"],
+    ///         vec!["hematopoietic", "stem", "cell", "transplantation"],
+    ///     ],
+    ///     vec![vec!["stem", "cell"], vec!["幹", "細胞"]],
+    ///     vec![
+    ///         vec!["幹", "細胞", "移植"],
+    ///         vec!["rescue", "transplant"],
+    ///         vec!["stem", "cell", "rescue"],
+    ///     ],
+    ///     vec![vec!["rescue"], vec!["救命"]],
+    ///     vec![vec!["blood"], vec!["血液"]],
+    /// ];
+    /// let parattice = PaRattice::new(paradict);
+    /// let words = vec!["造血", "幹", "細胞", "移植"];
+    /// let lattice = parattice.get_lattice(&words, true, 2);
+    ///
+    /// let kmp = LatticeKMP::new(vec!["幹", "細胞"]);
+    /// let options = SvgOptions {
+    ///     highlighted_paths: kmp.search(&lattice),
+    ///     ..SvgOptions::default()
+    /// };
+    /// let svg = lattice.dump_svg(&options);
+    /// println!("{}", svg);
+    /// ```
+    pub fn dump_svg(&self, options: &SvgOptions) -> String {
+        let font_size = options.font_size;
+        let (rows, columns) = self.svg_layout();
+        let max_label_width = self
+            .lattice
+            .iter()
+            .flat_map(|node| node.forwards.iter())
+            .map(|&(s, _)| text_width(s, font_size))
+            .fold(0.0, f64::max);
+        let column_width = max_label_width + font_size * 2.0;
+        let row_height = font_size * 3.0;
+        let max_row = rows.iter().cloned().max().unwrap_or(0);
+        let min_row = rows.iter().cloned().min().unwrap_or(0);
+        let max_column = columns.iter().cloned().max().unwrap_or(0);
+        let x = |i: usize| MARGIN + columns[i] as f64 * column_width;
+        let y = |i: usize| MARGIN + font_size + (max_row - rows[i]) as f64 * row_height;
+        let width = MARGIN * 2.0 + max_column as f64 * column_width;
+        let height = MARGIN * 2.0 + font_size * 2.0 + (max_row - min_row) as f64 * row_height;
+
+        let mut highlighted_edges = HashSet::new();
+        for path in &options.highlighted_paths {
+            for i in 1..path.len() {
+                highlighted_edges.insert((path[i - 1].1, path[i].0, path[i].1));
+            }
+        }
+        let trunk_spans = self.get_trunk_spans();
+
+        let mut result = format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0:.1}\" height=\"{1:.1}\" \
+             viewBox=\"0 0 {0:.1} {1:.1}\" font-family=\"sans-serif\" font-size=\"{2}\">\n\
+             <rect width=\"100%\" height=\"100%\" fill=\"white\"/>\n",
+            width, height, font_size
+        );
+        for (i, node) in self.lattice.iter().enumerate() {
+            // parallel edges are separated by their curvatures
+            let mut num_parallels = HashMap::new();
+            for &(s, j) in &node.forwards {
+                let parallel_index = num_parallels.entry(j).or_insert(0);
+                let (x1, y1, x2, y2) = (x(i), y(i), x(j), y(j));
+                let span = columns[j] - columns[i];
+                let mut bulge = if rows[i] == rows[j] && span > 1 {
+                    row_height * 0.5 * cmp::min(span - 1, 3) as f64
+                } else {
+                    0.0
+                };
+                bulge += font_size * 1.5 * ((*parallel_index + 1) / 2) as f64;
+                if rows[i] < 0 || *parallel_index % 2 == 1 {
+                    bulge = -bulge;
+                }
+                *parallel_index += 1;
+                let (path, label_x, label_y) = if bulge != 0.0 {
+                    let (cx, cy) = ((x1 + x2) / 2.0, (y1 + y2) / 2.0 - bulge);
+                    (
+                        format!(
+                            "M {:.1} {:.1} Q {:.1} {:.1} {:.1} {:.1}",
+                            x1, y1, cx, cy, x2, y2
+                        ),
+                        cx,
+                        (y1 + y2) / 4.0 + cy / 2.0,
+                    )
+                } else {
+                    let xm = (x1 + x2) / 2.0;
+                    (
+                        format!(
+                            "M {:.1} {:.1} C {:.1} {:.1} {:.1} {:.1} {:.1} {:.1}",
+                            x1, y1, xm, y1, xm, y2, x2, y2
+                        ),
+                        xm,
+                        (y1 + y2) / 2.0,
+                    )
+                };
+                let is_forward_main = node.forward_main == Some((s, j));
+                let is_backward_main = self.lattice[j].backward_main == Some((s, i));
+                let is_trunk = is_forward_main && rows[i] == 0 && rows[j] == 0;
+                let (color, stroke_width) = if highlighted_edges.contains(&(i, s, j)) {
+                    (options.highlight_color.as_str(), 2.5)
+                } else if is_trunk {
+                    ("#000000", 2.0)
+                } else {
+                    ("#808080", 1.0)
+                };
+                result += "<g class=\"edge\">";
+                if options.tooltips {
+                    result += &format!(
+                        "<title>{}: {} -&gt; {}\nforward main: {}\nbackward main: {}\n\
+                         trunk span: {}-{}</title>",
+                        escape_xml(s),
+                        i,
+                        j,
+                        is_forward_main,
+                        is_backward_main,
+                        trunk_spans[i].0,
+                        trunk_spans[j].1
+                    );
+                }
+                result += &format!(
+                    "<path d=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"{}\"/>\
+                     <text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"middle\" fill=\"{}\" \
+                     stroke=\"white\" stroke-width=\"3\" paint-order=\"stroke\">{}</text></g>\n",
+                    path,
+                    color,
+                    stroke_width,
+                    label_x,
+                    label_y - font_size * 0.3,
+                    color,
+                    escape_xml(s)
+                );
+            }
+        }
+        for (i, &row) in rows.iter().enumerate() {
+            result += &format!(
+                "<g class=\"node\"><title>{}</title><circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"{}\" \
+                 fill=\"{}\"/>",
+                i,
+                x(i),
+                y(i),
+                NODE_RADIUS,
+                if row == 0 { "#000000" } else { "#808080" }
+            );
+            if options.is_numbered {
+                result += &format!(
+                    "<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"middle\" font-size=\"{}\">{}</text>",
+                    x(i),
+                    y(i) + font_size * 1.2,
+                    font_size * 0.7,
+                    i
+                );
+            }
+            result += "</g>\n";
+        }
+        result += "</svg>\n";
+        result
+    }
+}
@@ -6,3 +6,105 @@ pub fn get_two_mut_elems<'a, T>(x: &'a mut Vec<T>, i: usize, j: usize) -> (&'a m
     let ptr = x.as_mut_ptr();
     unsafe { (ptr.add(i).as_mut().unwrap(), ptr.add(j).as_mut().unwrap()) }
 }
+
+/// Returns node IDs of the given edges `(source, label, target)` in topological order, or None
+/// if the edges have a cycle.
+pub(crate) fn topological_order<T>(
+    num_nodes: usize,
+    edges: &[(usize, T, usize)],
+) -> Option<Vec<usize>> {
+    let mut forwards = vec![vec![]; num_nodes];
+    let mut num_backwards = vec![0; num_nodes];
+    for &(source, _, target) in edges {
+        forwards[source].push(target);
+        num_backwards[target] += 1;
+    }
+    let mut order: Vec<usize> = (0..num_nodes).filter(|&i| num_backwards[i] == 0).collect();
+    let mut i = 0;
+    while i < order.len() {
+        for &target in &forwards[order[i]] {
+            num_backwards[target] -= 1;
+            if num_backwards[target] == 0 {
+                order.push(target);
+            }
+        }
+        i += 1;
+    }
+    if order.len() == num_nodes {
+        Some(order)
+    } else {
+        None
+    }
+}
+
+/// Returns the edge IDs of the lowest-cost path from `first_node_id` to `last_node_id`, or None
+/// if there is no such path. The edges must not have a cycle.
+pub(crate) fn lowest_cost_path<T>(
+    num_nodes: usize,
+    edges: &[(usize, T, usize)],
+    costs: &[f64],
+    first_node_id: usize,
+    last_node_id: usize,
+) -> Option<Vec<usize>> {
+    let mut forwards = vec![vec![]; num_nodes];
+    for (edge_id, &(source, _, _)) in edges.iter().enumerate() {
+        forwards[source].push(edge_id);
+    }
+    let mut best_costs = vec![None; num_nodes];
+    let mut best_edges = vec![None; num_nodes];
+    best_costs[last_node_id] = Some(0.0);
+    for &node_id in topological_order(num_nodes, edges)?.iter().rev() {
+        if node_id == last_node_id {
+            continue;
+        }
+        for &edge_id in &forwards[node_id] {
+            if let Some(target_cost) = best_costs[edges[edge_id].2] {
+                let cost = costs[edge_id] + target_cost;
+                if best_costs[node_id].is_none_or(|best_cost| cost < best_cost) {
+                    best_costs[node_id] = Some(cost);
+                    best_edges[node_id] = Some(edge_id);
+                }
+            }
+        }
+    }
+    best_costs[first_node_id]?;
+    let mut path = vec![];
+    let mut node_id = first_node_id;
+    while let Some(edge_id) = best_edges[node_id] {
+        path.push(edge_id);
+        node_id = edges[edge_id].2;
+    }
+    Some(path)
+}
+
+/// Returns the string with XML special characters escaped.
+pub(crate) fn escape_xml(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => result += "&amp;",
+            '<' => result += "&lt;",
+            '>' => result += "&gt;",
+            '"' => result += "&quot;",
+            '\'' => result += "&apos;",
+            _ => result.push(c),
+        }
+    }
+    result
+}
+
+/// Returns the CRC-32 (IEEE 802.3) checksum of the given data.
+pub(crate) fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
@@ -0,0 +1,125 @@
+#![cfg(feature = "arrow")]
+
+extern crate parattice;
+
+mod common;
+
+use common::get_paradict;
+
+use parattice::arrow::search_index_schema;
+use parattice::arrow::SearchIndexBatchBuilder;
+use parattice::PaRattice;
+
+use arrow_array::cast::AsArray;
+use arrow_array::types::UInt32Type;
+use arrow_array::types::UInt64Type;
+use arrow_array::Array;
+use arrow_array::RecordBatch;
+
+// rows of (document, text, offset_start, offset_end, increment, length)
+fn get_rows(batch: &RecordBatch) -> Vec<(String, String, u64, u64, u64, u64)> {
+    let document = batch.column(0).as_dictionary::<UInt32Type>();
+    let document_values = document.values().as_string::<i32>();
+    let text = batch.column(1).as_string::<i32>();
+    let u64_column = |i: usize| batch.column(i).as_primitive::<UInt64Type>();
+    (0..batch.num_rows())
+        .map(|i| {
+            (
+                document_values
+                    .value(document.keys().value(i) as usize)
+                    .to_string(),
+                text.value(i).to_string(),
+                u64_column(2).value(i),
+                u64_column(3).value(i),
+                u64_column(4).value(i),
+                u64_column(5).value(i),
+            )
+        })
+        .collect()
+}
+
+fn get_expected_rows(
+    parattice: &PaRattice,
+    keys: &[(&str, Vec<&str>)],
+) -> Vec<(String, String, u64, u64, u64, u64)> {
+    let mut rows = vec![];
+    for (document, words) in keys {
+        let lattice = parattice.get_lattice(words, true, 2);
+        for node in lattice.dump_for_search_index() {
+            rows.push((
+                document.to_string(),
+                node.text.to_string(),
+                node.offset.0 as u64,
+                node.offset.1 as u64,
+                node.increment as u64,
+                node.length as u64,
+            ));
+        }
+    }
+    rows
+}
+
+#[test]
+fn arrow_batch_test() {
+    let parattice = PaRattice::new(get_paradict());
+    let keys = [
+        ("doc1", vec!["造血", "幹", "細胞", "移植"]),
+        ("doc1", vec!["blood", "stem", "cell"]),
+        ("doc2", vec!["幹", "細胞"]),
+    ];
+
+    let mut builder = SearchIndexBatchBuilder::new();
+    assert!(builder.is_empty());
+    for (document, words) in &keys {
+        let lattice = parattice.get_lattice(words, true, 2);
+        builder.push(document, &lattice);
+    }
+    let expected = get_expected_rows(&parattice, &keys);
+    assert_eq!(expected.len(), builder.len());
+
+    let batch = builder.finish();
+    assert_eq!(search_index_schema(), batch.schema());
+    assert_eq!(expected, get_rows(&batch));
+    // each document ID is stored once
+    let document = batch.column(0).as_dictionary::<UInt32Type>();
+    assert_eq!(2, document.values().len());
+
+    assert!(builder.is_empty());
+    assert_eq!(0, builder.finish().num_rows());
+}
+
+#[cfg(feature = "parquet")]
+#[test]
+fn parquet_test() {
+    use parattice::arrow::SearchIndexWriter;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    use std::fs::File;
+
+    let parattice = PaRattice::new(get_paradict());
+    let keys = [
+        ("doc1", vec!["造血", "幹", "細胞", "移植"]),
+        ("doc2", vec!["blood", "stem", "cell"]),
+        ("doc3", vec!["幹", "細胞"]),
+    ];
+    let file_name = format!("parattice-search-index-{}.parquet", std::process::id());
+    let path = std::env::temp_dir().join(file_name);
+
+    let mut writer = SearchIndexWriter::with_batch_size(File::create(&path).unwrap(), 10).unwrap();
+    for (document, words) in &keys {
+        let lattice = parattice.get_lattice(words, true, 2);
+        writer.write(document, &lattice).unwrap();
+    }
+    drop(writer.finish().unwrap());
+
+    let reader = ParquetRecordBatchReaderBuilder::try_new(File::open(&path).unwrap())
+        .unwrap()
+        .build()
+        .unwrap();
+    let mut rows = vec![];
+    for batch in reader {
+        rows.extend(get_rows(&batch.unwrap()));
+    }
+    assert_eq!(get_expected_rows(&parattice, &keys), rows);
+    std::fs::remove_file(&path).unwrap();
+}
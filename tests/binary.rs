@@ -0,0 +1,225 @@
+extern crate parattice;
+
+mod common;
+
+use common::crc32;
+use common::fix_checksum;
+use common::get_paradict;
+
+use parattice::binary::BinaryOptions;
+use parattice::binary::Layout;
+use parattice::binary::FLAG_COMPACT;
+use parattice::binary::FLAG_DEPTH;
+use parattice::binary::FLAG_PROVENANCE;
+use parattice::binary::MAGIC;
+use parattice::error::DecodeError;
+use parattice::lattice::EdgeWeights;
+use parattice::lattice::LatticeNode;
+use parattice::Lattice;
+
+use parattice::PaRattice;
+
+#[test]
+fn binary_roundtrip_test() {
+    let parattice = PaRattice::new(get_paradict());
+    let words = vec!["造血", "幹", "細胞", "移植"];
+    let lattice = parattice.get_lattice(&words, true, 2);
+    assert!(lattice.lattice.iter().any(|node| node.depth == 2));
+
+    let bytes = lattice.to_bytes();
+    assert!(bytes.starts_with(&MAGIC));
+    assert_eq!(
+        crc32(&bytes[..bytes.len() - 4]).to_le_bytes(),
+        bytes[bytes.len() - 4..]
+    );
+    assert!(lattice == Lattice::new_from_bytes(&bytes));
+
+    let mut weights = EdgeWeights::new();
+    for (i, node) in lattice.lattice.iter().enumerate() {
+        // leaves the weight of the main edge absent
+        for &(s, j) in node.forwards.iter().skip(1) {
+            weights.insert((i, s, j), (i * 10 + j) as f64);
+        }
+    }
+    for &layout in &[Layout::Legacy, Layout::Compact, Layout::Indexed] {
+        let bytes = lattice.to_bytes_with_options(&BinaryOptions {
+            weights: Some(&weights),
+            depth: true,
+            offsets: true,
+            layout,
+        });
+        let (new_lattice, new_weights) = Lattice::try_from_bytes_with_weights(&bytes).unwrap();
+        assert!(lattice == new_lattice);
+        assert_eq!(Some(&weights), new_weights.as_ref());
+        for (node, new_node) in lattice.lattice.iter().zip(&new_lattice.lattice) {
+            assert_eq!(node.depth, new_node.depth);
+        }
+        assert!(lattice == Lattice::new_from_bytes(&bytes));
+    }
+
+    // the compact and the indexed layouts store depths without the section
+    for &layout in &[Layout::Compact, Layout::Indexed] {
+        let bytes = lattice.to_bytes_with_options(&BinaryOptions {
+            layout,
+            ..BinaryOptions::default()
+        });
+        assert!(lattice == Lattice::new_from_bytes(&bytes));
+    }
+    let bytes = lattice.to_bytes_with_options(&BinaryOptions {
+        layout: Layout::Legacy,
+        ..BinaryOptions::default()
+    });
+    let new_lattice = Lattice::new_from_bytes(&bytes);
+    assert!(new_lattice.lattice.iter().all(|node| node.depth == 0));
+}
+
+#[test]
+fn binary_compact_test() {
+    let parattice = PaRattice::new(get_paradict());
+    let words = vec!["造血", "幹", "細胞", "移植"];
+    for &shrink in &[true, false] {
+        let lattice = parattice.get_lattice(&words, shrink, 10);
+        let bytes = lattice.to_bytes();
+        assert_ne!(0, bytes[10] & FLAG_COMPACT as u8);
+        assert!(bytes.len() * 3 < lattice.to_legacy_bytes().len());
+        assert!(lattice == Lattice::new_from_bytes(&bytes));
+    }
+
+    // a lattice of a single edge "a" from node 0 to node 1
+    let mut section = vec![1, 1, b'a', 2];
+    section.extend(&[1, 0, 2, 1, 0, 0]);
+    section.extend(&[0, 0, 1, 0]);
+    let assert_section = |section: &[u8], expected: Result<(), DecodeError>| {
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend(&1u16.to_le_bytes());
+        bytes.extend(&FLAG_COMPACT.to_le_bytes());
+        bytes.extend(&[0; 4]);
+        bytes.extend(&(section.len() as u64 + 8).to_le_bytes());
+        bytes.extend(&(section.len() as u64).to_le_bytes());
+        bytes.extend(section);
+        bytes.extend(&crc32(&bytes).to_le_bytes());
+        assert_eq!(expected, Lattice::try_from_bytes(&bytes).map(|_| ()));
+    };
+    assert_section(&section, Ok(()));
+
+    let mut broken = section.clone();
+    broken[5] = 1;
+    assert_section(
+        &broken,
+        Err(DecodeError::LabelOutOfRange { node: 0, label: 1 }),
+    );
+
+    let mut broken = section.clone();
+    broken[6] = 4;
+    assert_section(
+        &broken,
+        Err(DecodeError::NodeOutOfRange { node: 0, target: 2 }),
+    );
+
+    let mut broken = section.clone();
+    broken[7] = 2;
+    assert_section(&broken, Err(DecodeError::InvalidMainEdge { node: 0 }));
+
+    let mut broken = section.clone();
+    broken.splice(0..1, vec![0xff; 10]);
+    assert_section(&broken, Err(DecodeError::InvalidVarint { offset: 0 }));
+
+    let mut broken = section.clone();
+    broken.push(0);
+    assert_section(&broken, Err(DecodeError::InvalidSection(FLAG_COMPACT)));
+}
+
+#[test]
+fn binary_legacy_test() {
+    let parattice = PaRattice::new(get_paradict());
+    let words = vec!["造血", "幹", "細胞", "移植"];
+    let mut lattice = parattice.get_lattice(&words, true, 2);
+    let bytes = lattice.to_legacy_bytes();
+    let (new_lattice, weights) = Lattice::try_from_bytes_with_weights(&bytes).unwrap();
+    assert!(weights.is_none());
+    // the legacy layout doesn't store depths
+    assert!(new_lattice.lattice.iter().all(|node| node.depth == 0));
+    for node in &mut lattice.lattice {
+        node.depth = 0;
+    }
+    assert!(lattice == new_lattice);
+    assert!(lattice == Lattice::new_from_bytes(&bytes));
+}
+
+#[test]
+fn binary_error_test() {
+    let parattice = PaRattice::new(get_paradict());
+    let words = vec!["造血", "幹", "細胞", "移植"];
+    let lattice = parattice.get_lattice(&words, true, 2);
+    let bytes = lattice.to_bytes_with_options(&BinaryOptions {
+        depth: true,
+        ..BinaryOptions::default()
+    });
+
+    let mut broken = bytes.clone();
+    broken[30] ^= 1;
+    match Lattice::try_from_bytes(&broken) {
+        Err(DecodeError::ChecksumMismatch { expected, actual }) => assert_ne!(expected, actual),
+        _ => panic!(),
+    }
+
+    match Lattice::try_from_bytes(&bytes[..bytes.len() - 1]) {
+        Err(DecodeError::Truncated { .. }) => {}
+        _ => panic!(),
+    }
+
+    let mut broken = bytes.clone();
+    broken[8..10].copy_from_slice(&2u16.to_le_bytes());
+    fix_checksum(&mut broken);
+    assert_eq!(
+        Err(DecodeError::UnsupportedVersion(2)),
+        Lattice::try_from_bytes(&broken).map(|_| ())
+    );
+
+    let mut broken = bytes.clone();
+    broken[10] |= FLAG_PROVENANCE as u8;
+    fix_checksum(&mut broken);
+    match Lattice::try_from_bytes(&broken) {
+        Err(DecodeError::UnsupportedFlags(flags)) => assert_ne!(0, flags & FLAG_PROVENANCE),
+        _ => panic!(),
+    }
+
+    // the depth section is shorter than the number of nodes
+    let mut broken = bytes.clone();
+    let length = broken.len();
+    broken.drain(length - 12..length - 4);
+    let section_length = lattice.lattice.len() * 8 - 8;
+    broken[length - 12 - section_length - 8..length - 12 - section_length]
+        .copy_from_slice(&(section_length as u64).to_le_bytes());
+    let payload_length = broken.len() - 28;
+    broken[16..24].copy_from_slice(&(payload_length as u64).to_le_bytes());
+    fix_checksum(&mut broken);
+    assert_eq!(
+        Err(DecodeError::InvalidSection(FLAG_DEPTH)),
+        Lattice::try_from_bytes(&broken).map(|_| ())
+    );
+}
+
+#[test]
+fn binary_invalid_main_edge_test() {
+    // 0 -> 3 is the trunk, and 0 -> 1 -> 2 -> 3 has no main edges on the nodes 1 and 2
+    let mut nodes = vec![
+        LatticeNode::new(("a", 3), None, 0),
+        LatticeNode::new(None, None, 1),
+        LatticeNode::new(None, None, 1),
+        LatticeNode::new(None, ("a", 0), 0),
+    ];
+    for &(s, i, j) in &[("b", 0, 1), ("c", 1, 2), ("d", 2, 3)] {
+        nodes[i].insert_forward(s, j);
+        nodes[j].insert_backward(s, i);
+    }
+    let lattice = Lattice {
+        lattice: nodes,
+        trunk: vec![(0, 0), (3, 1)].into_iter().collect(),
+        capacity: 4,
+    };
+    assert_eq!(
+        Err(DecodeError::InvalidMainEdge { node: 1 }),
+        Lattice::try_from_bytes(&lattice.to_bytes()).map(|_| ())
+    );
+}
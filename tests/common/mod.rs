@@ -0,0 +1,100 @@
+// fixtures shared by integration tests, each of which uses some of them
+#![allow(dead_code)]
+
+use parattice::Lattice;
+
+use std::collections::BTreeSet;
+
+pub fn get_paradict() -> Vec<Vec<Vec<&'static str>>> {
+    vec![
+        vec![
+            vec!["blood", "stem", "cell"],
+            vec!["造血", "幹", "細胞"],
+            vec!["hematopoietic", "stem", "cell"],
+        ],
+        vec![
+            vec!["造血", "幹", "細胞", "移植"],
+            vec!["hematopoietic", "stem", "cell", "transplantation"],
+        ],
+        vec![vec!["stem", "cell"], vec!["幹", "細胞"]],
+        vec![
+            vec!["幹", "細胞", "移植"],
+            vec!["rescue", "transplant"],
+            vec!["stem", "cell", "rescue"],
+        ],
+        vec![vec!["rescue"], vec!["救命"]],
+        vec![vec!["blood"], vec!["血液"]],
+    ]
+}
+
+// an independent implementation to check checksums written by the crate
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &b in data {
+        crc ^= b as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+/// Replaces the checksum at the end of the data.
+pub fn fix_checksum(bytes: &mut Vec<u8>) {
+    let length = bytes.len() - 4;
+    let checksum = crc32(&bytes[..length]);
+    bytes.truncate(length);
+    bytes.extend(&checksum.to_le_bytes());
+}
+
+pub fn get_sentences<'a>(lattice: &Lattice<'a>) -> BTreeSet<Vec<&'a str>> {
+    let mut result = BTreeSet::new();
+    let mut stack = vec![(0, vec![])];
+    while let Some((node_id, sentence)) = stack.pop() {
+        if node_id == lattice.lattice.len() - 1 {
+            result.insert(sentence);
+            continue;
+        }
+        for &(s, target) in &lattice.lattice[node_id].forwards {
+            let mut new_sentence = sentence.clone();
+            new_sentence.push(s);
+            stack.push((target, new_sentence));
+        }
+    }
+    result
+}
+
+pub fn get_trunk<'a>(lattice: &Lattice<'a>) -> Vec<&'a str> {
+    let mut trunk: Vec<(usize, usize)> = lattice.trunk.iter().map(|(&k, &v)| (v, k)).collect();
+    trunk.sort();
+    trunk[1..]
+        .iter()
+        .map(|&(_, node_id)| lattice.lattice[node_id].backward_main.unwrap().0)
+        .collect()
+}
+
+pub fn check_lattice(lattice: &Lattice) {
+    for (i, node) in lattice.lattice.iter().enumerate() {
+        for &(s, j) in &node.forwards {
+            assert!(i < j);
+            assert!(lattice.lattice[j].backwards.contains(&(s, i)));
+        }
+        for &(s, j) in &node.backwards {
+            assert!(lattice.lattice[j].forwards.contains(&(s, i)));
+        }
+        if let Some(edge) = node.forward_main {
+            assert!(node.forwards.contains(&edge));
+        }
+        if let Some(edge) = node.backward_main {
+            assert!(node.backwards.contains(&edge));
+        }
+    }
+    assert_eq!(lattice.lattice.len(), lattice.get_trunk_spans().len());
+    assert_eq!(lattice.capacity, lattice.dump_for_search_index().len());
+    let bytes = lattice.to_bytes();
+    assert_eq!(lattice, &Lattice::new_from_bytes(&bytes));
+}
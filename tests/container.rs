@@ -0,0 +1,162 @@
+extern crate parattice;
+
+mod common;
+
+use common::crc32;
+use common::get_paradict;
+
+use parattice::container::LatticeReader;
+use parattice::container::LatticeWriter;
+use parattice::error::ContainerError;
+use parattice::error::DecodeError;
+use parattice::PaRattice;
+
+use std::fs::File;
+use std::io::BufReader;
+use std::io::BufWriter;
+use std::io::Cursor;
+
+#[test]
+fn container_test() {
+    let parattice = PaRattice::new(get_paradict());
+    let sentences = [
+        vec!["造血", "幹", "細胞", "移植"],
+        vec!["blood", "stem", "cell"],
+        vec!["幹", "細胞"],
+    ];
+    let lattices: Vec<_> = sentences
+        .iter()
+        .map(|words| parattice.get_lattice(words, true, 2))
+        .collect();
+    let keys = vec![("doc2", 1), ("doc1", 0), ("doc2", 0)];
+
+    let mut writer = LatticeWriter::new(vec![]).unwrap();
+    for (&(document, sentence), lattice) in keys.iter().zip(&lattices) {
+        writer.write(document, sentence, lattice).unwrap();
+    }
+    match writer.write("doc1", 0, &lattices[0]) {
+        Err(ContainerError::DuplicateKey { document, sentence }) => {
+            assert_eq!(("doc1", 0), (document.as_str(), sentence));
+        }
+        _ => panic!(),
+    }
+    let bytes = writer.finish().unwrap();
+    let total_length: usize = lattices
+        .iter()
+        .map(|lattice| lattice.to_bytes().len())
+        .sum();
+    assert!(bytes.len() < total_length);
+
+    let reader = LatticeReader::new(Cursor::new(&bytes)).unwrap();
+    assert_eq!(3, reader.len());
+    assert_eq!(keys, reader.keys().collect::<Vec<_>>());
+    for (&(document, sentence), lattice) in keys.iter().zip(&lattices).rev() {
+        assert!(reader.contains(document, sentence));
+        assert_eq!(
+            Some(lattice),
+            reader.get(document, sentence).unwrap().as_ref()
+        );
+    }
+    assert!(!reader.contains("doc1", 1));
+    assert!(reader.get("doc1", 1).unwrap().is_none());
+    assert!(reader.get("doc3", 0).unwrap().is_none());
+
+    let reader = LatticeReader::new(Cursor::new(
+        LatticeWriter::new(vec![]).unwrap().finish().unwrap(),
+    ))
+    .unwrap();
+    assert!(reader.is_empty());
+}
+
+#[test]
+fn container_file_test() {
+    let parattice = PaRattice::new(get_paradict());
+    let words = vec!["造血", "幹", "細胞", "移植"];
+    let lattice = parattice.get_lattice(&words, false, 10);
+    let file_name = format!("parattice-container-{}.bin", std::process::id());
+    let path = std::env::temp_dir().join(file_name);
+
+    let mut writer = LatticeWriter::new(BufWriter::new(File::create(&path).unwrap())).unwrap();
+    for sentence in 0..100 {
+        writer.write("doc", sentence, &lattice).unwrap();
+    }
+    drop(writer.finish().unwrap());
+
+    let reader = LatticeReader::new(BufReader::new(File::open(&path).unwrap())).unwrap();
+    assert_eq!(100, reader.len());
+    let lattice50 = reader.get("doc", 50).unwrap().unwrap();
+    let lattice10 = reader.get("doc", 10).unwrap().unwrap();
+    assert_eq!(lattice, lattice50);
+    assert_eq!(lattice, lattice10);
+    drop(reader);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn container_error_test() {
+    let parattice = PaRattice::new(get_paradict());
+    let words = vec!["造血", "幹", "細胞", "移植"];
+    let lattice = parattice.get_lattice(&words, true, 2);
+    let mut writer = LatticeWriter::new(vec![]).unwrap();
+    writer.write("doc", 0, &lattice).unwrap();
+    let bytes = writer.finish().unwrap();
+
+    match LatticeReader::new(Cursor::new(lattice.to_bytes())) {
+        Err(ContainerError::NotContainer) => {}
+        _ => panic!(),
+    }
+
+    match LatticeReader::new(Cursor::new(&bytes[..bytes.len() - 1])) {
+        Err(ContainerError::Decode(_)) => {}
+        _ => panic!(),
+    }
+
+    let mut broken = bytes.clone();
+    broken[8] = 2;
+    match LatticeReader::new(Cursor::new(&broken)) {
+        Err(ContainerError::Decode(DecodeError::UnsupportedVersion(2))) => {}
+        _ => panic!(),
+    }
+
+    // the last byte of the index
+    let mut broken = bytes.clone();
+    let length = broken.len();
+    broken[length - 29] ^= 1;
+    match LatticeReader::new(Cursor::new(&broken)) {
+        Err(ContainerError::Decode(DecodeError::ChecksumMismatch { .. })) => {}
+        _ => panic!(),
+    }
+
+    // the first byte of the lattice
+    let mut broken = bytes.clone();
+    broken[16] ^= 1;
+    let reader = LatticeReader::new(Cursor::new(&broken)).unwrap();
+    match reader.get("doc", 0) {
+        Err(ContainerError::Decode(DecodeError::ChecksumMismatch { .. })) => {}
+        _ => panic!(),
+    }
+
+    // the index has the same key twice
+    let mut writer = LatticeWriter::new(vec![]).unwrap();
+    writer.write("doc", 0, &lattice).unwrap();
+    writer.write("doc", 1, &lattice).unwrap();
+    let mut broken = writer.finish().unwrap();
+    let length = broken.len();
+    let key = [3, b'd', b'o', b'c', 1];
+    let key_offset = (0..length - key.len())
+        .rev()
+        .find(|&i| broken[i..i + key.len()] == key)
+        .unwrap();
+    broken[key_offset + key.len() - 1] = 0;
+    let mut table_offset = [0; 8];
+    table_offset.copy_from_slice(&broken[length - 24..length - 16]);
+    let table_offset = u64::from_le_bytes(table_offset) as usize;
+    let checksum = crc32(&broken[table_offset..length - 8]);
+    broken[length - 8..length - 4].copy_from_slice(&checksum.to_le_bytes());
+    match LatticeReader::new(Cursor::new(&broken)) {
+        Err(ContainerError::DuplicateKey { document, sentence }) => {
+            assert_eq!(("doc", 0), (document.as_str(), sentence));
+        }
+        _ => panic!(),
+    }
+}
@@ -0,0 +1,75 @@
+extern crate parattice;
+
+use parattice::dot::DotEdgeLabels;
+use parattice::dot::DotOptions;
+use parattice::LatticeKMP;
+use parattice::PaRattice;
+
+use std::collections::HashSet;
+
+#[test]
+fn dump_dot_with_options_test() {
+    let paradict = vec![
+        vec![
+            vec!["blood", "stem", "cell"],
+            vec!["造血", "幹", "細胞"],
+            vec!["hematopoietic", "stem", "cell"],
+        ],
+        vec![
+            vec!["造血", "幹", "細胞", "移植"],
+            vec!["hematopoietic", "stem", "cell", "transplantation"],
+        ],
+        vec![vec!["stem", "cell"], vec!["幹", "細胞"]],
+        vec![
+            vec!["幹", "細胞", "移植"],
+            vec!["rescue", "transplant"],
+            vec!["stem", "cell", "rescue"],
+        ],
+        vec![vec!["rescue"], vec!["救命"]],
+        vec![vec!["blood"], vec!["血液"]],
+    ];
+    let parattice = PaRattice::new(paradict);
+    let words = vec!["造血", "幹", "細胞", "移植"];
+    let lattice = parattice.get_lattice(&words, true, 10);
+
+    // default options
+    assert_eq!(
+        lattice.dump_dot(true),
+        lattice.dump_dot_with_options(&DotOptions {
+            is_numbered: true,
+            ..DotOptions::default()
+        })
+    );
+
+    // labels on edges with trunk alignment and depth colors
+    let dot = lattice.dump_dot_with_options(&DotOptions {
+        edge_labels: DotEdgeLabels::OnEdges,
+        align_trunk: true,
+        depth_colors: vec!["white".to_string(), "gray".to_string()],
+        ..DotOptions::default()
+    });
+    assert_eq!(lattice.capacity, dot.matches(" -> ").count());
+    assert_eq!(lattice.trunk.len(), dot.matches("group=\"trunk\"").count());
+    assert_eq!(lattice.trunk.len() - 1, dot.matches("weight=10").count());
+    assert_eq!(lattice.lattice.len(), dot.matches("fillcolor=").count());
+    // nodes at different depths get different colors
+    assert!(dot.contains("fillcolor=\"white\""));
+    assert!(dot.contains("fillcolor=\"gray\""));
+
+    // highlighted search results
+    let kmp = LatticeKMP::new(vec!["幹", "細胞"]);
+    let results = kmp.search(&lattice);
+    let mut edges = HashSet::new();
+    for path in &results {
+        for i in 1..path.len() {
+            edges.insert((path[i - 1].1, path[i]));
+        }
+    }
+    let dot = lattice.dump_dot_with_options(&DotOptions {
+        edge_labels: DotEdgeLabels::OnEdges,
+        highlighted_paths: results,
+        highlight_color: "green".to_string(),
+        ..DotOptions::default()
+    });
+    assert_eq!(edges.len(), dot.matches("color=\"green\"").count());
+}
@@ -0,0 +1,66 @@
+extern crate parattice;
+
+mod common;
+
+use common::get_paradict;
+
+use parattice::lattice_graph::LatticeGraph;
+use parattice::LatticeKMP;
+use parattice::PaRattice;
+
+#[test]
+fn frozen_lattice_test() {
+    let parattice = PaRattice::new(get_paradict());
+    let words = vec!["造血", "幹", "細胞", "移植"];
+    for &shrink in &[true, false] {
+        let lattice = parattice.get_lattice(&words, shrink, 10);
+        let frozen = lattice.freeze();
+        assert_eq!(lattice.lattice.len(), frozen.num_nodes());
+        assert_eq!(lattice.capacity, frozen.num_edges());
+        let mut labels = frozen.labels().to_vec();
+        labels.sort();
+        labels.dedup();
+        assert_eq!(labels, frozen.labels());
+        for (node_id, node) in lattice.lattice.iter().enumerate() {
+            assert_eq!(
+                node.forwards.iter().cloned().collect::<Vec<_>>(),
+                frozen.forwards(node_id).collect::<Vec<_>>()
+            );
+            assert_eq!(
+                node.backwards.iter().cloned().collect::<Vec<_>>(),
+                frozen.backwards(node_id).collect::<Vec<_>>()
+            );
+            assert_eq!(node.forward_main, frozen.forward_main(node_id));
+            assert_eq!(node.backward_main, frozen.backward_main(node_id));
+            assert_eq!(
+                lattice.trunk.get(&node_id).cloned(),
+                frozen.trunk_position(node_id)
+            );
+        }
+        assert_eq!(lattice, frozen.to_lattice());
+
+        assert_eq!(lattice.get_trunk_spans(), frozen.get_trunk_spans());
+        let index_data: Vec<_> = lattice
+            .dump_for_search_index()
+            .into_iter()
+            .map(|node| (node.text, node.offset, node.increment, node.length))
+            .collect();
+        let frozen_index_data: Vec<_> = frozen
+            .dump_for_search_index()
+            .into_iter()
+            .map(|node| (node.text, node.offset, node.increment, node.length))
+            .collect();
+        assert_eq!(index_data, frozen_index_data);
+
+        let kmp = LatticeKMP::new(vec!["幹", "細胞"]);
+        let results = kmp.search(&lattice);
+        assert!(!results.is_empty());
+        assert_eq!(results, kmp.search(&frozen));
+        for path in results {
+            assert_eq!(
+                lattice.get_trunk_span(path.clone()),
+                frozen.get_trunk_span(path)
+            );
+        }
+    }
+}
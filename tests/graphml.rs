@@ -0,0 +1,45 @@
+extern crate parattice;
+
+use parattice::PaRattice;
+
+#[test]
+fn dump_graphml_test() {
+    let paradict = vec![
+        vec![
+            vec!["blood", "stem", "cell"],
+            vec!["造血", "幹", "細胞"],
+            vec!["hematopoietic", "stem", "cell"],
+        ],
+        vec![vec!["stem", "cell"], vec!["幹", "細胞"]],
+        vec![vec!["blood"], vec!["<血液>"]],
+    ];
+    let parattice = PaRattice::new(paradict);
+    let words = vec!["造血", "幹", "細胞"];
+    let lattice = parattice.get_lattice(&words, true, 10);
+    let graphml = lattice.dump_graphml();
+
+    assert_eq!(lattice.lattice.len(), graphml.matches("<node id=").count());
+    assert_eq!(lattice.capacity, graphml.matches("<edge id=").count());
+    assert_eq!(
+        lattice.trunk.len(),
+        graphml.matches("<data key=\"trunk_position\">").count()
+    );
+    // one forward main edge for each node except the last one
+    assert_eq!(
+        lattice.lattice.len() - 1,
+        graphml
+            .matches("<data key=\"forward_main\">true</data>")
+            .count()
+    );
+    // nodes inside "blood stem cell" are reached through a paraphrase
+    assert!(lattice.lattice.iter().any(|node| node.depth != 0));
+    for (i, node) in lattice.lattice.iter().enumerate() {
+        assert!(graphml.contains(&format!(
+            "<node id=\"n{}\"><data key=\"depth\">{}</data>",
+            i, node.depth
+        )));
+    }
+    assert!(graphml.contains("<data key=\"label\">&lt;血液&gt;</data>"));
+    assert!(!graphml.contains("<血液>"));
+    assert!(graphml.ends_with("</graphml>\n"));
+}
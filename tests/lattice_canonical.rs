@@ -0,0 +1,75 @@
+extern crate parattice;
+
+use parattice::Lattice;
+use parattice::PaRattice;
+
+fn get_sentences<'a>(lattice: &Lattice<'a>) -> Vec<Vec<&'a str>> {
+    let mut sentences: Vec<Vec<&str>> = lattice
+        .n_best(1000, |_, _, _| 1.0)
+        .into_iter()
+        .map(|(path, _)| path[1..].iter().map(|edge| edge.0).collect())
+        .collect();
+    sentences.sort();
+    sentences.dedup();
+    sentences
+}
+
+#[test]
+fn canonicalize_test() {
+    let paradict = vec![
+        vec![
+            vec!["blood", "stem", "cell"],
+            vec!["造血", "幹", "細胞"],
+            vec!["hematopoietic", "stem", "cell"],
+        ],
+        vec![
+            vec!["造血", "幹", "細胞", "移植"],
+            vec!["hematopoietic", "stem", "cell", "transplantation"],
+        ],
+        vec![vec!["stem", "cell"], vec!["幹", "細胞"]],
+        vec![
+            vec!["幹", "細胞", "移植"],
+            vec!["rescue", "transplant"],
+            vec!["stem", "cell", "rescue"],
+        ],
+        vec![vec!["rescue"], vec!["救命"]],
+        vec![vec!["blood"], vec!["血液"]],
+    ];
+    let parattice = PaRattice::new(paradict);
+    let words1 = vec!["造血", "幹", "細胞", "移植"];
+    let words2 = vec!["blood", "stem", "cell"];
+    let lattice1 = parattice.get_lattice(&words1, true, 10);
+    let lattice1_noshrink = parattice.get_lattice(&words1, false, 10);
+    assert_ne!(lattice1.to_bytes(), lattice1_noshrink.to_bytes());
+    let canonical = lattice1.canonicalize();
+    assert_eq!(canonical, lattice1_noshrink.canonicalize());
+    assert_eq!(canonical, canonical.canonicalize());
+    assert_eq!(lattice1.trunk.len(), canonical.trunk.len());
+    assert_eq!(
+        lattice1.canonical_hash(),
+        lattice1_noshrink.canonical_hash()
+    );
+    assert_eq!(get_sentences(&lattice1), get_sentences(&canonical));
+
+    // same paths with a different trunk
+    let lattice2 = parattice.get_lattice(&words2, true, 10);
+    let union12 = lattice1.union(&lattice2);
+    let union21 = lattice2.union(&lattice1);
+    assert_ne!(union12.canonical_hash(), union21.canonical_hash());
+    assert_eq!(
+        union12.canonical_hash(),
+        lattice1_noshrink.union(&lattice2).canonical_hash()
+    );
+
+    // paths of different lengths that end with the same word
+    let lattice3 = lattice1.concat(&lattice2);
+    let lattice4 = lattice1_noshrink.concat(&parattice.get_lattice(&words2, false, 10));
+    assert_eq!(lattice3.canonicalize(), lattice4.canonicalize());
+
+    // hash values are stable across versions of the crate
+    assert_eq!(0x4bf8_dd76_453e_1290, lattice1.canonical_hash());
+
+    let empty = parattice.get_lattice(&[], true, 10);
+    assert_eq!(empty, empty.canonicalize());
+    assert_eq!(0x7659_bfa3_6a90_f46c, empty.canonical_hash());
+}
@@ -0,0 +1,128 @@
+extern crate parattice;
+
+mod common;
+
+use common::{check_lattice, get_paradict, get_sentences, get_trunk};
+
+use parattice::error::EditError;
+use parattice::PaRattice;
+
+use std::collections::BTreeSet;
+
+#[test]
+fn retain_edges_test() {
+    let parattice = PaRattice::new(get_paradict());
+    let words = vec!["造血", "幹", "細胞", "移植"];
+    let orig_lattice = parattice.get_lattice(&words, true, 10);
+
+    let mut lattice = parattice.get_lattice(&words, true, 10);
+    let node_id_map = lattice.retain_edges(|s, _, _, _| s != "stem");
+    check_lattice(&lattice);
+    assert_eq!(words, get_trunk(&lattice));
+    let expected: BTreeSet<_> = get_sentences(&orig_lattice)
+        .into_iter()
+        .filter(|sentence| !sentence.contains(&"stem"))
+        .collect();
+    assert_eq!(expected, get_sentences(&lattice));
+    for (&node_id, &position) in &orig_lattice.trunk {
+        assert_eq!(
+            Some(&position),
+            lattice.trunk.get(&node_id_map[node_id].unwrap())
+        );
+    }
+
+    // prune by a depth threshold
+    let mut lattice = parattice.get_lattice(&words, true, 10);
+    assert!(lattice.lattice.iter().any(|node| node.depth > 1));
+    lattice.retain_edges(|_, _, _, node| node.depth <= 1);
+    check_lattice(&lattice);
+    assert_eq!(words, get_trunk(&lattice));
+    assert!(lattice.lattice.len() < orig_lattice.lattice.len());
+    assert!(lattice.lattice.iter().all(|node| node.depth <= 1));
+    let sentences = get_sentences(&lattice);
+    assert!(sentences.is_subset(&get_sentences(&orig_lattice)));
+    assert!(sentences.len() < get_sentences(&orig_lattice).len());
+
+    // trunk edges are kept
+    let mut lattice = parattice.get_lattice(&words, true, 10);
+    lattice.retain_edges(|_, _, _, _| false);
+    check_lattice(&lattice);
+    assert_eq!(words, get_trunk(&lattice));
+    assert_eq!(words.len(), lattice.capacity);
+    assert_eq!(words.len() + 1, lattice.lattice.len());
+}
+
+#[test]
+fn edit_test() {
+    let parattice = PaRattice::new(get_paradict());
+    let words = vec!["造血", "幹", "細胞", "移植"];
+    let mut lattice = parattice.get_lattice(&words, true, 10);
+    let mut expected = get_sentences(&lattice);
+
+    // add a branch
+    lattice.add_branch(1, 3, &["stem", "cells"]).unwrap();
+    check_lattice(&lattice);
+    expected.insert(vec!["造血", "stem", "cells", "移植"]);
+    assert_eq!(expected, get_sentences(&lattice));
+    assert_eq!(
+        Err(EditError::InvalidTrunkSpan),
+        lattice.add_branch(3, 1, &["stem"])
+    );
+    assert_eq!(Err(EditError::EmptyBranch), lattice.add_branch(1, 3, &[]));
+    let (s, _) = lattice.lattice[0].forward_main.unwrap();
+    assert_eq!(
+        Err(EditError::DuplicateEdge),
+        lattice.add_branch(0, 1, &[s])
+    );
+    assert_eq!(expected, get_sentences(&lattice));
+
+    // remove an edge
+    let (_, node_id) = *lattice.lattice[0]
+        .forwards
+        .iter()
+        .find(|&&(s, _)| s == "血液")
+        .unwrap();
+    lattice.remove_edge(0, "血液", node_id).unwrap();
+    check_lattice(&lattice);
+    expected.retain(|sentence| sentence[0] != "血液");
+    assert_eq!(expected, get_sentences(&lattice));
+    assert_eq!(
+        Err(EditError::EdgeNotFound),
+        lattice.remove_edge(0, "血液", node_id)
+    );
+    let (s, node_id) = lattice.lattice[0].forward_main.unwrap();
+    assert_eq!(
+        Err(EditError::TrunkEdge),
+        lattice.remove_edge(0, s, node_id)
+    );
+
+    // relabel an edge and replace a trunk token
+    let (_, node_id) = *lattice.lattice[0]
+        .forwards
+        .iter()
+        .find(|&&(s, _)| s == "blood")
+        .unwrap();
+    lattice.relabel_edge(0, "blood", node_id, "Blood").unwrap();
+    lattice.replace_trunk_token(3, "移殖").unwrap();
+    check_lattice(&lattice);
+    assert_eq!(vec!["造血", "幹", "細胞", "移殖"], get_trunk(&lattice));
+    let expected: BTreeSet<_> = expected
+        .into_iter()
+        .map(|sentence| {
+            sentence
+                .into_iter()
+                .map(|s| match s {
+                    "blood" => "Blood",
+                    // the only edge of 移植 is on the trunk
+                    "移植" => "移殖",
+                    _ => s,
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    assert_eq!(expected, get_sentences(&lattice));
+    assert_eq!(
+        Err(EditError::InvalidTrunkSpan),
+        lattice.replace_trunk_token(4, "x")
+    );
+}
@@ -0,0 +1,78 @@
+extern crate parattice;
+
+use parattice::PaRattice;
+
+#[test]
+fn n_best_test() {
+    let paradict = vec![
+        vec![
+            vec!["blood", "stem", "cell"],
+            vec!["造血", "幹", "細胞"],
+            vec!["hematopoietic", "stem", "cell"],
+        ],
+        vec![
+            vec!["造血", "幹", "細胞", "移植"],
+            vec!["hematopoietic", "stem", "cell", "transplantation"],
+        ],
+        vec![vec!["stem", "cell"], vec!["幹", "細胞"]],
+        vec![
+            vec!["幹", "細胞", "移植"],
+            vec!["rescue", "transplant"],
+            vec!["stem", "cell", "rescue"],
+        ],
+        vec![vec!["rescue"], vec!["救命"]],
+        vec![vec!["blood"], vec!["血液"]],
+    ];
+    let parattice = PaRattice::new(paradict);
+    let words = vec!["造血", "幹", "細胞", "移植"];
+    let lattice = parattice.get_lattice(&words, true, 10);
+
+    // the trunk is the only path without any cost
+    let results = lattice.n_best(4, |_, i, j| {
+        if lattice.trunk.contains_key(&i) && lattice.trunk.contains_key(&j) {
+            0.0
+        } else {
+            1.0
+        }
+    });
+    assert_eq!(4, results.len());
+    assert_eq!(
+        (
+            vec![("", 0), ("造血", 3), ("幹", 9), ("細胞", 13), ("移植", 16)],
+            0.0
+        ),
+        results[0]
+    );
+    for i in 1..results.len() {
+        assert!(results[i - 1].1 <= results[i].1);
+        assert_ne!(results[i - 1].0, results[i].0);
+    }
+
+    // shortest paraphrases
+    let results = lattice.n_best(3, |_, _, _| 1.0);
+    let mut labels: Vec<(Vec<&str>, f64)> = results
+        .iter()
+        .map(|(path, cost)| (path[1..].iter().map(|edge| edge.0).collect(), *cost))
+        .collect();
+    labels.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    assert_eq!(
+        vec![
+            (vec!["blood", "rescue", "transplant"], 3.0),
+            (vec!["血液", "rescue", "transplant"], 3.0),
+            (vec!["造血", "rescue", "transplant"], 3.0),
+        ],
+        labels
+    );
+
+    // all paths
+    let mut num_paths = vec![0; lattice.lattice.len()];
+    num_paths[lattice.lattice.len() - 1] = 1;
+    for i in (0..lattice.lattice.len() - 1).rev() {
+        num_paths[i] = lattice.lattice[i]
+            .forwards
+            .iter()
+            .map(|edge| num_paths[edge.1])
+            .sum();
+    }
+    assert_eq!(num_paths[0], lattice.n_best(1000, |_, _, _| 1.0).len());
+}
@@ -0,0 +1,123 @@
+extern crate parattice;
+
+mod common;
+
+use common::{check_lattice, get_paradict, get_sentences, get_trunk};
+
+use parattice::PaRattice;
+
+use std::collections::BTreeSet;
+
+#[test]
+fn union_test() {
+    let parattice = PaRattice::new(get_paradict());
+    let words1 = vec!["造血", "幹", "細胞", "移植"];
+    let words2 = vec!["造血", "幹", "細胞", "の", "移植"];
+    let lattice1 = parattice.get_lattice(&words1, true, 10);
+    let lattice2 = parattice.get_lattice(&words2, true, 10);
+    let lattice = lattice1.union(&lattice2);
+    check_lattice(&lattice);
+    let expected: BTreeSet<_> = get_sentences(&lattice1)
+        .union(&get_sentences(&lattice2))
+        .cloned()
+        .collect();
+    assert_eq!(expected, get_sentences(&lattice));
+    assert_eq!(words1, get_trunk(&lattice));
+    assert_eq!(lattice1, lattice1.union(&lattice1));
+}
+
+#[test]
+fn intersect_test() {
+    let parattice = PaRattice::new(get_paradict());
+    let words1 = vec!["造血", "幹", "細胞", "移植"];
+    let words2 = vec!["blood", "stem", "cell", "rescue"];
+    let lattice1 = parattice.get_lattice(&words1, true, 10);
+    let lattice2 = parattice.get_lattice(&words2, true, 10);
+    let lattice = lattice1.intersect(&lattice2).unwrap();
+    check_lattice(&lattice);
+    let expected: BTreeSet<_> = get_sentences(&lattice1)
+        .intersection(&get_sentences(&lattice2))
+        .cloned()
+        .collect();
+    assert!(!expected.is_empty());
+    assert_eq!(expected, get_sentences(&lattice));
+    assert_eq!(words1, get_trunk(&lattice));
+
+    let lattice3 = parattice.get_lattice(&["血液"], true, 10);
+    assert!(lattice1.intersect(&lattice3).is_none());
+
+    // the trunk is chosen by the number of trunk edges of self, not by the number of the other
+    // edges
+    let parattice1 = PaRattice::new(vec![
+        vec![vec!["d"], vec!["x"]],
+        vec![vec!["a", "b", "c", "d"], vec!["y"]],
+    ]);
+    let parattice2 = PaRattice::new(vec![vec![vec!["y"], vec!["a", "b", "c", "x"]]]);
+    let lattice1 = parattice1.get_lattice(&["a", "b", "c", "d"], true, 10);
+    let lattice2 = parattice2.get_lattice(&["y"], true, 10);
+    let lattice = lattice1.intersect(&lattice2).unwrap();
+    check_lattice(&lattice);
+    let expected: BTreeSet<_> = vec![vec!["a", "b", "c", "x"], vec!["y"]]
+        .into_iter()
+        .collect();
+    assert_eq!(expected, get_sentences(&lattice));
+    assert_eq!(vec!["a", "b", "c", "x"], get_trunk(&lattice));
+    assert_eq!(
+        vec!["y"],
+        get_trunk(&lattice2.intersect(&lattice1).unwrap())
+    );
+}
+
+#[test]
+fn concat_test() {
+    let parattice = PaRattice::new(get_paradict());
+    let words1 = vec!["造血", "幹", "細胞", "移植"];
+    let words2 = vec!["blood", "stem", "cell"];
+    let lattice1 = parattice.get_lattice(&words1, true, 10);
+    let lattice2 = parattice.get_lattice(&words2, true, 10);
+    let lattice = lattice1.concat(&lattice2);
+    check_lattice(&lattice);
+    let mut expected = BTreeSet::new();
+    for sentence1 in &get_sentences(&lattice1) {
+        for sentence2 in &get_sentences(&lattice2) {
+            expected.insert([&sentence1[..], &sentence2[..]].concat());
+        }
+    }
+    assert_eq!(expected, get_sentences(&lattice));
+    assert_eq!([&words1[..], &words2[..]].concat(), get_trunk(&lattice));
+    let trunk_spans = lattice.get_trunk_spans();
+    assert_eq!((0, 0), trunk_spans[0]);
+    assert_eq!((7, 7), trunk_spans[lattice.lattice.len() - 1]);
+}
+
+#[test]
+fn slice_test() {
+    let parattice = PaRattice::new(get_paradict());
+    let words = vec!["造血", "幹", "細胞", "移植"];
+    let lattice = parattice.get_lattice(&words, true, 10);
+
+    let (sub_lattice, node_ids) = lattice.slice(1, 3);
+    check_lattice(&sub_lattice);
+    assert_eq!(vec!["幹", "細胞"], get_trunk(&sub_lattice));
+    let expected: BTreeSet<_> = vec![vec!["幹", "細胞"], vec!["stem", "cell"]]
+        .into_iter()
+        .collect();
+    assert_eq!(expected, get_sentences(&sub_lattice));
+    assert_eq!(sub_lattice.lattice.len(), node_ids.len());
+    for (i, node) in sub_lattice.lattice.iter().enumerate() {
+        for &(s, j) in &node.forwards {
+            assert!(lattice.lattice[node_ids[i]]
+                .forwards
+                .contains(&(s, node_ids[j])));
+        }
+    }
+
+    let (sub_lattice, node_ids) = lattice.slice(0, 4);
+    assert_eq!(lattice, sub_lattice);
+    assert_eq!((0..lattice.lattice.len()).collect::<Vec<_>>(), node_ids);
+
+    let (sub_lattice, node_ids) = lattice.slice(2, 2);
+    assert_eq!(1, sub_lattice.lattice.len());
+    assert_eq!(0, sub_lattice.capacity);
+    assert_eq!(1, node_ids.len());
+}
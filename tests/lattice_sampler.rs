@@ -0,0 +1,100 @@
+#![cfg(feature = "sampler")]
+
+extern crate parattice;
+
+use parattice::PaRattice;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use std::collections::HashSet;
+
+#[test]
+fn sample_paths_test() {
+    let paradict = vec![
+        vec![
+            vec!["blood", "stem", "cell"],
+            vec!["造血", "幹", "細胞"],
+            vec!["hematopoietic", "stem", "cell"],
+        ],
+        vec![
+            vec!["造血", "幹", "細胞", "移植"],
+            vec!["hematopoietic", "stem", "cell", "transplantation"],
+        ],
+        vec![vec!["stem", "cell"], vec!["幹", "細胞"]],
+        vec![
+            vec!["幹", "細胞", "移植"],
+            vec!["rescue", "transplant"],
+            vec!["stem", "cell", "rescue"],
+        ],
+        vec![vec!["rescue"], vec!["救命"]],
+        vec![vec!["blood"], vec!["血液"]],
+    ];
+    let parattice = PaRattice::new(paradict);
+    let words = vec!["造血", "幹", "細胞", "移植"];
+    let lattice = parattice.get_lattice(&words, true, 10);
+    let paths = lattice.sample_paths(&mut StdRng::seed_from_u64(1), 200);
+    assert_eq!(200, paths.len());
+    for path in &paths {
+        assert_eq!(("", 0), path[0]);
+        assert_eq!(lattice.lattice.len() - 1, path.last().unwrap().1);
+        for i in 1..path.len() {
+            assert!(lattice.lattice[path[i - 1].1].forwards.contains(&path[i]));
+        }
+    }
+    let distinct: HashSet<_> = paths.iter().collect();
+    assert!(distinct.len() > 10);
+    assert_eq!(
+        paths,
+        lattice.sample_paths(&mut StdRng::seed_from_u64(1), 200)
+    );
+}
+
+#[test]
+fn sample_paths_weighted_test() {
+    let paradict = vec![
+        vec![
+            vec!["blood", "stem", "cell"],
+            vec!["造血", "幹", "細胞"],
+            vec!["hematopoietic", "stem", "cell"],
+        ],
+        vec![
+            vec!["造血", "幹", "細胞", "移植"],
+            vec!["hematopoietic", "stem", "cell", "transplantation"],
+        ],
+        vec![vec!["stem", "cell"], vec!["幹", "細胞"]],
+        vec![
+            vec!["幹", "細胞", "移植"],
+            vec!["rescue", "transplant"],
+            vec!["stem", "cell", "rescue"],
+        ],
+        vec![vec!["rescue"], vec!["救命"]],
+        vec![vec!["blood"], vec!["血液"]],
+    ];
+    let parattice = PaRattice::new(paradict);
+    let words = vec!["造血", "幹", "細胞", "移植"];
+    let lattice = parattice.get_lattice(&words, true, 10);
+    let paths = lattice.sample_paths_weighted(&mut StdRng::seed_from_u64(1), 10, |s, _, _| {
+        if s == "stem" || s == "幹" {
+            0.0
+        } else {
+            1.0
+        }
+    });
+    let mut distinct: Vec<Vec<&str>> = paths
+        .iter()
+        .map(|path| path[1..].iter().map(|edge| edge.0).collect())
+        .collect();
+    distinct.sort();
+    distinct.dedup();
+    assert_eq!(
+        vec![
+            vec!["blood", "rescue", "transplant"],
+            vec!["血液", "rescue", "transplant"],
+            vec!["造血", "rescue", "transplant"],
+            vec!["造血", "救命", "transplant"],
+        ],
+        distinct
+    );
+    let nothing = lattice.sample_paths_weighted(&mut StdRng::seed_from_u64(1), 10, |_, _, _| 0.0);
+    assert!(nothing.is_empty());
+}
@@ -0,0 +1,110 @@
+extern crate parattice;
+
+mod common;
+
+use common::get_paradict;
+
+use parattice::error::ValidationError;
+use parattice::PaRattice;
+
+#[test]
+fn validate_test() {
+    let parattice = PaRattice::new(get_paradict());
+    let words = vec!["造血", "幹", "細胞", "移植"];
+    let lattice = parattice.get_lattice(&words, true, 10);
+    assert_eq!(Ok(()), lattice.validate());
+    let lattice = parattice.get_lattice(&words, false, 10);
+    assert_eq!(Ok(()), lattice.validate());
+    let mut lattice = parattice.get_lattice(&words, true, 10);
+    lattice.retain_edges(|s, _, _, _| s != "stem");
+    assert_eq!(Ok(()), lattice.validate());
+}
+
+#[test]
+fn validate_broken_lattice_test() {
+    let parattice = PaRattice::new(get_paradict());
+    let words = vec!["造血", "幹", "細胞", "移植"];
+    let orig_capacity = parattice.get_lattice(&words, true, 10).capacity;
+
+    // a missing backward edge
+    let mut lattice = parattice.get_lattice(&words, true, 10);
+    let (s, node_id) = *lattice.lattice[0]
+        .forwards
+        .iter()
+        .find(|&&(s, _)| s == "血液")
+        .unwrap();
+    lattice.lattice[node_id].backwards.remove(&(s, 0));
+    assert_eq!(
+        Err(vec![ValidationError::MissingBackwardEdge {
+            source: 0,
+            label: "血液",
+            target: node_id,
+        }]),
+        lattice.validate()
+    );
+
+    // a wrong capacity and a main edge not in the edge set
+    let mut lattice = parattice.get_lattice(&words, true, 10);
+    lattice.capacity += 1;
+    let last_node_id = lattice.lattice.len() - 1;
+    let edge = lattice.lattice[last_node_id].backward_main.unwrap();
+    lattice.lattice[last_node_id].backward_main = Some(("x", edge.1));
+    assert_eq!(
+        Err(vec![
+            ValidationError::BackwardMainNotFound {
+                node: last_node_id,
+                edge: ("x", edge.1),
+            },
+            ValidationError::CapacityMismatch {
+                capacity: orig_capacity + 1,
+                num_edges: orig_capacity,
+            },
+        ]),
+        lattice.validate()
+    );
+
+    // an edge from right to left and an extra source
+    let mut lattice = parattice.get_lattice(&words, true, 10);
+    let node_id = lattice.lattice.len() - 2;
+    lattice.lattice[node_id].forwards.insert(("x", 1));
+    lattice.lattice[1].backwards.insert(("x", node_id));
+    lattice.capacity += 1;
+    let errors = lattice.validate().unwrap_err();
+    assert_eq!(
+        vec![ValidationError::NotLeftToRight {
+            source: node_id,
+            label: "x",
+            target: 1,
+        }],
+        errors
+    );
+    assert_eq!(
+        format!("edge {} -x-> 1 doesn't go from left to right", node_id),
+        errors[0].to_string()
+    );
+
+    // a broken trunk
+    let mut lattice = parattice.get_lattice(&words, true, 10);
+    let (&node_id, _) = lattice.trunk.iter().find(|&(_, &pos)| pos == 2).unwrap();
+    lattice.trunk.insert(node_id, 3);
+    let errors = lattice.validate().unwrap_err();
+    assert_eq!(
+        ValidationError::TrunkPositionMismatch {
+            node: node_id,
+            expected: 2,
+            actual: Some(3),
+        },
+        errors[0]
+    );
+    assert_eq!(1, errors.len());
+
+    let mut lattice = parattice.get_lattice(&words, true, 10);
+    let node_id = lattice.lattice[0].forward_main.unwrap().1;
+    let removed = lattice.lattice[node_id].forward_main.take();
+    assert!(removed.is_some());
+    let errors = lattice.validate().unwrap_err();
+    assert!(errors.contains(&ValidationError::MissingForwardMain { node: node_id }));
+    assert!(errors.contains(&ValidationError::TrunkNodeNotOnPath {
+        node: lattice.lattice.len() - 1,
+    }));
+}
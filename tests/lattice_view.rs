@@ -0,0 +1,164 @@
+extern crate parattice;
+
+mod common;
+
+use common::fix_checksum;
+use common::get_paradict;
+
+use parattice::binary::BinaryOptions;
+use parattice::binary::Layout;
+use parattice::binary::FLAG_COMPACT;
+use parattice::binary::FLAG_INDEXED;
+use parattice::error::DecodeError;
+use parattice::lattice_graph::LatticeGraph;
+use parattice::lattice_view::LatticeView;
+use parattice::Lattice;
+use parattice::LatticeKMP;
+use parattice::PaRattice;
+
+fn to_indexed_bytes(lattice: &Lattice) -> Vec<u8> {
+    lattice.to_bytes_with_options(&BinaryOptions {
+        layout: Layout::Indexed,
+        ..BinaryOptions::default()
+    })
+}
+
+#[test]
+fn lattice_view_test() {
+    let parattice = PaRattice::new(get_paradict());
+    let words = vec!["造血", "幹", "細胞", "移植"];
+    for &shrink in &[true, false] {
+        let lattice = parattice.get_lattice(&words, shrink, 10);
+        let bytes = to_indexed_bytes(&lattice);
+        let view = LatticeView::new(&bytes).unwrap();
+        assert_eq!(lattice.lattice.len(), view.num_nodes());
+        assert_eq!(lattice.capacity, view.num_edges());
+        for (node_id, node) in lattice.lattice.iter().enumerate() {
+            assert_eq!(
+                node.forwards.iter().cloned().collect::<Vec<_>>(),
+                view.forwards(node_id).collect::<Vec<_>>()
+            );
+            assert_eq!(
+                node.backwards.iter().cloned().collect::<Vec<_>>(),
+                view.backwards(node_id).collect::<Vec<_>>()
+            );
+            assert_eq!(node.forward_main, view.forward_main(node_id));
+            assert_eq!(node.backward_main, view.backward_main(node_id));
+            assert_eq!(
+                lattice.trunk.get(&node_id).cloned(),
+                view.trunk_position(node_id)
+            );
+        }
+        assert_eq!(lattice, view.to_lattice());
+        assert_eq!(lattice, Lattice::new_from_bytes(&bytes));
+        assert_eq!(lattice.get_trunk_spans(), view.get_trunk_spans());
+        let index = lattice.dump_for_search_index();
+        let view_index = view.dump_for_search_index();
+        assert_eq!(index.len(), view_index.len());
+        for (node, view_node) in index.iter().zip(&view_index) {
+            assert_eq!(
+                (node.text, node.offset, node.increment, node.length),
+                (
+                    view_node.text,
+                    view_node.offset,
+                    view_node.increment,
+                    view_node.length
+                )
+            );
+        }
+
+        let kmp = LatticeKMP::new(vec!["幹", "細胞"]);
+        let results = kmp.search(&lattice);
+        assert!(!results.is_empty());
+        assert_eq!(results, kmp.search(&view));
+        for path in results {
+            assert_eq!(
+                lattice.get_trunk_span(path.clone()),
+                view.get_trunk_span(path)
+            );
+        }
+    }
+}
+
+#[test]
+fn lattice_view_error_test() {
+    let parattice = PaRattice::new(get_paradict());
+    let words = vec!["造血", "幹", "細胞", "移植"];
+    let lattice = parattice.get_lattice(&words, true, 2);
+
+    let bytes = lattice.to_bytes();
+    match LatticeView::new(&bytes) {
+        Err(DecodeError::UnsupportedFlags(flags)) => assert_eq!(FLAG_COMPACT, flags),
+        _ => panic!(),
+    }
+
+    let bytes = to_indexed_bytes(&lattice);
+    let mut broken = bytes.clone();
+    broken[40] ^= 1;
+    match LatticeView::new(&broken) {
+        Err(DecodeError::ChecksumMismatch { .. }) => {}
+        _ => panic!(),
+    }
+
+    // the section starts after the header of 24 bytes and its length of 8 bytes
+    let u32_at = |bytes: &[u8], i: usize| {
+        u32::from_le_bytes([
+            bytes[32 + i * 4],
+            bytes[32 + i * 4 + 1],
+            bytes[32 + i * 4 + 2],
+            bytes[32 + i * 4 + 3],
+        ]) as usize
+    };
+    let num_labels = u32_at(&bytes, 0);
+    let num_nodes = u32_at(&bytes, 1);
+    let forward_edges = 4 + num_labels + 1 + num_nodes + 1;
+    let num_edges = u32_at(&bytes, 2);
+    let backward_edges = forward_edges + num_edges * 2 + num_nodes + 1;
+    let backward_main = backward_edges + num_edges * 2 + num_nodes;
+    let break_u32s = |changes: &[(usize, u32)]| {
+        let mut broken = bytes.clone();
+        for &(i, x) in changes {
+            broken[32 + i * 4..32 + i * 4 + 4].copy_from_slice(&x.to_le_bytes());
+        }
+        fix_checksum(&mut broken);
+        broken
+    };
+    let break_u32 = |i: usize, x: u32| break_u32s(&[(i, x)]);
+    assert_eq!(
+        Err(DecodeError::LabelOutOfRange {
+            node: 0,
+            label: num_labels,
+        }),
+        LatticeView::new(&break_u32(forward_edges, num_labels as u32)).map(|_| ())
+    );
+    assert_eq!(
+        Err(DecodeError::NodeOutOfRange {
+            node: 0,
+            target: num_nodes,
+        }),
+        LatticeView::new(&break_u32(forward_edges + 1, num_nodes as u32)).map(|_| ())
+    );
+    assert_eq!(
+        Err(DecodeError::InvalidSection(FLAG_INDEXED)),
+        LatticeView::new(&break_u32(4, 1)).map(|_| ())
+    );
+    assert_eq!(
+        Err(DecodeError::InvalidSection(FLAG_INDEXED)),
+        Lattice::try_from_bytes(&break_u32(4, 1)).map(|_| ())
+    );
+
+    // main edges of a node off the trunk must lead to the trunk
+    let node = (0..num_nodes)
+        .find(|node_id| !lattice.trunk.contains_key(node_id))
+        .unwrap();
+    assert_eq!(
+        Err(DecodeError::InvalidMainEdge { node }),
+        LatticeView::new(&break_u32(backward_main + node, u32::MAX)).map(|_| ())
+    );
+    // a backward main edge to the node itself
+    let edge_id = u32_at(&bytes, backward_main + node);
+    assert_eq!(
+        Err(DecodeError::InvalidMainEdge { node }),
+        LatticeView::new(&break_u32(backward_edges + edge_id * 2 + 1, node as u32)).map(|_| ())
+    );
+}
@@ -0,0 +1,129 @@
+extern crate parattice;
+
+use parattice::error::ParseError;
+use parattice::lattice::GenericLattice;
+use parattice::openfst::OpenFstTrunk;
+use parattice::Lattice;
+use parattice::PaRattice;
+
+#[test]
+fn openfst_test() {
+    let paradict = vec![
+        vec![
+            vec!["blood", "stem", "cell"],
+            vec!["造血", "幹", "細胞"],
+            vec!["hematopoietic", "stem", "cell"],
+        ],
+        vec![
+            vec!["造血", "幹", "細胞", "移植"],
+            vec!["hematopoietic", "stem", "cell", "transplantation"],
+        ],
+        vec![vec!["stem", "cell"], vec!["幹", "細胞"]],
+        vec![
+            vec!["幹", "細胞", "移植"],
+            vec!["rescue", "transplant"],
+            vec!["stem", "cell", "rescue"],
+        ],
+        vec![vec!["rescue"], vec!["救命"]],
+        vec![vec!["blood"], vec!["血液"]],
+    ];
+    let parattice = PaRattice::new(paradict);
+    let words = vec!["造血", "幹", "細胞", "移植"];
+    let lattice = parattice.get_lattice(&words, true, 10);
+    let is_trunk_edge =
+        |i: usize, j: usize| lattice.trunk.contains_key(&i) && lattice.trunk.contains_key(&j);
+
+    // trunk by weights
+    let (fst, symbols) =
+        lattice.dump_openfst_weighted(|_, i, j| if is_trunk_edge(i, j) { 0.0 } else { 1.0 });
+    assert_eq!(lattice.capacity + 1, fst.lines().count());
+    let (new_lattice, weights) =
+        Lattice::from_openfst(&fst, &symbols, OpenFstTrunk::BestPath).unwrap();
+    assert_eq!(lattice.canonicalize(), new_lattice.canonicalize());
+    assert_eq!(lattice.capacity, weights.len());
+    for (&(i, _, j), &weight) in &weights {
+        let is_new_trunk_edge =
+            new_lattice.trunk.contains_key(&i) && new_lattice.trunk.contains_key(&j);
+        assert_eq!(is_new_trunk_edge, weight == 0.0);
+    }
+
+    // explicit trunk
+    let (fst, symbols) = lattice.dump_openfst();
+    let mut trunk: Vec<(usize, usize)> = lattice.trunk.iter().map(|(&k, &v)| (v, k)).collect();
+    trunk.sort();
+    let trunk = trunk.into_iter().map(|(_, node_id)| node_id).collect();
+    let (new_lattice, _) =
+        Lattice::from_openfst(&fst, &symbols, OpenFstTrunk::States(trunk)).unwrap();
+    assert_eq!(lattice.canonicalize(), new_lattice.canonicalize());
+}
+
+#[test]
+fn openfst_symbols_test() {
+    let paradict = vec![vec![
+        vec!["<eps>"],
+        vec!["stem cell"],
+        vec!["a\\b"],
+        vec!["a\u{3000}b"],
+    ]];
+    let parattice = PaRattice::new(paradict);
+    let lattice = parattice.get_lattice(&["<eps>"], true, 10);
+    let (fst, symbols) = lattice.dump_openfst();
+    assert_eq!(
+        "<eps>\t0\n\\<eps>\t1\na\\\\b\t2\na\\u{3000}b\t3\nstem\\scell\t4\n",
+        symbols
+    );
+    assert_eq!(
+        Err(ParseError {
+            line: 2,
+            message: "escaped symbols need owned labels".to_string()
+        }),
+        Lattice::from_openfst(&fst, &symbols, OpenFstTrunk::BestPath).map(|_| ())
+    );
+    let trunk = OpenFstTrunk::States(vec![0, 1]);
+    let (new_lattice, weights) = GenericLattice::from_openfst_owned(&fst, &symbols, trunk).unwrap();
+    assert_eq!(lattice, new_lattice.to_lattice());
+    assert_eq!(lattice.capacity, weights.len());
+    assert!(weights.contains_key(&(0, "a\\b".to_string(), 1)));
+}
+
+#[test]
+fn openfst_error_test() {
+    let symbols = "<eps> 0\na 1\nb 2\n";
+    assert_eq!(
+        Err(ParseError {
+            line: 2,
+            message: "epsilon transitions are not supported".to_string()
+        }),
+        Lattice::from_openfst("0 1 1\n1 2 0\n2\n", symbols, OpenFstTrunk::BestPath).map(|_| ())
+    );
+    assert_eq!(
+        Err(ParseError {
+            line: 1,
+            message: "unknown symbol ID".to_string()
+        }),
+        Lattice::from_openfst("0 1 3\n1\n", symbols, OpenFstTrunk::BestPath).map(|_| ())
+    );
+    assert_eq!(
+        Err(ParseError {
+            line: 0,
+            message: "cycles are not supported".to_string()
+        }),
+        Lattice::from_openfst("0 1 1\n1 0 2\n1 2 2\n2\n", symbols, OpenFstTrunk::BestPath)
+            .map(|_| ())
+    );
+    assert_eq!(
+        Err(ParseError {
+            line: 0,
+            message: "exactly one final state is required".to_string()
+        }),
+        Lattice::from_openfst("0 1 1\n0 2 2\n1\n2\n", symbols, OpenFstTrunk::BestPath).map(|_| ())
+    );
+    assert_eq!(
+        Err(ParseError {
+            line: 2,
+            message: "<eps> is reserved for the symbol 0".to_string()
+        }),
+        Lattice::from_openfst("0 1 1\n1\n", "<eps> 0\n<eps> 1\n", OpenFstTrunk::BestPath)
+            .map(|_| ())
+    );
+}
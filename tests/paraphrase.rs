@@ -0,0 +1,67 @@
+extern crate parattice;
+
+use parattice::paraphrase::AlignedEdge;
+use parattice::paraphrase::ParaphraseOptions;
+use parattice::PaRattice;
+
+#[test]
+fn are_paraphrases_test() {
+    let paradict = vec![
+        vec![
+            vec!["blood", "stem", "cell"],
+            vec!["造血", "幹", "細胞"],
+            vec!["hematopoietic", "stem", "cell"],
+        ],
+        vec![
+            vec!["造血", "幹", "細胞", "移植"],
+            vec!["hematopoietic", "stem", "cell", "transplantation"],
+        ],
+        vec![vec!["stem", "cell"], vec!["幹", "細胞"]],
+        vec![
+            vec!["幹", "細胞", "移植"],
+            vec!["rescue", "transplant"],
+            vec!["stem", "cell", "rescue"],
+        ],
+        vec![vec!["rescue"], vec!["救命"]],
+        vec![vec!["blood"], vec!["血液"]],
+    ];
+    let parattice = PaRattice::new(paradict);
+    let options = ParaphraseOptions {
+        shrink: true,
+        max_depth: 10,
+    };
+    let a = vec!["造血", "幹", "細胞", "移植"];
+    let b = vec!["血液", "rescue", "transplant"];
+    let result = parattice.are_paraphrases(&a, &b, &options);
+    assert_eq!(1.0, result.score);
+    assert_eq!(
+        vec![
+            AlignedEdge {
+                text: "造血",
+                offset_a: (0, 1),
+                offset_b: (0, 3),
+            },
+            AlignedEdge {
+                text: "幹",
+                offset_a: (1, 2),
+                offset_b: (0, 3),
+            },
+            AlignedEdge {
+                text: "細胞",
+                offset_a: (2, 4),
+                offset_b: (0, 3),
+            },
+            AlignedEdge {
+                text: "rescue",
+                offset_a: (1, 4),
+                offset_b: (1, 3),
+            },
+        ],
+        result.witness.unwrap()
+    );
+
+    let c = vec!["造血", "幹", "細胞", "の", "移植"];
+    let result = parattice.are_paraphrases(&a, &c, &options);
+    assert!(result.witness.is_none());
+    assert_eq!(0.8, result.score);
+}
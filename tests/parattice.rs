@@ -1,9 +1,13 @@
 extern crate parattice;
 
+use parattice::error::DecodeError;
 use parattice::Lattice;
+use parattice::LatticeKMP;
 use parattice::PaRattice;
 use parattice::SearchIndexNode;
 
+use std::collections::HashMap;
+
 #[test]
 fn dump_for_search_index_test() {
     let paradict = vec![
@@ -96,6 +100,94 @@ fn serialize_test() {
     assert_eq!(lattice, lattice_from_bytes);
 }
 
+fn usize_to_vec(x: usize) -> Vec<u8> {
+    (0..8).map(|i| (x >> (i * 8)) as u8).collect()
+}
+
+fn edge_to_vec(s: &[u8], target: usize) -> Vec<u8> {
+    let mut result = usize_to_vec(s.len());
+    result.extend(s);
+    result.extend(usize_to_vec(target));
+    result
+}
+
+type RawNode<'a> = (Vec<(&'a [u8], usize)>, Vec<(&'a [u8], usize)>);
+
+fn encode(num_nodes: usize, nodes: &[RawNode]) -> Vec<u8> {
+    let mut result = usize_to_vec(num_nodes);
+    for (forwards, backwards) in nodes {
+        result.extend(usize_to_vec(forwards.len()));
+        result.extend(usize_to_vec(backwards.len()));
+        for &(s, target) in forwards.iter().chain(backwards) {
+            result.extend(edge_to_vec(s, target));
+        }
+    }
+    result
+}
+
+#[test]
+fn try_from_bytes_test() {
+    // 0 -a-> 1 -b-> 2
+    let nodes: Vec<RawNode> = vec![
+        (vec![(&b"a"[..], 1)], vec![]),
+        (vec![(&b"b"[..], 2)], vec![(&b"a"[..], 0)]),
+        (vec![], vec![(&b"b"[..], 1)]),
+    ];
+    let bytes = encode(3, &nodes);
+    let lattice = Lattice::try_from_bytes(&bytes).unwrap();
+    assert_eq!(lattice.to_legacy_bytes(), bytes);
+
+    // every prefix is truncated
+    for length in 1..bytes.len() {
+        match Lattice::try_from_bytes(&bytes[..length]) {
+            Err(DecodeError::Truncated { .. }) | Err(DecodeError::NodeCountMismatch { .. }) => {}
+            result => panic!(
+                "unexpected result for length {}: {:?}",
+                length,
+                result.err()
+            ),
+        }
+    }
+    assert_eq!(
+        Err(DecodeError::Truncated { offset: 0 }),
+        Lattice::try_from_bytes(&bytes[..7])
+    );
+
+    let mut broken_nodes = nodes.clone();
+    broken_nodes[1].0[0].0 = &[0xff, 0xfe];
+    let broken_bytes = encode(3, &broken_nodes);
+    assert_eq!(
+        Err(DecodeError::InvalidUtf8 { offset: 65 }),
+        Lattice::try_from_bytes(&broken_bytes)
+    );
+
+    let mut broken_nodes = nodes.clone();
+    broken_nodes[1].0[0].1 = 3;
+    assert_eq!(
+        Err(DecodeError::NodeOutOfRange { node: 1, target: 3 }),
+        Lattice::try_from_bytes(&encode(3, &broken_nodes))
+    );
+
+    assert_eq!(
+        Err(DecodeError::NodeCountMismatch {
+            expected: usize::MAX,
+            actual: 3,
+        }),
+        Lattice::try_from_bytes(&encode(usize::MAX, &nodes))
+    );
+
+    let mut broken_nodes = nodes.clone();
+    broken_nodes[1].0.clear();
+    assert_eq!(
+        Err(DecodeError::MissingTrunk),
+        Lattice::try_from_bytes(&encode(3, &broken_nodes))
+    );
+    assert_eq!(
+        Err(DecodeError::MissingTrunk),
+        Lattice::try_from_bytes(&encode(0, &[]))
+    );
+}
+
 fn search_index_relative_to_absolute<'a>(
     data: &'a Vec<SearchIndexNode>,
 ) -> Vec<(&'a str, usize, usize, usize, usize)> {
@@ -207,3 +299,120 @@ fn max_depth_test() {
     expected.sort();
     assert_eq!(expected, search_index_relative_to_absolute(&index_data));
 }
+
+#[test]
+fn token_id_test() {
+    let paradict = vec![
+        vec![
+            vec!["blood", "stem", "cell"],
+            vec!["造血", "幹", "細胞"],
+            vec!["hematopoietic", "stem", "cell"],
+        ],
+        vec![
+            vec!["造血", "幹", "細胞", "移植"],
+            vec!["hematopoietic", "stem", "cell", "transplantation"],
+        ],
+        vec![vec!["stem", "cell"], vec!["幹", "細胞"]],
+        vec![
+            vec!["幹", "細胞", "移植"],
+            vec!["rescue", "transplant"],
+            vec!["stem", "cell", "rescue"],
+        ],
+        vec![vec!["rescue"], vec!["救命"]],
+        vec![vec!["blood"], vec!["血液"]],
+    ];
+    let mut vocab: HashMap<&str, u32> = HashMap::new();
+    let id_paradict: Vec<Vec<Vec<u32>>> = paradict
+        .iter()
+        .map(|group| {
+            group
+                .iter()
+                .map(|phrase| {
+                    phrase
+                        .iter()
+                        .map(|&word| {
+                            let id = vocab.len() as u32 + 1;
+                            *vocab.entry(word).or_insert(id)
+                        })
+                        .collect()
+                })
+                .collect()
+        })
+        .collect();
+    let words = vec!["造血", "幹", "細胞", "移植"];
+    let id_words: Vec<u32> = words.iter().map(|word| vocab[word]).collect();
+
+    let parattice = PaRattice::new(paradict);
+    let lattice = parattice.get_lattice(&words, true, 10);
+    let id_parattice = PaRattice::new(id_paradict);
+    let id_lattice = id_parattice.get_lattice(&id_words, true, 10);
+
+    // token IDs are numbered in order of appearance, so node IDs can differ
+    assert_eq!(lattice.lattice.len(), id_lattice.lattice.len());
+    assert_eq!(lattice.capacity, id_lattice.capacity);
+    assert_eq!(lattice.trunk.len(), id_lattice.trunk.len());
+    let get_paths = |kmp_results: Vec<Vec<(u32, usize)>>| {
+        let mut paths: Vec<Vec<u32>> = kmp_results
+            .into_iter()
+            .map(|path| path[1..].iter().map(|&(s, _)| s).collect())
+            .collect();
+        paths.sort();
+        paths
+    };
+    let kmp = LatticeKMP::new(vec!["stem", "cell"]);
+    let results: Vec<Vec<(u32, usize)>> = kmp
+        .search(&lattice)
+        .into_iter()
+        .map(|path| {
+            path.into_iter()
+                .map(|(s, i)| (vocab.get(s).cloned().unwrap_or(0), i))
+                .collect()
+        })
+        .collect();
+    let id_kmp = LatticeKMP::new(vec![vocab["stem"], vocab["cell"]]);
+    let id_results = id_kmp.search(&id_lattice);
+    assert_eq!(get_paths(results), get_paths(id_results.clone()));
+    for path in id_results {
+        let trunk_span = id_lattice.get_trunk_span(path);
+        assert_eq!(0, trunk_span[0].0);
+        assert!(id_lattice.trunk.contains_key(&trunk_span[0].1));
+    }
+
+    assert_eq!(id_lattice, id_parattice.get_lattice(&id_words, true, 10));
+    assert_eq!(
+        format!(
+            "Lattice {{ nodes: {}, edges: {}, trunk: {:?} }}",
+            id_lattice.lattice.len(),
+            id_lattice.capacity,
+            id_words
+        ),
+        format!("{:?}", id_lattice)
+    );
+
+    // operations that don't look at label contents
+    assert!(id_lattice.validate().is_ok());
+    assert_eq!(
+        lattice.n_best(100, |_, _, _| 1.0).len(),
+        id_lattice.n_best(100, |_, _, _| 1.0).len()
+    );
+    assert_eq!(
+        id_lattice.canonicalize(),
+        id_lattice.union(&id_lattice).canonicalize()
+    );
+    assert_eq!(
+        lattice.slice(1, 3).0.capacity,
+        id_lattice.slice(1, 3).0.capacity
+    );
+    let mut edited = parattice.get_lattice(&words, true, 10);
+    let mut id_edited = id_parattice.get_lattice(&id_words, true, 10);
+    edited.retain_edges(|s, _, _, _| s != "blood");
+    id_edited.retain_edges(|s, _, _, _| s != vocab["blood"]);
+    assert_eq!(edited.capacity, id_edited.capacity);
+    id_edited.add_branch(0, 2, &[vocab["blood"]]).unwrap();
+    assert_eq!(edited.capacity + 1, id_edited.capacity);
+    let id_texts: Vec<String> = id_words.iter().map(|id| id.to_string()).collect();
+    assert_eq!(
+        id_texts.join(" "),
+        id_lattice.dump_ascii().lines().next().unwrap()
+    );
+}
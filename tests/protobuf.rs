@@ -0,0 +1,251 @@
+#![cfg(feature = "protobuf")]
+
+extern crate parattice;
+
+mod common;
+
+use common::get_paradict;
+
+use parattice::error::DecodeError;
+use parattice::protobuf;
+use parattice::protobuf::Message;
+use parattice::Lattice;
+use parattice::PaRattice;
+use parattice::SearchIndexNode;
+
+#[test]
+fn protobuf_lattice_test() {
+    let parattice = PaRattice::new(get_paradict());
+    let words = vec!["造血", "幹", "細胞", "移植"];
+    for &shrink in &[true, false] {
+        let lattice = parattice.get_lattice(&words, shrink, 10);
+        let mut message = lattice.to_protobuf();
+        assert_eq!(lattice.lattice.len(), message.nodes.len());
+        assert_eq!(lattice.trunk.len(), message.trunk.len());
+        for (node, node_message) in lattice.lattice.iter().zip(&message.nodes) {
+            let main: Vec<_> = node_message
+                .forwards
+                .iter()
+                .filter(|edge| edge.main)
+                .map(|edge| (edge.label.as_str(), edge.target as usize))
+                .collect();
+            assert_eq!(node.forward_main.into_iter().collect::<Vec<_>>(), main);
+        }
+        message
+            .metadata
+            .insert("document".to_string(), "doc1".to_string());
+
+        let bytes = message.encode_to_vec();
+        let new_message = protobuf::Lattice::decode(&bytes[..]).unwrap();
+        assert_eq!(message, new_message);
+        let new_lattice = Lattice::try_from_protobuf(&new_message).unwrap();
+        assert_eq!(lattice, new_lattice);
+        for (node, new_node) in lattice.lattice.iter().zip(&new_lattice.lattice) {
+            assert_eq!(node.depth, new_node.depth);
+        }
+
+        // the trunk is optional
+        let mut message = lattice.to_protobuf();
+        message.trunk.clear();
+        assert_eq!(lattice, Lattice::try_from_protobuf(&message).unwrap());
+    }
+}
+
+#[test]
+fn protobuf_lattice_error_test() {
+    let parattice = PaRattice::new(get_paradict());
+    let words = vec!["造血", "幹", "細胞", "移植"];
+    let lattice = parattice.get_lattice(&words, true, 2);
+    let message = lattice.to_protobuf();
+
+    let mut broken = message.clone();
+    broken.nodes[0].forwards[0].target = 100;
+    assert_eq!(
+        Err(DecodeError::NodeOutOfRange {
+            node: 0,
+            target: 100
+        }),
+        Lattice::try_from_protobuf(&broken)
+    );
+
+    let mut broken = message.clone();
+    for edge in &mut broken.nodes[0].forwards {
+        edge.main = true;
+    }
+    assert_eq!(
+        Err(DecodeError::InvalidMainEdge { node: 0 }),
+        Lattice::try_from_protobuf(&broken)
+    );
+
+    let mut broken = message.clone();
+    broken.trunk.swap(1, 2);
+    assert_eq!(
+        Err(DecodeError::TrunkMismatch),
+        Lattice::try_from_protobuf(&broken)
+    );
+
+    let mut broken = message;
+    broken.nodes.truncate(0);
+    broken.trunk.clear();
+    assert_eq!(
+        Err(DecodeError::MissingTrunk),
+        Lattice::try_from_protobuf(&broken)
+    );
+}
+
+#[test]
+fn protobuf_invalid_main_edge_test() {
+    // 0 -> 3 is the trunk, and 0 -> 1 -> 2 -> 3 has no main edges on the nodes 1 and 2
+    let edge = |label: &str, target, main| protobuf::Edge {
+        label: label.to_string(),
+        target,
+        main,
+    };
+    let message = protobuf::Lattice {
+        nodes: vec![
+            protobuf::Node {
+                forwards: vec![edge("a", 3, true), edge("b", 1, false)],
+                backwards: vec![],
+                depth: 0,
+            },
+            protobuf::Node {
+                forwards: vec![edge("c", 2, false)],
+                backwards: vec![edge("b", 0, false)],
+                depth: 1,
+            },
+            protobuf::Node {
+                forwards: vec![edge("d", 3, false)],
+                backwards: vec![edge("c", 1, false)],
+                depth: 1,
+            },
+            protobuf::Node {
+                forwards: vec![],
+                backwards: vec![edge("a", 0, true), edge("d", 2, false)],
+                depth: 0,
+            },
+        ],
+        trunk: vec![0, 3],
+        metadata: Default::default(),
+    };
+    match Lattice::try_from_protobuf(&message) {
+        Err(DecodeError::InvalidMainEdge { .. }) => {}
+        _ => panic!(),
+    }
+}
+
+#[test]
+fn protobuf_search_index_test() {
+    let parattice = PaRattice::new(get_paradict());
+    let words = vec!["造血", "幹", "細胞", "移植"];
+    let lattice = parattice.get_lattice(&words, true, 2);
+    let index = lattice.dump_for_search_index();
+
+    let message = protobuf::SearchIndex::from(&index[..]);
+    let bytes = message.encode_to_vec();
+    let new_message = protobuf::SearchIndex::decode(&bytes[..]).unwrap();
+    assert_eq!(message, new_message);
+    let new_index: Vec<SearchIndexNode> = new_message.nodes.iter().map(From::from).collect();
+    assert_eq!(index.len(), new_index.len());
+    for (node, new_node) in index.iter().zip(&new_index) {
+        assert_eq!(
+            (node.text, node.offset, node.increment, node.length),
+            (
+                new_node.text,
+                new_node.offset,
+                new_node.increment,
+                new_node.length
+            )
+        );
+    }
+}
+
+// wire bytes encoded by hand after proto/parattice.proto, with each field given once
+#[test]
+fn protobuf_wire_format_test() {
+    // Edge { label = 1: "a", target = 2: 2, main = 3: true }
+    let edge_bytes = vec![0x0a, 1, b'a', 0x10, 2, 0x18, 1];
+    let edge = protobuf::Edge {
+        label: "a".to_string(),
+        target: 2,
+        main: true,
+    };
+    assert_eq!(edge, protobuf::Edge::decode(&edge_bytes[..]).unwrap());
+    assert_eq!(edge_bytes, edge.encode_to_vec());
+
+    // Node { forwards = 1: [edge], backwards = 2: [edge], depth = 3: 3 }
+    let mut node_bytes = vec![0x0a, 7];
+    node_bytes.extend(&edge_bytes);
+    node_bytes.extend(&[0x12, 7]);
+    node_bytes.extend(&edge_bytes);
+    node_bytes.extend(&[0x18, 3]);
+    let node = protobuf::Node {
+        forwards: vec![edge.clone()],
+        backwards: vec![edge],
+        depth: 3,
+    };
+    assert_eq!(node, protobuf::Node::decode(&node_bytes[..]).unwrap());
+    assert_eq!(node_bytes, node.encode_to_vec());
+
+    // Lattice { nodes = 1: [node], trunk = 2: [0, 1] (packed), metadata = 3: {"k": "v"} }
+    let metadata_bytes = vec![0x0a, 1, b'k', 0x12, 1, b'v'];
+    let mut lattice_bytes = vec![0x0a, node_bytes.len() as u8];
+    lattice_bytes.extend(&node_bytes);
+    lattice_bytes.extend(&[0x12, 2, 0, 1]);
+    lattice_bytes.extend(&[0x1a, 6]);
+    lattice_bytes.extend(&metadata_bytes);
+    let mut lattice = protobuf::Lattice {
+        nodes: vec![node],
+        trunk: vec![0, 1],
+        ..protobuf::Lattice::default()
+    };
+    lattice.metadata.insert("k".to_string(), "v".to_string());
+    assert_eq!(
+        lattice,
+        protobuf::Lattice::decode(&lattice_bytes[..]).unwrap()
+    );
+    assert_eq!(lattice_bytes, lattice.encode_to_vec());
+    // unpacked repeated fields are accepted as well
+    let mut unpacked_bytes = vec![0x0a, node_bytes.len() as u8];
+    unpacked_bytes.extend(&node_bytes);
+    unpacked_bytes.extend(&[0x10, 0, 0x10, 1]);
+    unpacked_bytes.extend(&[0x1a, 6]);
+    unpacked_bytes.extend(&metadata_bytes);
+    assert_eq!(
+        lattice,
+        protobuf::Lattice::decode(&unpacked_bytes[..]).unwrap()
+    );
+
+    // SearchIndexNode { text = 1: "t", offset_start = 2: 1, offset_end = 3: 2, increment = 4: 3,
+    // length = 5: 4 }
+    let search_index_node_bytes = vec![0x0a, 1, b't', 0x10, 1, 0x18, 2, 0x20, 3, 0x28, 4];
+    let search_index_node = protobuf::SearchIndexNode {
+        text: "t".to_string(),
+        offset_start: 1,
+        offset_end: 2,
+        increment: 3,
+        length: 4,
+    };
+    assert_eq!(
+        search_index_node,
+        protobuf::SearchIndexNode::decode(&search_index_node_bytes[..]).unwrap()
+    );
+    assert_eq!(search_index_node_bytes, search_index_node.encode_to_vec());
+
+    // SearchIndex { nodes = 1: [search_index_node], metadata = 2: {"k": "v"} }
+    let mut search_index_bytes = vec![0x0a, search_index_node_bytes.len() as u8];
+    search_index_bytes.extend(&search_index_node_bytes);
+    search_index_bytes.extend(&[0x12, 6]);
+    search_index_bytes.extend(&metadata_bytes);
+    let mut search_index = protobuf::SearchIndex {
+        nodes: vec![search_index_node],
+        ..protobuf::SearchIndex::default()
+    };
+    search_index
+        .metadata
+        .insert("k".to_string(), "v".to_string());
+    assert_eq!(
+        search_index,
+        protobuf::SearchIndex::decode(&search_index_bytes[..]).unwrap()
+    );
+    assert_eq!(search_index_bytes, search_index.encode_to_vec());
+}
@@ -0,0 +1,86 @@
+#![cfg(feature = "serde")]
+
+extern crate parattice;
+
+use parattice::lattice::GenericLattice;
+use parattice::Lattice;
+use parattice::PaRattice;
+use parattice::SearchIndexNode;
+
+#[test]
+fn serde_json_test() {
+    let paradict = vec![
+        vec![
+            vec!["blood", "stem", "cell"],
+            vec!["造血", "幹", "細胞"],
+            vec!["hematopoietic", "stem", "cell"],
+        ],
+        vec![
+            vec!["造血", "幹", "細胞", "移植"],
+            vec!["hematopoietic", "stem", "cell", "transplantation"],
+        ],
+        vec![vec!["stem", "cell"], vec!["幹", "細胞"]],
+        vec![
+            vec!["幹", "細胞", "移植"],
+            vec!["rescue", "transplant"],
+            vec!["stem", "cell", "rescue"],
+        ],
+        vec![vec!["rescue"], vec!["救命"]],
+        vec![vec!["blood"], vec!["血液"]],
+    ];
+    let parattice = PaRattice::new(paradict);
+    let words = vec!["造血", "幹", "細胞", "移植"];
+    let lattice = parattice.get_lattice(&words, true, 10);
+    let json = serde_json::to_string(&lattice).unwrap();
+    let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(serde_json::json!([0, 3, 9, 13, 16]), value["trunk"]);
+    assert_eq!(
+        serde_json::json!(["造血", 3]),
+        value["nodes"][0]["forward_main"]
+    );
+    assert_eq!(serde_json::json!(null), value["nodes"][0]["backward_main"]);
+    assert_eq!(serde_json::json!(32), value["capacity"]);
+    let new_lattice: Lattice = serde_json::from_str(&json).unwrap();
+    assert_eq!(lattice, new_lattice);
+
+    // escaped labels, e.g. written by Python with ensure_ascii, need owned labels
+    let json = r#"{"nodes":[{"forwards":[["\u9020\u8840",1]],"backwards":[],"forward_main":["\u9020\u8840",1],"backward_main":null,"depth":0},{"forwards":[],"backwards":[["\u9020\u8840",0]],"forward_main":null,"backward_main":["\u9020\u8840",0],"depth":0}],"trunk":[0,1],"capacity":1}"#;
+    assert!(serde_json::from_str::<Lattice>(json).is_err());
+    let owned_lattice: GenericLattice<String> = serde_json::from_str(json).unwrap();
+    let new_lattice = owned_lattice.to_lattice();
+    assert_eq!(Some(("造血", 1)), new_lattice.lattice[0].forward_main);
+    assert_eq!(parattice.get_lattice(&["造血"], true, 10), new_lattice);
+    let owned_lattice: GenericLattice<String> =
+        serde_json::from_str(&serde_json::to_string(&lattice).unwrap()).unwrap();
+    assert_eq!(lattice, owned_lattice.to_lattice());
+
+    // an inconsistent trunk is an error
+    let mut broken = parattice.get_lattice(&words, true, 10);
+    broken.trunk.insert(1, 100);
+    assert!(serde_json::to_string(&broken).is_err());
+
+    // inconsistent lattices are rejected on deserialization
+    let mut value: serde_json::Value = serde_json::to_value(&lattice).unwrap();
+    value["trunk"] = serde_json::json!([0, 3, 9, 16]);
+    assert!(serde_json::from_value::<GenericLattice<String>>(value).is_err());
+    let mut value: serde_json::Value = serde_json::to_value(&lattice).unwrap();
+    value["capacity"] = serde_json::json!(33);
+    assert!(serde_json::from_value::<GenericLattice<String>>(value).is_err());
+    let mut value: serde_json::Value = serde_json::to_value(&lattice).unwrap();
+    value["nodes"][1]["forward_main"] = serde_json::json!(null);
+    assert!(serde_json::from_value::<GenericLattice<String>>(value).is_err());
+    let mut value: serde_json::Value = serde_json::to_value(&lattice).unwrap();
+    value["nodes"][0]["forward_main"] = serde_json::json!(["造血", 100]);
+    assert!(serde_json::from_value::<GenericLattice<String>>(value).is_err());
+    let value: serde_json::Value = serde_json::to_value(&lattice).unwrap();
+    assert!(serde_json::from_value::<GenericLattice<String>>(value).is_ok());
+
+    let index_data = lattice.dump_for_search_index();
+    let json = serde_json::to_string(&index_data[0]).unwrap();
+    assert_eq!(
+        r#"{"text":"blood","offset":[0,3],"increment":1,"length":2}"#,
+        json
+    );
+    let node: SearchIndexNode = serde_json::from_str(&json).unwrap();
+    assert_eq!("blood", node.text);
+}
@@ -0,0 +1,231 @@
+extern crate parattice;
+
+use parattice::error::ParseError;
+use parattice::lattice::GenericLattice;
+use parattice::Lattice;
+use parattice::PaRattice;
+
+#[test]
+fn slf_test() {
+    let paradict = vec![
+        vec![
+            vec!["blood", "stem", "cell"],
+            vec!["造血", "幹", "細胞"],
+            vec!["hematopoietic", "stem", "cell"],
+        ],
+        vec![
+            vec!["造血", "幹", "細胞", "移植"],
+            vec!["hematopoietic", "stem", "cell", "transplantation"],
+        ],
+        vec![vec!["stem", "cell"], vec!["幹", "細胞"]],
+        vec![
+            vec!["幹", "細胞", "移植"],
+            vec!["rescue", "transplant"],
+            vec!["stem", "cell", "rescue"],
+        ],
+        vec![vec!["rescue"], vec!["救命"]],
+        vec![vec!["blood"], vec!["血液"]],
+    ];
+    let parattice = PaRattice::new(paradict);
+    let words = vec!["造血", "幹", "細胞", "移植"];
+    let lattice = parattice.get_lattice(&words, true, 10);
+    let is_trunk_edge =
+        |i: usize, j: usize| lattice.trunk.contains_key(&i) && lattice.trunk.contains_key(&j);
+
+    let slf = lattice.to_slf_weighted(|_, i, j| if is_trunk_edge(i, j) { 0.0 } else { -1.0 });
+    let (new_lattice, scores) = Lattice::from_slf(&slf).unwrap();
+    assert_eq!(lattice.canonicalize(), new_lattice.canonicalize());
+    assert_eq!(lattice.capacity, scores.len());
+    for (&(i, _, j), &score) in &scores {
+        let is_new_trunk_edge =
+            new_lattice.trunk.contains_key(&i) && new_lattice.trunk.contains_key(&j);
+        assert_eq!(is_new_trunk_edge, score == 0.0);
+    }
+
+    // the trunk is not kept without scores
+    let slf = lattice.to_slf();
+    let (new_lattice, _) = Lattice::from_slf(&slf).unwrap();
+    assert_eq!(lattice.lattice.len(), new_lattice.lattice.len());
+    assert_eq!(lattice.capacity, new_lattice.capacity);
+}
+
+#[test]
+fn slf_node_words_test() {
+    // words on nodes, shuffled node IDs and acoustic scores
+    let slf = "VERSION=1.0\n\
+               UTTERANCE=test\n\
+               N=4 L=4\n\
+               I=0 t=0.00\n\
+               I=1 t=0.30 W=幹\n\
+               I=2 t=0.60 W=細胞\n\
+               I=3 t=0.00 W=造血\n\
+               J=0 S=0 E=3 a=-1.0\n\
+               J=1 S=3 E=1 a=-2.0 l=-0.5\n\
+               J=2 S=1 E=2 a=-1.0\n\
+               J=3 S=0 E=2 W=stem_cell a=-6.0\n";
+    let (lattice, scores) = Lattice::from_slf(slf).unwrap();
+    assert_eq!(4, lattice.lattice.len());
+    assert_eq!(4, lattice.capacity);
+    assert_eq!(4, lattice.trunk.len());
+    assert_eq!(Some(("造血", 1)), lattice.lattice[0].forward_main);
+    assert_eq!(Some(&-2.5), scores.get(&(1, "幹", 2)));
+    assert_eq!(Some(&-6.0), scores.get(&(0, "stem_cell", 3)));
+}
+
+#[test]
+fn slf_quoted_words_test() {
+    let paradict = vec![vec![
+        vec!["stem cell"],
+        vec!["!NULL"],
+        vec!["\"x\""],
+        vec!["it's"],
+    ]];
+    let parattice = PaRattice::new(paradict);
+    let lattice = parattice.get_lattice(&["stem cell"], true, 10);
+    let slf = lattice.to_slf();
+    assert!(slf.contains("W=\"stem cell\"\n"));
+    assert!(slf.contains("W=\"!NULL\"\n"));
+    assert!(slf.contains("W='\"x\"'\n"));
+    assert!(slf.contains("W=it's\n"));
+    let (new_lattice, _) = Lattice::from_slf(&slf).unwrap();
+    assert_eq!(lattice.lattice[0].forwards, new_lattice.lattice[0].forwards);
+
+    // words that need escapes are read with owned labels
+    let paradict = vec![vec![
+        vec!["a b"],
+        vec!["\"it's\""],
+        vec!["back\\slash two"],
+        vec!["line\nbreak"],
+    ]];
+    let parattice = PaRattice::new(paradict);
+    let lattice = parattice.get_lattice(&["a b"], true, 10);
+    let slf = lattice.to_slf_weighted(|s, _, _| if s == "a b" { 0.0 } else { -1.0 });
+    assert!(slf.contains("W=\"\\\"it's\\\"\""));
+    assert!(slf.contains("W=\"line\\012break\""));
+    assert_eq!(
+        Err(ParseError {
+            line: 6,
+            message: "escaped words need owned labels".to_string()
+        }),
+        Lattice::from_slf(&slf).map(|_| ())
+    );
+    let (new_lattice, scores) = GenericLattice::from_slf_owned(&slf).unwrap();
+    assert_eq!(lattice, new_lattice.to_lattice());
+    assert_eq!(
+        Some(&-1.0),
+        scores.get(&(0, "back\\slash two".to_string(), 1))
+    );
+
+    // octal escapes are bytes of UTF-8
+    let slf = "N=2 L=1\nJ=0 S=0 E=1 W=\"\\351\\200\\240\\350\\241\\200\"\n";
+    let (new_lattice, _) = GenericLattice::from_slf_owned(slf).unwrap();
+    assert_eq!(
+        Some(("造血".to_string(), 1)),
+        new_lattice.lattice[0].forward_main
+    );
+}
+
+#[test]
+fn slf_null_words_test() {
+    // a typical HTK lattice with words on nodes, and !NULL start and end nodes
+    let slf = "VERSION=1.0\n\
+               UTTERANCE=test\n\
+               lmscale=12.00 wdpenalty=-10.00\n\
+               start=0 end=5\n\
+               N=6 L=7\n\
+               I=0 t=0.00 W=!NULL\n\
+               I=1 t=0.30 W=stem v=1\n\
+               I=2 t=0.30 W=幹 v=1\n\
+               I=3 t=0.60 W=cell v=1\n\
+               I=4 t=0.60 W=細胞 v=1\n\
+               I=5 t=0.70 W=!NULL\n\
+               J=0 S=0 E=1 a=-10.0 l=-1.0\n\
+               J=1 S=0 E=2 a=-12.0 l=-1.0\n\
+               J=2 S=1 E=3 a=-10.0 l=-1.0\n\
+               J=3 S=2 E=4 a=-10.0 l=-1.0\n\
+               J=4 S=1 E=4 a=-15.0 l=-1.0\n\
+               J=5 S=3 E=5 a=-1.0 l=0.0\n\
+               J=6 S=4 E=5 a=-2.0 l=0.0\n";
+    let (lattice, scores) = Lattice::from_slf(slf).unwrap();
+    let mut sentences = vec![];
+    let mut stack = vec![(0, vec![])];
+    while let Some((node_id, sentence)) = stack.pop() {
+        if node_id == lattice.lattice.len() - 1 {
+            sentences.push(sentence);
+            continue;
+        }
+        for &(s, target) in &lattice.lattice[node_id].forwards {
+            let mut new_sentence = sentence.clone();
+            new_sentence.push(s);
+            stack.push((target, new_sentence));
+        }
+    }
+    sentences.sort();
+    assert_eq!(
+        vec![
+            vec!["stem", "cell"],
+            vec!["stem", "細胞"],
+            vec!["幹", "細胞"]
+        ],
+        sentences
+    );
+    assert_eq!(5, lattice.capacity);
+    assert_eq!(3, lattice.trunk.len());
+    assert_eq!(Some(("stem", 1)), lattice.lattice[0].forward_main);
+    // scores of !NULL links are added to the words before them
+    let (_, cell) = lattice.lattice[1].forward_main.unwrap();
+    assert_eq!(Some(&-12.0), scores.get(&(1, "cell", cell)));
+
+    // !NULL links in the middle of paths
+    let slf = "N=4 L=3\nJ=0 S=0 E=1 W=a\nJ=1 S=1 E=2 W=!NULL l=-1.0\nJ=2 S=2 E=3 W=b\n";
+    let (lattice, scores) = Lattice::from_slf(slf).unwrap();
+    assert_eq!(3, lattice.lattice.len());
+    assert_eq!(Some(("a", 1)), lattice.lattice[0].forward_main);
+    assert_eq!(Some(&-1.0), scores.get(&(0, "a", 1)));
+}
+
+#[test]
+fn slf_error_test() {
+    assert_eq!(
+        Err(ParseError {
+            line: 0,
+            message: "the end node is not reachable".to_string()
+        }),
+        Lattice::from_slf("N=2 L=1\nI=0\nI=1\nJ=0 S=0 E=1 W=!NULL\n").map(|_| ())
+    );
+    assert_eq!(
+        Err(ParseError {
+            line: 4,
+            message: "a link without a word".to_string()
+        }),
+        Lattice::from_slf("N=2 L=1\nI=0\nI=1\nJ=0 S=0 E=1\n").map(|_| ())
+    );
+    assert_eq!(
+        Err(ParseError {
+            line: 2,
+            message: "node ID out of range".to_string()
+        }),
+        Lattice::from_slf("N=2 L=1\nJ=0 S=0 E=2 W=a\n").map(|_| ())
+    );
+    assert_eq!(
+        Err(ParseError {
+            line: 0,
+            message: "the end node is not unique".to_string()
+        }),
+        Lattice::from_slf("N=3 L=2\nJ=0 S=0 E=1 W=a\nJ=1 S=0 E=2 W=b\n").map(|_| ())
+    );
+    assert_eq!(
+        Err(ParseError {
+            line: 1,
+            message: "N is too large".to_string()
+        }),
+        Lattice::from_slf("N=1000000000000 L=1\nJ=0 S=0 E=1 W=a\n").map(|_| ())
+    );
+    assert_eq!(
+        Err(ParseError {
+            line: 2,
+            message: "an unterminated quote".to_string()
+        }),
+        Lattice::from_slf("N=2 L=1\nJ=0 S=0 E=1 W=\"a\n").map(|_| ())
+    );
+}
@@ -0,0 +1,67 @@
+extern crate parattice;
+
+use parattice::svg::SvgOptions;
+use parattice::LatticeKMP;
+use parattice::PaRattice;
+
+#[test]
+fn dump_svg_test() {
+    let paradict = vec![
+        vec![
+            vec!["blood", "stem", "cell"],
+            vec!["造血", "幹", "細胞"],
+            vec!["hematopoietic", "stem", "cell"],
+        ],
+        vec![
+            vec!["造血", "幹", "細胞", "移植"],
+            vec!["hematopoietic", "stem", "cell", "transplantation"],
+        ],
+        vec![vec!["stem", "cell"], vec!["幹", "細胞"]],
+        vec![
+            vec!["幹", "細胞", "移植"],
+            vec!["rescue", "transplant"],
+            vec!["stem", "cell", "rescue"],
+        ],
+        vec![vec!["rescue"], vec!["救命"]],
+        vec![vec!["blood"], vec!["血液"]],
+    ];
+    let parattice = PaRattice::new(paradict);
+    let words = vec!["造血", "幹", "細胞", "移植"];
+    let lattice = parattice.get_lattice(&words, true, 10);
+
+    let svg = lattice.dump_svg(&SvgOptions::default());
+    assert!(svg.starts_with("<?xml"));
+    assert!(svg.ends_with("</svg>\n"));
+    assert_eq!(lattice.lattice.len(), svg.matches("<circle ").count());
+    assert_eq!(lattice.capacity, svg.matches("<path ").count());
+    assert_eq!(
+        lattice.capacity,
+        svg.matches("<title>").count() - lattice.lattice.len()
+    );
+    // trunk nodes are on the same baseline
+    let trunk_ys: Vec<&str> = svg
+        .lines()
+        .filter(|line| line.contains("fill=\"#000000\"/>"))
+        .map(|line| {
+            line.split("cy=\"")
+                .nth(1)
+                .unwrap()
+                .split('"')
+                .next()
+                .unwrap()
+        })
+        .collect();
+    assert_eq!(lattice.trunk.len(), trunk_ys.len());
+    assert!(trunk_ys.iter().all(|&y| y == trunk_ys[0]));
+
+    // highlighted search results without tooltips
+    let kmp = LatticeKMP::new(vec!["幹", "細胞"]);
+    let svg = lattice.dump_svg(&SvgOptions {
+        highlighted_paths: kmp.search(&lattice),
+        highlight_color: "green".to_string(),
+        tooltips: false,
+        ..SvgOptions::default()
+    });
+    assert!(svg.contains("stroke=\"green\""));
+    assert_eq!(lattice.lattice.len(), svg.matches("<title>").count());
+}
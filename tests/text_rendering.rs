@@ -0,0 +1,97 @@
+extern crate parattice;
+
+use parattice::Lattice;
+use parattice::PaRattice;
+
+use std::collections::HashMap;
+
+#[test]
+fn dump_ascii_test() {
+    let paradict = vec![
+        vec![
+            vec!["blood", "stem", "cell"],
+            vec!["造血", "幹", "細胞"],
+            vec!["hematopoietic", "stem", "cell"],
+        ],
+        vec![
+            vec!["造血", "幹", "細胞", "移植"],
+            vec!["hematopoietic", "stem", "cell", "transplantation"],
+        ],
+        vec![vec!["stem", "cell"], vec!["幹", "細胞"]],
+        vec![
+            vec!["幹", "細胞", "移植"],
+            vec!["rescue", "transplant"],
+            vec!["stem", "cell", "rescue"],
+        ],
+        vec![vec!["rescue"], vec!["救命"]],
+        vec![vec!["blood"], vec!["血液"]],
+    ];
+    let parattice = PaRattice::new(paradict);
+    let words = vec!["造血", "幹", "細胞", "移植"];
+    let lattice = parattice.get_lattice(&words, true, 10);
+    let ascii = lattice.dump_ascii();
+    let lines: Vec<&str> = ascii.lines().collect();
+    assert_eq!("造血 幹 細胞 移植", lines[0]);
+    assert_eq!("==== == ==== ====", lines[1]);
+    assert!(lines.contains(&"blood stem cell"));
+    assert!(lines.contains(&"     stem cell"));
+    assert!(lines.contains(&"        細胞 rescue"));
+
+    assert_eq!(
+        "Lattice { nodes: 17, edges: 32, trunk: [\"造血\", \"幹\", \"細胞\", \"移植\"] }",
+        format!("{:?}", lattice)
+    );
+    // labels are formatted with {:?}
+    let debug_lines: Vec<String> = format!("{:#?}", lattice)
+        .lines()
+        .map(|line| line.to_string())
+        .collect();
+    assert_eq!(lines.len() + 1, debug_lines.len());
+    assert_eq!("Lattice (17 nodes, 32 edges)", debug_lines[0]);
+    assert_eq!("\"造血\" \"幹\" \"細胞\" \"移植\"", debug_lines[1]);
+    assert!(debug_lines.contains(&"\"blood\" \"stem\" \"cell\"".to_string()));
+
+    // lattices that are not valid are dumped as they are
+    let mut broken = parattice.get_lattice(&words, true, 10);
+    broken.trunk.insert(1, 100);
+    let lines: Vec<String> = broken.dump_ascii().lines().map(String::from).collect();
+    assert_eq!(broken.lattice.len(), lines.len());
+    assert!(lines[0].starts_with("0 [trunk 0]: "));
+    assert!(lines[0].contains(" *造血->"));
+    assert!(lines[1].starts_with("1 [trunk 100]: "));
+    assert!(format!("{:?}", broken).contains("trunk: {0: 0, 1: 100, "));
+    assert!(format!("{:#?}", broken).starts_with("Lattice (17 nodes, 32 edges)\n0 [trunk 0]: "));
+
+    let empty: Lattice = Lattice {
+        lattice: vec![],
+        trunk: HashMap::new(),
+        capacity: 0,
+    };
+    assert_eq!("", empty.dump_ascii());
+    assert_eq!(
+        "Lattice { nodes: [], trunk: {}, capacity: 0 }",
+        format!("{:?}", empty)
+    );
+    assert_eq!("Lattice (0 nodes, 0 edges)\n", format!("{:#?}", empty));
+}
+
+#[test]
+fn dump_mermaid_test() {
+    let paradict = vec![
+        vec![vec!["blood", "stem", "cell"], vec!["造血", "幹", "細胞"]],
+        vec![vec!["blood"], vec!["\"血液\""]],
+    ];
+    let parattice = PaRattice::new(paradict);
+    let words = vec!["造血", "幹", "細胞"];
+    let lattice = parattice.get_lattice(&words, true, 10);
+    let mermaid = lattice.dump_mermaid();
+
+    assert!(mermaid.starts_with("flowchart LR\n"));
+    assert_eq!(lattice.lattice.len(), mermaid.matches("((").count());
+    assert_eq!(lattice.trunk.len() - 1, mermaid.matches(" ==>|").count());
+    assert_eq!(
+        lattice.capacity,
+        mermaid.matches(" ==>|").count() + mermaid.matches(" -->|").count()
+    );
+    assert!(mermaid.contains("|\"#quot;血液#quot;\"|"));
+}